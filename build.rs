@@ -2,5 +2,33 @@ use std::path::PathBuf;
 
 fn main() {
     println!("cargo:rerun-if-changed=src");
-    rust_sitter_tool::build_parsers(&PathBuf::from("src/grammar/mod.rs"));
+    let root = PathBuf::from("src/grammar/mod.rs");
+    rust_sitter_tool::build_parsers(&root);
+    write_token_table(&root);
+}
+
+// Rule name -> source text (keywords, punctuation) or regex (names, literals) of each
+// token, so syntax errors can list the expected tokens as they are written
+fn write_token_table(root: &std::path::Path) {
+    let mut strings = String::new();
+    let mut patterns = String::new();
+    for grammar in rust_sitter_tool::generate_grammars(root) {
+        let Some(rules) = grammar["rules"].as_object() else {
+            continue;
+        };
+        for (name, rule) in rules {
+            let value = rule["value"].as_str().unwrap_or_default();
+            match rule["type"].as_str() {
+                Some("STRING") => strings.push_str(&format!("({:?}, {:?}),\n", name, value)),
+                Some("PATTERN") => patterns.push_str(&format!("({:?}, {:?}),\n", name, value)),
+                _ => {}
+            }
+        }
+    }
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("tokens.rs");
+    let table = format!(
+        "pub const STRING_TOKENS: &[(&str, &str)] = &[\n{}];\npub const PATTERN_TOKENS: &[(&str, &str)] = &[\n{}];\n",
+        strings, patterns
+    );
+    std::fs::write(out, table).unwrap();
 }
@@ -13,6 +13,7 @@ use std::convert::TryFrom;
 #[derive(Clone, Debug, PartialEq)]
 pub enum RuntimeVal {
     Num(f64),
+    Int(i64),
     Str(String),
     Bool(bool),
     List(Vec<RuntimeVal>),
@@ -22,17 +23,35 @@ pub enum RuntimeVal {
 
 /// Kiro error type for Rust glue functions.
 /// The `name` field must match a Kiro error definition.
+/// `fields` carries the payload of errors declared with fields,
+/// e.g. `error HttpError { status: int url: str }`.
 #[derive(Clone, Debug)]
 pub struct KiroError {
     pub name: String,
+    pub fields: HashMap<String, RuntimeVal>,
 }
 
 impl KiroError {
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            fields: HashMap::new(),
         }
     }
+
+    /// Attaches a payload field:
+    /// `KiroError::new("HttpError").with_field("status", 404i64)`
+    pub fn with_field(mut self, name: &str, value: impl Into<RuntimeVal>) -> Self {
+        self.fields.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Reads a payload field back as a Rust value
+    pub fn field<T: TryFrom<RuntimeVal>>(&self, name: &str) -> Option<T> {
+        self.fields
+            .get(name)
+            .and_then(|v| T::try_from(v.clone()).ok())
+    }
 }
 
 impl std::fmt::Display for KiroError {
@@ -51,6 +70,12 @@ impl From<f64> for RuntimeVal {
     }
 }
 
+impl From<i64> for RuntimeVal {
+    fn from(v: i64) -> Self {
+        RuntimeVal::Int(v)
+    }
+}
+
 impl From<String> for RuntimeVal {
     fn from(v: String) -> Self {
         RuntimeVal::Str(v)
@@ -98,6 +123,13 @@ impl RuntimeVal {
         }
     }
 
+    pub fn as_int(&self) -> Result<i64, KiroError> {
+        match self {
+            RuntimeVal::Int(n) => Ok(*n),
+            _ => Err(KiroError::new("TypeError")),
+        }
+    }
+
     pub fn as_bool(&self) -> Result<bool, KiroError> {
         match self {
             RuntimeVal::Bool(b) => Ok(*b),
@@ -126,6 +158,16 @@ impl TryFrom<RuntimeVal> for f64 {
     }
 }
 
+impl TryFrom<RuntimeVal> for i64 {
+    type Error = KiroError;
+    fn try_from(val: RuntimeVal) -> Result<Self, Self::Error> {
+        match val {
+            RuntimeVal::Int(n) => Ok(n),
+            _ => Err(KiroError::new("TypeError")),
+        }
+    }
+}
+
 impl TryFrom<RuntimeVal> for bool {
     type Error = KiroError;
     fn try_from(val: RuntimeVal) -> Result<Self, Self::Error> {
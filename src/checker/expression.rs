@@ -0,0 +1,621 @@
+use super::{Checker, Signature, substitute};
+use crate::grammar::grammar::{
+    Expression, FieldInit, FieldNameVal, KiroType, LambdaDef, StructNameVal,
+};
+use std::collections::HashMap;
+
+impl Checker {
+    // Type of an expression, None when unknown. Errors inside it are recorded.
+    pub fn infer(&mut self, expr: &Expression) -> Option<KiroType> {
+        match expr {
+            Expression::Number(_) => Some(KiroType::Num),
+            Expression::StringLit(_) => Some(KiroType::Str),
+            Expression::BoolLit(_) => Some(KiroType::Bool),
+            Expression::NoneLit(_) => None,
+            Expression::Variable(v) | Expression::MoveExpr(_, v) => self.var_type(&v.value),
+            Expression::ErrorRef(name) => self.consts.get(&name.value).cloned(),
+            Expression::StructInit(name, _, inits, _) => self.infer_struct_init(name, inits),
+            Expression::EnumInit(name, _, variant, args) => {
+                let values = args.as_ref().map(|a| a.values.clone()).unwrap_or_default();
+                self.infer_enum_init(name, variant, &values)
+            }
+            Expression::Lambda(def) => Some(self.check_lambda(def)),
+            // The type all arm values agree on
+            Expression::Match(_, subject, _, arms, _) => {
+                let subject_type = self.infer(subject);
+                let types: Vec<Option<KiroType>> = arms
+                    .iter()
+                    .map(|arm| self.check_arm(arm, &subject_type, None))
+                    .collect();
+                let first = types.first().cloned().flatten()?;
+                types
+                    .iter()
+                    .all(|t| {
+                        t.as_ref()
+                            .is_some_and(|t| t.to_string() == first.to_string())
+                    })
+                    .then_some(first)
+            }
+            Expression::TupleLit(list) => {
+                let items: Vec<Option<KiroType>> =
+                    list.items.iter().map(|e| self.infer(e)).collect();
+                let items: Option<Vec<KiroType>> = items.into_iter().collect();
+                items.filter(|items| items.len() >= 2).map(KiroType::tuple)
+            }
+            Expression::ListInit(_, item, _, values, _) => {
+                for value in values {
+                    self.expect(value, item, &format!("Element of list {}", item));
+                }
+                Some(KiroType::List((), Box::new(item.clone())))
+            }
+            Expression::MapInit(_, key, value, _, pairs, _) => {
+                let map = KiroType::Map((), Box::new(key.clone()), Box::new(value.clone()));
+                for pair in pairs {
+                    self.expect(&pair.key, key, &format!("Key of {}", map));
+                    self.expect(&pair.value, value, &format!("Value of {}", map));
+                }
+                Some(map)
+            }
+            Expression::FieldAccess(target, _, field) => self.infer_field(target, field),
+            Expression::At(col, _, key) => self.infer_at(col, key),
+            Expression::Push(col, _, value) => {
+                match self.infer(col) {
+                    Some(KiroType::List(_, item)) => {
+                        self.expect(value, &item, &format!("Value pushed to list {}", item))
+                    }
+                    _ => {
+                        self.infer(value);
+                    }
+                }
+                None
+            }
+            Expression::Insert(col, _, args) => {
+                let col_type = self.infer(col);
+                match (col_type, args.items.as_slice()) {
+                    (Some(KiroType::List(_, item)), [index, value]) => {
+                        self.expect(index, &KiroType::Int, "List index");
+                        self.expect(value, &item, &format!("Value inserted in list {}", item));
+                    }
+                    (Some(KiroType::Map(_, k, v)), [key, value]) => {
+                        self.expect(key, &k, "Map key");
+                        self.expect(value, &v, "Map value");
+                    }
+                    (_, items) => {
+                        for item in items {
+                            self.infer(item);
+                        }
+                    }
+                }
+                None
+            }
+            Expression::Remove(col, _, key) => match self.infer(col) {
+                Some(KiroType::List(_, item)) => {
+                    self.expect(key, &KiroType::Int, "List index");
+                    Some(*item)
+                }
+                Some(KiroType::Map(_, k, v)) => {
+                    self.expect(key, &k, "Map key");
+                    Some(*v)
+                }
+                _ => {
+                    self.infer(key);
+                    None
+                }
+            },
+            Expression::Contains(col, _, value) => {
+                match self.infer(col) {
+                    Some(KiroType::List(_, item)) => {
+                        self.expect(value, &item, "Value searched in list")
+                    }
+                    Some(KiroType::Map(_, k, _)) => self.expect(value, &k, "Map key"),
+                    _ => {
+                        self.infer(value);
+                    }
+                }
+                Some(KiroType::Bool)
+            }
+            Expression::Pop(_, col) => match self.infer(col) {
+                Some(KiroType::List(_, item)) => Some(*item),
+                _ => None,
+            },
+            Expression::Clear(_, col) => {
+                self.infer(col);
+                None
+            }
+            Expression::AdrInit(_, t) => Some(KiroType::Adr((), Box::new(t.clone()))),
+            Expression::PipeInit(_, t) => Some(KiroType::Pipe((), Box::new(t.clone()))),
+            Expression::Take(_, channel) => match self.infer(channel) {
+                Some(KiroType::Pipe(_, inner)) => Some(*inner),
+                _ => None,
+            },
+            Expression::Len(_, col) => {
+                self.infer(col);
+                Some(KiroType::Int)
+            }
+            Expression::Ref(_, target) => {
+                self.infer(target).map(|t| KiroType::Adr((), Box::new(t)))
+            }
+            Expression::Deref(_, target) => match self.infer(target) {
+                Some(KiroType::Adr(_, inner)) => Some(*inner),
+                _ => None,
+            },
+            Expression::Call(func, args) => self.infer_call(func, &args.items),
+            Expression::RunCall(_, call) => {
+                self.infer(call);
+                None
+            }
+            Expression::SomeExpr(_, value) => {
+                self.infer(value).map(|t| KiroType::Opt((), Box::new(t)))
+            }
+            Expression::Try(_, operand) => self.infer(operand),
+            Expression::Not(_, operand) => {
+                self.expect(operand, &KiroType::Bool, "Operand of '!'");
+                Some(KiroType::Bool)
+            }
+            Expression::And(lhs, _, rhs) => {
+                self.expect(lhs, &KiroType::Bool, "Operand of '&&'");
+                self.expect(rhs, &KiroType::Bool, "Operand of '&&'");
+                Some(KiroType::Bool)
+            }
+            Expression::Or(lhs, _, rhs) => {
+                self.expect(lhs, &KiroType::Bool, "Operand of '||'");
+                self.expect(rhs, &KiroType::Bool, "Operand of '||'");
+                Some(KiroType::Bool)
+            }
+            Expression::Neg(_, operand) => {
+                let t = self.infer(operand)?;
+                if self.is_non_numeric(&t) {
+                    self.error(format!("Operator '-' needs a number, got {}.", t));
+                    return None;
+                }
+                Some(t)
+            }
+            Expression::Add(lhs, _, rhs) => self.infer_arithmetic("+", lhs, rhs),
+            Expression::Sub(lhs, _, rhs) => self.infer_arithmetic("-", lhs, rhs),
+            Expression::Mul(lhs, _, rhs) => self.infer_arithmetic("*", lhs, rhs),
+            Expression::Div(lhs, _, rhs) => self.infer_arithmetic("/", lhs, rhs),
+            Expression::Mod(lhs, _, rhs) => self.infer_arithmetic("%", lhs, rhs),
+            Expression::Eq(lhs, _, rhs) => self.infer_comparison("==", lhs, rhs),
+            Expression::Neq(lhs, _, rhs) => self.infer_comparison("!=", lhs, rhs),
+            Expression::Gt(lhs, _, rhs) => self.infer_comparison(">", lhs, rhs),
+            Expression::Lt(lhs, _, rhs) => self.infer_comparison("<", lhs, rhs),
+            Expression::Geq(lhs, _, rhs) => self.infer_comparison(">=", lhs, rhs),
+            Expression::Leq(lhs, _, rhs) => self.infer_comparison("<=", lhs, rhs),
+            Expression::Range(start, _, end) | Expression::RangeInclusive(start, _, end) => {
+                for bound in [start, end] {
+                    if let Some(t) = self.infer(bound).filter(|t| self.is_non_numeric(t)) {
+                        self.error(format!("Range bound needs a number, got {}.", t));
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    // A value headed for a declared type: argument, field, element, return value,
+    // assignment. `what` names the destination in the message.
+    pub fn expect(&mut self, expr: &Expression, expected: &KiroType, what: &str) {
+        // Each arm's value goes to the destination
+        if let Expression::Match(_, subject, _, arms, _) = expr {
+            let subject_type = self.infer(subject);
+            for arm in arms {
+                self.check_arm(arm, &subject_type, Some((expected, what)));
+            }
+            return;
+        }
+        // `some x` checks x against the optional's inner type
+        if let (KiroType::Opt(_, inner), Expression::SomeExpr(_, value)) = (expected, expr) {
+            self.expect(value, inner, what);
+            return;
+        }
+        if let (Some(types), Expression::TupleLit(list)) = (expected.tuple_items(), expr)
+            && types.len() == list.items.len()
+        {
+            for (item, t) in list.items.iter().zip(&types) {
+                self.expect(item, t, what);
+            }
+            return;
+        }
+        let Some(actual) = self.infer(expr) else {
+            return;
+        };
+        // Integral literals are ints where an int is expected
+        let wants_int = match expected {
+            KiroType::Opt(_, inner) => matches!(**inner, KiroType::Int),
+            t => matches!(t, KiroType::Int),
+        };
+        if wants_int && expr.int_literal().is_some() {
+            return;
+        }
+        if !self.fits(expected, &actual) {
+            self.mismatch(what, expected, &actual);
+        }
+    }
+
+    // Local variables, then constants, then named functions as values
+    fn var_type(&self, name: &str) -> Option<KiroType> {
+        if let Some(t) = self.env.get(name) {
+            return t.clone();
+        }
+        if let Some(t) = self.consts.get(name) {
+            return Some(t.clone());
+        }
+        let sig = self.functions.get(name)?;
+        Some(KiroType::func(
+            sig.params.iter().map(|(_, t)| t.clone()).collect(),
+            sig.return_type.clone(),
+        ))
+    }
+
+    fn infer_struct_init(&mut self, name: &StructNameVal, inits: &[FieldInit]) -> Option<KiroType> {
+        let Some(fields) = self.structs.get(&name.value).cloned() else {
+            for init in inits {
+                self.infer(&init.value);
+            }
+            return None;
+        };
+        let kind = match self.error_types.contains(&name.value) {
+            true => "Error",
+            false => "Struct",
+        };
+        for init in inits {
+            match fields.iter().find(|f| f.name.value == init.name.value) {
+                Some(field) => {
+                    let outer = self.focus.replace(field.name.value.clone());
+                    self.expect(
+                        &init.value,
+                        &field.field_type,
+                        &format!("Field '{}' of '{}'", field.name.value, name.value),
+                    );
+                    self.focus = outer;
+                }
+                None => {
+                    let message = format!(
+                        "{} '{}' has no field '{}'.",
+                        kind, name.value, init.name.value
+                    );
+                    self.error_on(&init.name.value, message);
+                    self.infer(&init.value);
+                }
+            }
+        }
+        // An error value is not a struct; it is only seen through handlers
+        match kind {
+            "Error" => None,
+            _ => Some(KiroType::Custom(name.clone())),
+        }
+    }
+
+    fn infer_enum_init(
+        &mut self,
+        name: &StructNameVal,
+        variant: &StructNameVal,
+        values: &[Expression],
+    ) -> Option<KiroType> {
+        let payload = self.enums.get(&name.value).and_then(|variants| {
+            variants
+                .iter()
+                .find(|(v, _)| *v == variant.value)
+                .map(|(_, payload)| payload.clone())
+        });
+        match payload {
+            Some(types) if types.len() == values.len() => {
+                let what = format!("Payload of '{}.{}'", name.value, variant.value);
+                for (value, t) in values.iter().zip(&types) {
+                    self.expect(value, t, &what);
+                }
+            }
+            Some(types) => {
+                self.error(format!(
+                    "Variant '{}.{}' expects {} values, got {}.",
+                    name.value,
+                    variant.value,
+                    types.len(),
+                    values.len()
+                ));
+            }
+            None => {
+                for value in values {
+                    self.infer(value);
+                }
+            }
+        }
+        self.enums
+            .contains_key(&name.value)
+            .then(|| KiroType::Custom(name.clone()))
+    }
+
+    // Type of a function literal; its body is checked against its own return type
+    fn check_lambda(&mut self, def: &LambdaDef) -> KiroType {
+        self.env.push();
+        for p in &def.params {
+            self.env
+                .declare(p.name.clone(), Some(p.command_type.clone()));
+        }
+        let declared = def
+            .return_type
+            .clone()
+            .filter(|t| !matches!(t, KiroType::Void))
+            .map(|t| ("Return value of closure".to_string(), t));
+        let outer = std::mem::replace(&mut self.return_type, declared);
+        self.check_block(&def.body);
+        self.return_type = outer;
+        self.env.pop();
+        KiroType::func(
+            def.params.iter().map(|p| p.command_type.clone()).collect(),
+            def.return_type.clone(),
+        )
+    }
+
+    // The struct behind a value, looking through `adr`, with its type arguments bound
+    fn struct_of(&self, t: &KiroType) -> Option<(String, HashMap<String, KiroType>)> {
+        match t {
+            KiroType::Adr(_, inner) => self.struct_of(inner),
+            KiroType::Custom(name) => Some((name.value.clone(), HashMap::new())),
+            KiroType::Generic(name, _, args, _) => {
+                let params = self.struct_params.get(&name.value)?;
+                let bindings = params.iter().cloned().zip(args.iter().cloned()).collect();
+                Some((name.value.clone(), bindings))
+            }
+            _ => None,
+        }
+    }
+
+    fn infer_field(&mut self, target: &Expression, field: &FieldNameVal) -> Option<KiroType> {
+        let target_type = self.infer(target)?;
+        let Some((name, bindings)) = self.struct_of(&target_type) else {
+            if self.is_non_numeric(&target_type) || target_type.is_payload() {
+                let message = format!("Cannot access field '{}' on {}.", field.value, target_type);
+                self.error_on(&field.value, message);
+            }
+            return None;
+        };
+        let fields = self.structs.get(&name)?;
+        match fields.iter().find(|f| f.name.value == field.value) {
+            Some(f) => Some(substitute(&f.field_type, &bindings)),
+            None => {
+                let is_method = self
+                    .methods
+                    .get(&name)
+                    .is_some_and(|m| m.contains_key(&field.value));
+                if !is_method {
+                    let message = format!("Struct '{}' has no field '{}'.", name, field.value);
+                    self.error_on(&field.value, message);
+                }
+                None
+            }
+        }
+    }
+
+    fn infer_at(&mut self, col: &Expression, key: &Expression) -> Option<KiroType> {
+        match self.infer(col) {
+            Some(KiroType::List(_, item)) => {
+                self.expect(key, &KiroType::Int, "List index");
+                Some(*item)
+            }
+            Some(KiroType::Map(_, k, v)) => {
+                self.expect(key, &k, "Map key");
+                Some(*v)
+            }
+            Some(t) => {
+                let indexable = matches!(t, KiroType::Adr(..) | KiroType::Opt(..))
+                    || !self.is_non_numeric(&t) && !t.is_payload();
+                if !indexable {
+                    self.error(format!("Cannot use 'at' on {}; it needs a list or map.", t));
+                }
+                self.infer(key);
+                None
+            }
+            None => {
+                self.infer(key);
+                None
+            }
+        }
+    }
+
+    fn infer_arithmetic(
+        &mut self,
+        op: &str,
+        lhs: &Expression,
+        rhs: &Expression,
+    ) -> Option<KiroType> {
+        let (lt, rt) = (self.infer(lhs), self.infer(rhs));
+        // `+` joins a string with any value
+        if op == "+" && (matches!(lt, Some(KiroType::Str)) || matches!(rt, Some(KiroType::Str))) {
+            return Some(KiroType::Str);
+        }
+        for t in [&lt, &rt].into_iter().flatten() {
+            if let KiroType::Opt(..) = t {
+                self.error(
+                    "Optional value used without checking it. Use `on (x is some v)`.".to_string(),
+                );
+                return None;
+            }
+            if self.is_non_numeric(t) {
+                self.error(format!("Operator '{}' needs numbers, got {}.", op, t));
+                return None;
+            }
+        }
+        self.check_numeric_mix(lhs, &lt, rhs, &rt)?;
+        // An int literal takes the type of the other side
+        if lhs.int_literal().is_some() {
+            rt.or(lt)
+        } else {
+            lt.or(rt)
+        }
+    }
+
+    fn infer_comparison(
+        &mut self,
+        op: &str,
+        lhs: &Expression,
+        rhs: &Expression,
+    ) -> Option<KiroType> {
+        let (lt, rt) = (self.infer(lhs), self.infer(rhs));
+        let ordered = !matches!(op, "==" | "!=");
+        if let (Some(l), Some(r)) = (&lt, &rt) {
+            let numeric = |t: &KiroType| matches!(t, KiroType::Num | KiroType::Int);
+            if numeric(l) && numeric(r) {
+                self.check_numeric_mix(lhs, &lt, rhs, &rt);
+            } else if !self.fits(l, r) && !self.fits(r, l) {
+                self.error(format!("Cannot compare {} with {} using '{}'.", l, r, op));
+            } else if ordered && matches!(l, KiroType::Bool) {
+                self.error(format!(
+                    "Operator '{}' needs numbers or strings, got bool.",
+                    op
+                ));
+            }
+        }
+        Some(KiroType::Bool)
+    }
+
+    // int and num never mix implicitly, except an integral literal next to an int
+    fn check_numeric_mix(
+        &mut self,
+        lhs: &Expression,
+        lt: &Option<KiroType>,
+        rhs: &Expression,
+        rt: &Option<KiroType>,
+    ) -> Option<()> {
+        let mixed = match (lt, rt) {
+            (Some(KiroType::Int), Some(KiroType::Num)) => rhs.int_literal().is_none(),
+            (Some(KiroType::Num), Some(KiroType::Int)) => lhs.int_literal().is_none(),
+            _ => false,
+        };
+        if mixed {
+            self.error("Cannot mix int and num. Convert with int(x) or num(x).".to_string());
+            return None;
+        }
+        Some(())
+    }
+
+    fn infer_call(&mut self, func: &Expression, args: &[Expression]) -> Option<KiroType> {
+        match func {
+            // Variables holding function values shadow named functions
+            Expression::Variable(v) if self.env.contains(&v.value) => {
+                let t = self.env.get(&v.value).cloned().flatten();
+                self.call_value(&v.value, t, args)
+            }
+            // Conversion builtins: int(x), num(x)
+            Expression::Variable(v)
+                if (v.value == "int" || v.value == "num")
+                    && !self.functions.contains_key(&v.value) =>
+            {
+                for arg in args {
+                    self.infer(arg);
+                }
+                if args.len() != 1 {
+                    self.error(format!(
+                        "Function '{}' expects 1 argument, got {}.",
+                        v.value,
+                        args.len()
+                    ));
+                }
+                Some(match v.value.as_str() {
+                    "int" => KiroType::Int,
+                    _ => KiroType::Num,
+                })
+            }
+            Expression::Variable(v) if self.functions.contains_key(&v.value) => {
+                let sig = self.functions[&v.value].clone();
+                self.check_args("Function", &v.value, &sig, args);
+                sig.return_type
+            }
+            Expression::FieldAccess(target, _, field) => {
+                self.infer_method_call(target, field, args)
+            }
+            Expression::Variable(_) => {
+                for arg in args {
+                    self.infer(arg);
+                }
+                None
+            }
+            other => {
+                let t = self.infer(other);
+                self.call_value(&other.describe(), t, args)
+            }
+        }
+    }
+
+    // Method, interface method, or function stored in a struct field
+    fn infer_method_call(
+        &mut self,
+        target: &Expression,
+        method: &FieldNameVal,
+        args: &[Expression],
+    ) -> Option<KiroType> {
+        let target_type = self.infer(target);
+        let Some((name, bindings)) = target_type.as_ref().and_then(|t| self.struct_of(t)) else {
+            for arg in args {
+                self.infer(arg);
+            }
+            return None;
+        };
+        let sig = self
+            .methods
+            .get(&name)
+            .or_else(|| self.interfaces.get(&name))
+            .and_then(|m| m.get(&method.value))
+            .cloned();
+        if let Some(sig) = sig {
+            let label = format!("{}.{}", name, method.value);
+            self.check_args("Method", &label, &sig, args);
+            return sig.return_type.map(|t| substitute(&t, &bindings));
+        }
+        let field = self
+            .structs
+            .get(&name)
+            .and_then(|fields| fields.iter().find(|f| f.name.value == method.value))
+            .map(|f| substitute(&f.field_type, &bindings));
+        if field.is_none() && self.structs.contains_key(&name) {
+            let message = format!("Struct '{}' has no method '{}'.", name, method.value);
+            self.error_on(&method.value, message);
+        }
+        let label = format!("{}.{}", name, method.value);
+        self.call_value(&label, field, args)
+    }
+
+    // Call of a function value, checked against its `fn(...)` type when known
+    fn call_value(
+        &mut self,
+        name: &str,
+        t: Option<KiroType>,
+        args: &[Expression],
+    ) -> Option<KiroType> {
+        let Some(KiroType::Func(_, _, _, params, _, ret)) = t else {
+            for arg in args {
+                self.infer(arg);
+            }
+            return None;
+        };
+        let sig = Signature {
+            params: params
+                .into_iter()
+                .enumerate()
+                .map(|(i, t)| ((i + 1).to_string(), t))
+                .collect(),
+            return_type: ret.map(|r| *r.ret),
+        };
+        self.check_args("Function", name, &sig, args);
+        sig.return_type
+    }
+
+    fn check_args(&mut self, kind: &str, name: &str, sig: &Signature, args: &[Expression]) {
+        if args.len() != sig.params.len() {
+            self.error(format!(
+                "{} '{}' expects {} arguments, got {}.",
+                kind,
+                name,
+                sig.params.len(),
+                args.len()
+            ));
+            for arg in args {
+                self.infer(arg);
+            }
+            return;
+        }
+        for (arg, (param, t)) in args.iter().zip(&sig.params) {
+            self.expect(arg, t, &format!("Argument '{}' of '{}'", param, name));
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use crate::diagnostics::{Diagnostic, Kind};
+use crate::grammar::grammar::{self, FieldDef, FuncParam, KiroType, Statement};
+use crate::scope::Scopes;
+use std::collections::{HashMap, HashSet};
+
+pub mod expression;
+pub mod statement;
+
+// Static type checks on a parsed file, run before the interpreter or the compiler sees it.
+// Types are only inferred where they are certain. Anything else (type parameters,
+// interfaces, module values, variables read from the caller's scope) is unknown and
+// accepted everywhere, so the backends keep their own checks.
+
+// Inferred types of local bindings, by (start of the statement binding it, name). The
+// compiler declares its variables with these rather than inferring them a second time.
+pub type Types = HashMap<(usize, String), KiroType>;
+
+// Declared parameters and return type of a function or method
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub params: Vec<(String, KiroType)>,
+    pub return_type: Option<KiroType>,
+}
+
+impl Signature {
+    fn new(params: &[FuncParam], return_type: Option<KiroType>) -> Self {
+        Signature {
+            params: params
+                .iter()
+                .map(|p| (p.name.clone(), p.command_type.clone()))
+                .collect(),
+            return_type,
+        }
+    }
+}
+
+pub struct Checker {
+    pub env: Scopes<Option<KiroType>>,
+    pub functions: HashMap<String, Signature>,
+    pub structs: HashMap<String, Vec<FieldDef>>, // Structs, and errors with payload fields
+    pub struct_params: HashMap<String, Vec<String>>, // Generic struct -> type parameters
+    pub enums: HashMap<String, Vec<(String, Vec<KiroType>)>>, // name -> (variant, payload)
+    pub methods: HashMap<String, HashMap<String, Signature>>, // struct -> method -> sig
+    pub interfaces: HashMap<String, HashMap<String, Signature>>,
+    pub error_types: HashSet<String>,
+    pub consts: HashMap<String, KiroType>,
+    pub return_type: Option<(String, KiroType)>, // "Return value of 'f'" and its declared type
+    pub span: (usize, usize),                    // Statement being checked
+    pub focus: Option<String>,                   // Name inside it that errors point at
+    pub errors: Vec<Diagnostic>,                 // Located at the statement containing the mistake
+    pub types: HashMap<(usize, String), Option<KiroType>>, // None: bound with different types
+}
+
+// Checks a whole file; every mistake found is reported, in source order
+pub fn check(program: &grammar::Program) -> Result<Types, Vec<Diagnostic>> {
+    let mut checker = Checker::new();
+    // Functions and types may be used before their definition
+    for stmt in &program.statements {
+        checker.declare(&stmt.value);
+    }
+    for stmt in &program.statements {
+        checker.check_statement(stmt);
+    }
+    match checker.errors.is_empty() {
+        true => Ok(checker
+            .types
+            .into_iter()
+            .filter_map(|(key, t)| Some((key, t?)))
+            .collect()),
+        false => Err(checker.errors),
+    }
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Checker {
+            env: Scopes::new(),
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            struct_params: HashMap::new(),
+            enums: HashMap::new(),
+            methods: HashMap::new(),
+            interfaces: HashMap::new(),
+            error_types: crate::grammar::BUILTIN_ERRORS
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            consts: HashMap::new(),
+            return_type: None,
+            span: (0, 0),
+            focus: None,
+            errors: Vec::new(),
+            types: HashMap::new(),
+        }
+    }
+
+    // Declares a local binding and records its type for the compiler. A name bound twice
+    // by one statement (e.g. in two match arms) keeps a type only if both agree.
+    pub fn bind(&mut self, name: String, t: Option<KiroType>) {
+        if let Some(t) = &t {
+            self.types
+                .entry((self.span.0, name.clone()))
+                .and_modify(|known| {
+                    if known
+                        .as_ref()
+                        .is_some_and(|k| k.to_string() != t.to_string())
+                    {
+                        *known = None;
+                    }
+                })
+                .or_insert_with(|| Some(t.clone()));
+        }
+        self.env.declare(name, t);
+    }
+
+    pub fn error(&mut self, message: String) {
+        let error = self.located(message);
+        self.errors.push(error);
+    }
+
+    // An error pointing at `name` inside the statement
+    pub fn error_on(&mut self, name: &str, message: String) {
+        let error = self.located(message).focus(name);
+        self.errors.push(error);
+    }
+
+    fn located(&self, message: String) -> Diagnostic {
+        let error = Diagnostic::new(Kind::Type, message, self.span);
+        match &self.focus {
+            Some(name) => error.focus(name.clone()),
+            None => error,
+        }
+    }
+
+    pub fn mismatch(&mut self, what: &str, expected: &KiroType, actual: &KiroType) {
+        let message = format!("{}: expected {}, got {}.", what, expected, actual);
+        let mut error = self.located(message);
+        match (expected, actual) {
+            (KiroType::Int, KiroType::Num) => error = error.help("Convert with int(x)."),
+            (KiroType::Num, KiroType::Int) => error = error.help("Convert with num(x)."),
+            _ => {}
+        }
+        self.errors.push(error);
+    }
+
+    // Records the names a definition introduces
+    pub fn declare(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::FunctionDef(def) => {
+                let sig = Signature::new(&def.params, def.return_type.clone());
+                self.functions.insert(def.name.clone(), sig);
+            }
+            Statement::RustFnDecl(def) => {
+                let sig = Signature::new(&def.params, Some(def.return_type.clone()));
+                self.functions.insert(def.name.clone(), sig);
+            }
+            Statement::StructDef(def) => {
+                let params = def
+                    .type_params
+                    .as_ref()
+                    .map(|p| p.names.iter().map(|n| n.value.clone()).collect())
+                    .unwrap_or_default();
+                self.structs
+                    .insert(def.name.value.clone(), def.fields.clone());
+                self.struct_params.insert(def.name.value.clone(), params);
+            }
+            Statement::EnumDef(def) => {
+                let variants = def
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        let payload = v.payload.as_ref().map(|p| p.types.clone());
+                        (v.name.value.clone(), payload.unwrap_or_default())
+                    })
+                    .collect();
+                self.enums.insert(def.name.value.clone(), variants);
+            }
+            Statement::ImplBlock(block) => {
+                let methods = self
+                    .methods
+                    .entry(block.struct_name().to_string())
+                    .or_default();
+                for m in &block.methods {
+                    let params: Vec<FuncParam> = m.params.iter().map(|p| p.param.clone()).collect();
+                    methods.insert(
+                        m.name.clone(),
+                        Signature::new(&params, m.return_type.clone()),
+                    );
+                }
+            }
+            Statement::InterfaceDef(def) => {
+                let sigs = def
+                    .methods
+                    .iter()
+                    .map(|m| {
+                        let params: Vec<FuncParam> =
+                            m.params.iter().map(|p| p.param.clone()).collect();
+                        let ret = m.ret.as_ref().map(|r| (*r.ret).clone());
+                        (m.name.clone(), Signature::new(&params, ret))
+                    })
+                    .collect();
+                self.interfaces.insert(def.name.value.clone(), sigs);
+            }
+            Statement::ErrorDef { name, fields, .. } => {
+                self.error_types.insert(name.clone());
+                if let Some(f) = fields.as_ref().filter(|f| !f.fields.is_empty()) {
+                    self.structs.insert(name.clone(), f.fields.clone());
+                }
+            }
+            Statement::ConstDecl {
+                name, const_type, ..
+            } => {
+                self.consts.insert(name.clone(), const_type.clone());
+            }
+            Statement::Documented { item, .. } => self.declare(&item.clone().into_statement()),
+            _ => {}
+        }
+    }
+
+    // A struct or enum declared in this file; other names are type parameters or
+    // interfaces, which stand for many types
+    pub fn is_named_type(&self, name: &str) -> bool {
+        self.structs.contains_key(name) || self.enums.contains_key(name)
+    }
+
+    // Known not to be an int or num
+    pub fn is_non_numeric(&self, t: &KiroType) -> bool {
+        match t {
+            KiroType::Num | KiroType::Int => false,
+            KiroType::Custom(name) | KiroType::Generic(name, ..) => self.is_named_type(&name.value),
+            _ => true,
+        }
+    }
+
+    // A value of type `actual` may be used where `expected` is declared
+    pub fn fits(&self, expected: &KiroType, actual: &KiroType) -> bool {
+        use KiroType::*;
+        match (expected, actual) {
+            (Opt(_, e), Opt(_, a)) => self.fits(e, a),
+            (Opt(_, e), a) => self.fits(e, a),
+            (Custom(n) | Generic(n, ..), _) | (_, Custom(n) | Generic(n, ..))
+                if !self.is_named_type(&n.value) =>
+            {
+                true
+            }
+            (Custom(e) | Generic(e, ..), Custom(a) | Generic(a, ..)) => e.value == a.value,
+            (Num, Num) | (Int, Int) | (Str, Str) | (Bool, Bool) | (Void, Void) => true,
+            // `adr void` is an opaque address
+            (Adr(_, e), Adr(..)) if matches!(**e, Void) => true,
+            (List(_, e), List(_, a)) | (Adr(_, e), Adr(_, a)) | (Pipe(_, e), Pipe(_, a)) => {
+                self.fits(e, a)
+            }
+            (Map(_, ek, ev), Map(_, ak, av)) => self.fits(ek, ak) && self.fits(ev, av),
+            (Tuple(..), Tuple(..)) => {
+                let (e, a) = (
+                    expected.tuple_items().unwrap(),
+                    actual.tuple_items().unwrap(),
+                );
+                e.len() == a.len() && e.iter().zip(&a).all(|(e, a)| self.fits(e, a))
+            }
+            (Func(..), Func(..)) => true,
+            _ => false,
+        }
+    }
+}
+
+// Replaces type parameters, e.g. A -> num in a field of Pair<num, str>
+pub fn substitute(t: &KiroType, bindings: &HashMap<String, KiroType>) -> KiroType {
+    let sub = |t: &KiroType| Box::new(substitute(t, bindings));
+    match t {
+        KiroType::Custom(name) => bindings
+            .get(&name.value)
+            .cloned()
+            .unwrap_or_else(|| t.clone()),
+        KiroType::Adr(_, inner) => KiroType::Adr((), sub(inner)),
+        KiroType::Pipe(_, inner) => KiroType::Pipe((), sub(inner)),
+        KiroType::Opt(_, inner) => KiroType::Opt((), sub(inner)),
+        KiroType::List(_, inner) => KiroType::List((), sub(inner)),
+        KiroType::Map(_, k, v) => KiroType::Map((), sub(k), sub(v)),
+        KiroType::Tuple(..) => KiroType::tuple(
+            t.tuple_items()
+                .unwrap()
+                .iter()
+                .map(|t| substitute(t, bindings))
+                .collect(),
+        ),
+        KiroType::Generic(name, _, args, _) => KiroType::Generic(
+            name.clone(),
+            (),
+            args.iter().map(|t| substitute(t, bindings)).collect(),
+            (),
+        ),
+        _ => t.clone(),
+    }
+}
@@ -0,0 +1,378 @@
+use super::Checker;
+use crate::exhaustive::check_pattern;
+use crate::grammar::grammar::{
+    self, ArmBody, Expression, FunctionDef, KiroType, LoopBinding, MatchArm, MethodDef, Pattern,
+    Statement, StructNameVal,
+};
+use crate::scope::Scopes;
+use rust_sitter::Spanned;
+
+impl Checker {
+    pub fn check_statement(&mut self, stmt: &Spanned<Statement>) {
+        let outer = std::mem::replace(&mut self.span, stmt.span);
+        self.check(&stmt.value);
+        self.span = outer;
+    }
+
+    fn check(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::StructDef(_)
+            | Statement::EnumDef(_)
+            | Statement::InterfaceDef(_)
+            | Statement::ErrorDef { .. }
+            | Statement::RustFnDecl(_) => self.declare(stmt),
+            Statement::ImplBlock(block) => {
+                self.declare(stmt);
+                for method in &block.methods {
+                    self.check_method(block.struct_name(), method);
+                }
+            }
+            Statement::FunctionDef(def) => {
+                self.declare(stmt);
+                self.check_function(def);
+            }
+            Statement::Documented { item, .. } => self.check(&item.clone().into_statement()),
+            Statement::VarDecl { ident, value, .. } => {
+                let t = self.infer(value);
+                self.bind(ident.clone(), t);
+            }
+            Statement::ConstDecl {
+                name,
+                const_type,
+                value,
+                ..
+            } => {
+                self.expect(value, const_type, &format!("Constant '{}'", name));
+                self.consts.insert(name.clone(), const_type.clone());
+            }
+            Statement::VarTuple { names, value, .. } => {
+                let items = self.infer(value).and_then(|t| t.tuple_items());
+                self.bind_tuple(&names.values(), items);
+            }
+            Statement::AssignStmt { lhs, rhs, .. } => self.check_assign(lhs, rhs),
+            Statement::CompoundAssign { lhs, op, rhs } => {
+                if let Statement::AssignStmt { lhs, rhs, .. } =
+                    op.clone().desugar(lhs.clone(), rhs.clone())
+                {
+                    self.check_assign(&lhs, &rhs);
+                }
+            }
+            Statement::On {
+                condition,
+                some_check,
+                body,
+                else_clause,
+                error_clauses,
+                ..
+            } => {
+                let condition_type = self.infer(condition);
+                self.env.push();
+                if let Some(check) = some_check {
+                    let inner = match condition_type {
+                        Some(KiroType::Opt(_, inner)) => Some(*inner),
+                        _ => None,
+                    };
+                    self.bind(check.binding.clone(), inner);
+                }
+                self.check_block(body);
+                self.env.pop();
+                if let Some(off) = else_clause {
+                    self.check_block(&off.body);
+                }
+                let mut next = error_clauses.as_ref();
+                while let Some(list) = next {
+                    let clause = &list.first;
+                    self.env.push();
+                    if let (Some(binding), Some(name)) = (&clause.binding, &clause.error_type) {
+                        let error_type = KiroType::Custom(StructNameVal {
+                            value: name.clone(),
+                        });
+                        self.bind(binding.name.clone(), Some(error_type));
+                    }
+                    self.check_block(&clause.body);
+                    self.env.pop();
+                    next = list.rest.as_deref();
+                }
+            }
+            Statement::LoopOn {
+                condition, body, ..
+            } => {
+                self.infer(condition);
+                self.check_block(body);
+            }
+            Statement::LoopIter {
+                iterator,
+                iterable,
+                step,
+                filter,
+                body,
+                else_clause,
+                ..
+            } => {
+                let (index, item) = self.loop_item_types(iterable);
+                if let Some(step) = step {
+                    self.expect(&step.value, &KiroType::Int, "Loop step");
+                }
+                self.env.push();
+                match iterator {
+                    LoopBinding::Name(name) => self.bind(name.clone(), item),
+                    LoopBinding::Tuple(names) => {
+                        let items = item.and_then(|t| t.tuple_items());
+                        self.bind_tuple(&names.values(), items);
+                    }
+                    // Index and element of a list, key and value of a map
+                    LoopBinding::Pair(first, _, second) => {
+                        let (first_type, second_type) = match item {
+                            Some(t) if index.is_none() => match t.tuple_items() {
+                                Some(kv) if kv.len() == 2 => {
+                                    (Some(kv[0].clone()), Some(kv[1].clone()))
+                                }
+                                _ => (None, None),
+                            },
+                            t => (index, t),
+                        };
+                        self.bind(first.clone(), first_type);
+                        self.bind(second.value.clone(), second_type);
+                    }
+                }
+                if let Some(filter) = filter {
+                    self.infer(&filter.condition);
+                }
+                self.check_block(body);
+                if let Some(off) = else_clause {
+                    self.check_block(&off.body);
+                }
+                self.env.pop();
+            }
+            Statement::Give(_, channel, value) => match self.infer(channel) {
+                Some(KiroType::Pipe(_, inner)) => {
+                    self.expect(value, &inner, &format!("Value given to pipe {}", inner))
+                }
+                _ => {
+                    self.infer(value);
+                }
+            },
+            Statement::Close(_, channel) => {
+                self.infer(channel);
+            }
+            Statement::Return(_, Some(value)) => match self.return_type.clone() {
+                Some((what, t)) => self.expect(value, &t, &what),
+                None => {
+                    self.infer(value);
+                    self.error(
+                        "Cannot return a value: the function declares no return value.".to_string(),
+                    );
+                }
+            },
+            Statement::Return(_, None) | Statement::Break(..) | Statement::Continue(..) => {}
+            Statement::Defer(_, body) => self.check_block(body),
+            Statement::Import { module_name, .. } => {
+                self.env.declare(module_name.clone(), None);
+            }
+            Statement::ExprStmt(expr) | Statement::Print(_, expr) => {
+                self.infer(expr);
+            }
+        }
+    }
+
+    pub fn check_block(&mut self, block: &grammar::Block) {
+        self.env.push();
+        for stmt in &block.statements {
+            self.check_statement(stmt);
+        }
+        self.env.pop();
+    }
+
+    // Named functions may run on the caller's variables, so only their parameters are
+    // known inside
+    fn check_function(&mut self, def: &FunctionDef) {
+        let mut env = Scopes::new();
+        for p in &def.params {
+            env.declare(p.name.clone(), Some(p.command_type.clone()));
+        }
+        let what = format!("Return value of '{}'", def.name);
+        self.check_body(env, what, &def.return_type, &def.body);
+    }
+
+    fn check_method(&mut self, struct_name: &str, def: &MethodDef) {
+        let mut env = Scopes::new();
+        let receiver = KiroType::Custom(StructNameVal {
+            value: struct_name.to_string(),
+        });
+        env.declare("self".to_string(), Some(receiver));
+        for p in &def.params {
+            env.declare(p.param.name.clone(), Some(p.param.command_type.clone()));
+        }
+        let what = format!("Return value of '{}.{}'", struct_name, def.name);
+        self.check_body(env, what, &def.return_type, &def.body);
+    }
+
+    fn check_body(
+        &mut self,
+        env: Scopes<Option<KiroType>>,
+        what: String,
+        return_type: &Option<KiroType>,
+        body: &grammar::Block,
+    ) {
+        let declared = return_type
+            .clone()
+            .filter(|t| !matches!(t, KiroType::Void))
+            .map(|t| (what, t));
+        let outer_env = std::mem::replace(&mut self.env, env);
+        let outer_ret = std::mem::replace(&mut self.return_type, declared);
+        self.check_block(body);
+        self.env = outer_env;
+        self.return_type = outer_ret;
+    }
+
+    fn check_assign(&mut self, lhs: &Expression, rhs: &Expression) {
+        match lhs {
+            Expression::Variable(v) => match self.env.get(&v.value).cloned() {
+                Some(Some(t)) => self.expect(rhs, &t, &format!("Assignment to '{}'", v.value)),
+                Some(None) => {
+                    self.infer(rhs);
+                }
+                // Assigning an unknown name declares it
+                None => {
+                    let t = self.infer(rhs);
+                    self.bind(v.value.clone(), t);
+                }
+            },
+            _ => match self.infer(lhs) {
+                Some(t) => self.expect(rhs, &t, &format!("Assignment to {}", lhs.describe())),
+                None => {
+                    self.infer(rhs);
+                }
+            },
+        }
+    }
+
+    // `var (a, b) = ...` and `loop (a, b) in ...`
+    fn bind_tuple(&mut self, names: &[String], items: Option<Vec<KiroType>>) {
+        if let Some(items) = &items
+            && items.len() != names.len()
+        {
+            self.error(format!(
+                "Cannot destructure a {}-tuple into {} names.",
+                items.len(),
+                names.len()
+            ));
+        }
+        for (i, name) in names.iter().enumerate() {
+            let t = items.as_ref().and_then(|items| items.get(i).cloned());
+            self.bind(name.clone(), t);
+        }
+    }
+
+    // (index, item) types of a loop: index is the position in a list or string, and
+    // None for maps, whose items are (key, value) tuples
+    fn loop_item_types(&mut self, iterable: &Expression) -> (Option<KiroType>, Option<KiroType>) {
+        if let Expression::Range(start, _, end) | Expression::RangeInclusive(start, _, end) =
+            iterable
+        {
+            let mut ints = false;
+            for bound in [start, end] {
+                match self.infer(bound) {
+                    Some(KiroType::Int) => ints = true,
+                    Some(t) if self.is_non_numeric(&t) => {
+                        self.error(format!("Range bound needs a number, got {}.", t));
+                    }
+                    _ => {}
+                }
+            }
+            return (Some(KiroType::Int), ints.then_some(KiroType::Int));
+        }
+        match self.infer(iterable) {
+            Some(KiroType::List(_, item)) => (Some(KiroType::Int), Some(*item)),
+            Some(KiroType::Str) => (Some(KiroType::Int), Some(KiroType::Str)),
+            Some(KiroType::Map(_, k, v)) => (None, Some(KiroType::tuple(vec![*k, *v]))),
+            Some(t) if self.is_non_numeric(&t) || t.is_payload() => {
+                self.error(format!(
+                    "Cannot loop over {}; it needs a range, list, map or string.",
+                    t
+                ));
+                (None, None)
+            }
+            _ => (None, None),
+        }
+    }
+
+    // `expected` is the declared type the arm values are headed for, and its description;
+    // returns the type of the arm's value
+    pub fn check_arm(
+        &mut self,
+        arm: &MatchArm,
+        subject: &Option<KiroType>,
+        expected: Option<(&KiroType, &str)>,
+    ) -> Option<KiroType> {
+        self.env.push();
+        self.bind_pattern(&arm.pattern, subject);
+        if let Some(guard) = &arm.guard {
+            self.infer(&guard.condition);
+        }
+        let value_type = match &arm.body {
+            ArmBody::Block(block) => {
+                self.check_block(block);
+                None
+            }
+            ArmBody::Value(value) => match expected {
+                Some((t, what)) => {
+                    self.expect(value, t, what);
+                    Some(t.clone())
+                }
+                None => self.infer(value),
+            },
+        };
+        self.env.pop();
+        value_type
+    }
+
+    fn bind_pattern(&mut self, pattern: &Pattern, subject: &Option<KiroType>) {
+        if let Some(t) = subject
+            && let Err(e) = check_pattern(pattern, t, &self.structs, &self.enums)
+        {
+            match e.focus {
+                Some(name) => self.error_on(&name, e.message),
+                None => self.error(e.message),
+            }
+        }
+        match pattern {
+            Pattern::Binding(name) => self.bind(name.value.clone(), subject.clone()),
+            Pattern::Variant(enum_name, _, variant, Some(bindings)) => {
+                let payload = self.enums.get(&enum_name.value).and_then(|variants| {
+                    variants
+                        .iter()
+                        .find(|(v, _)| *v == variant.value)
+                        .map(|(_, payload)| payload.clone())
+                });
+                let names = bindings.values();
+                if let Some(types) = payload.as_ref().filter(|t| t.len() != names.len()) {
+                    self.error(format!(
+                        "Variant '{}.{}' carries {} values, the pattern binds {}.",
+                        enum_name.value,
+                        variant.value,
+                        types.len(),
+                        names.len()
+                    ));
+                }
+                for (i, name) in names.into_iter().enumerate() {
+                    let t = payload.as_ref().and_then(|types| types.get(i).cloned());
+                    self.bind(name, t);
+                }
+            }
+            Pattern::Struct(name, _, bindings, _) => {
+                let fields = self.structs.get(&name.value).cloned();
+                for binding in bindings {
+                    let t = fields.as_ref().and_then(|fields| {
+                        fields
+                            .iter()
+                            .find(|f| f.name.value == binding.value)
+                            .map(|f| f.field_type.clone())
+                    });
+                    self.bind(binding.value.clone(), t);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use super::Compiler;
+use crate::diagnostics::Kind;
+use crate::grammar::grammar::{Expression, KiroType};
+
+impl Compiler {
+    // Key and value types of a collection. Lists are indexed by int, which is also assumed
+    // when the collection type is unknown.
+    fn collection_types(&self, col: &Expression) -> (KiroType, Option<KiroType>) {
+        match self.static_type(col) {
+            Some(KiroType::Map(_, key, value)) => (*key, Some(*value)),
+            Some(KiroType::List(_, item)) => (KiroType::Int, Some(*item)),
+            _ => (KiroType::Int, None),
+        }
+    }
+
+    fn compile_item(&mut self, expr: Expression, item_type: Option<&KiroType>) -> String {
+        match item_type {
+            Some(t) => self.compile_expecting(expr, t),
+            None => self.compile_expr(expr),
+        }
+    }
+
+    // Collection commands. The mutating ones work on the collection's place, like an
+    // assignment; `insert`, `remove` and `pop` give a failable value.
+    pub fn compile_collection_op(&mut self, expr: Expression) -> String {
+        match expr {
+            Expression::Push(col, _, value) => {
+                let (_, item_type) = self.collection_types(&col);
+                let value_str = self.compile_item(*value, item_type.as_ref());
+                format!("{}.push({})", self.compile_place(*col), value_str)
+            }
+            Expression::Insert(col, _, args) => {
+                let Ok([key, value]) = <[Expression; 2]>::try_from(args.items) else {
+                    self.error(
+                        Kind::Compile,
+                        "'insert' expects (index, value) or (key, value).",
+                    );
+                    return String::new();
+                };
+                let (key_type, item_type) = self.collection_types(&col);
+                let key_str = self.compile_expecting(key, &key_type);
+                let value_str = self.compile_item(value, item_type.as_ref());
+                format!(
+                    "{}.kiro_insert({}, {})",
+                    self.compile_place(*col),
+                    key_str,
+                    value_str
+                )
+            }
+            Expression::Remove(col, _, key) => {
+                let (key_type, _) = self.collection_types(&col);
+                let key_str = self.compile_expecting(*key, &key_type);
+                format!("{}.kiro_remove({})", self.compile_place(*col), key_str)
+            }
+            // Lists are searched by value, maps by key
+            Expression::Contains(col, _, item) => {
+                let item_type = match self.static_type(&col) {
+                    Some(KiroType::List(_, item)) => Some(*item),
+                    Some(KiroType::Map(_, key, _)) => Some(*key),
+                    _ => None,
+                };
+                let item_str = self.compile_item(*item, item_type.as_ref());
+                format!("{}.kiro_contains({})", self.compile_checked(*col), item_str)
+            }
+            Expression::Pop(_, col) => format!("{}.kiro_pop()", self.compile_place(*col)),
+            Expression::Clear(_, col) => format!("{}.clear()", self.compile_place(*col)),
+            _ => unreachable!("not a collection command"),
+        }
+    }
+
+    // list at index = value / map at key = value. An index out of range is IndexOutOfBounds.
+    pub fn compile_set_at(
+        &mut self,
+        col: Expression,
+        key: Expression,
+        value_str: String,
+    ) -> String {
+        let (key_type, _) = self.collection_types(&col);
+        let key_str = self.compile_expecting(key, &key_type);
+        let set = format!(
+            "{}.kiro_set_at({}, {})",
+            self.compile_place(col),
+            key_str,
+            value_str
+        );
+        format!("{};", self.raise_on_error(set))
+    }
+
+    // A collection access that fails with a Kiro error: a failable function returns the
+    // error, anywhere else the program stops with it
+    pub fn raise_on_error(&self, call: String) -> String {
+        match self.in_failable_fn {
+            true => format!("{}?", call),
+            false => format!("{}.unwrap_or_else(|e| kiro_unhandled(e))", call),
+        }
+    }
+}
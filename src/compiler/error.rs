@@ -0,0 +1,204 @@
+use super::types::compile_type;
+use super::{Compiler, ErrorInfo};
+use crate::diagnostics::Kind;
+use crate::grammar::grammar::{self, FieldDef, FieldInit, KiroType};
+use std::collections::HashMap;
+
+// Helper giving a plain error, tagged with its name
+pub fn plain_error_helper(name: &str, desc: &str) -> String {
+    format!(
+        "pub fn kiro_error_{name}() -> anyhow::Error {{ kiro_error(\"{name}\", anyhow::Error::msg({desc:?})) }}"
+    )
+}
+
+impl Compiler {
+    // Errors declared in this file. Payload fields are also registered as struct fields,
+    // so a bound error's fields are typed like a struct's.
+    pub fn register_error(&mut self, name: &str, fields: &Option<grammar::ErrorFields>) {
+        let fields = fields
+            .as_ref()
+            .map(|f| f.fields.clone())
+            .unwrap_or_default();
+        if let Some(f) = fields.iter().find(|f| !f.field_type.is_payload()) {
+            let message = format!(
+                "Error field '{}' must be num, int, str or bool.",
+                f.name.value
+            );
+            self.error_on(Kind::Compile, &f.name.value, message);
+        }
+        self.add_error(name, String::new(), fields);
+    }
+
+    // Errors declared by an imported module, which handlers here can name too. Errors
+    // declared here take precedence.
+    pub fn import_errors(&mut self, module: &str, errors: &HashMap<String, Vec<FieldDef>>) {
+        for (name, fields) in errors {
+            if !self.errors.contains_key(name) {
+                self.add_error(name, format!("{}::", module), fields.clone());
+            }
+        }
+    }
+
+    fn add_error(&mut self, name: &str, path: String, fields: Vec<FieldDef>) {
+        if !fields.is_empty() {
+            self.structs.insert(name.to_string(), fields.clone());
+        }
+        self.errors
+            .insert(name.to_string(), ErrorInfo { path, fields });
+    }
+
+    // What an importer of this file learns about its errors
+    pub fn declared_errors(&self) -> HashMap<String, Vec<FieldDef>> {
+        self.errors
+            .iter()
+            .filter(|(_, info)| info.path.is_empty())
+            .map(|(name, info)| (name.clone(), info.fields.clone()))
+            .collect()
+    }
+
+    fn error_info(&mut self, name: &str) -> ErrorInfo {
+        match self.errors.get(name) {
+            Some(info) => info.clone(),
+            None => {
+                self.error_on(
+                    Kind::Compile,
+                    name,
+                    format!("Unknown error type '{}'.", name),
+                );
+                ErrorInfo {
+                    path: String::new(),
+                    fields: Vec::new(),
+                }
+            }
+        }
+    }
+
+    fn has_payload(&self, name: &str) -> bool {
+        self.errors
+            .get(name)
+            .is_some_and(|info| !info.fields.is_empty())
+    }
+
+    // A plain error is a helper giving an `anyhow::Error`. An error with fields is a
+    // struct carried inside the `anyhow::Error`; `kiro_from` takes it back out, or
+    // rebuilds it from the fields a host function attached to its `KiroError`.
+    pub fn compile_error_def(&mut self, name: &str, desc: &str) -> String {
+        if !self.has_payload(name) {
+            return plain_error_helper(name, desc);
+        }
+        let fields = self.errors[name].fields.clone();
+        let field_strs: Vec<String> = fields
+            .iter()
+            .map(|f| format!("pub {}: {}", f.name.value, compile_type(&f.field_type)))
+            .collect();
+        let host_strs: Vec<String> = fields
+            .iter()
+            .map(|f| {
+                format!(
+                    "{0}: host.and_then(|h| h.field(\"{0}\")).expect(\"Error '{1}' is missing field '{0}'\")",
+                    f.name.value, name
+                )
+            })
+            .collect();
+        format!(
+            "#[derive(Clone, Debug)]\npub struct {name} {{ {fields} }}\nimpl KiroGet for {name} {{ type Inner = Self; fn kiro_get<R>(&self, f: impl FnOnce(&Self::Inner) -> R) -> R {{ f(self) }} }}\nimpl std::fmt::Display for {name} {{ fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {{ write!(f, \"{{}}\", {desc:?}) }} }}\nimpl std::error::Error for {name} {{}}\nimpl {name} {{ pub fn kiro_from(err: &anyhow::Error) -> Self {{ if let Some(e) = err.downcast_ref::<Self>() {{ return e.clone(); }} let host = err.downcast_ref::<kiro_runtime::KiroError>(); Self {{ {host} }} }} }}",
+            fields = field_strs.join(", "),
+            host = host_strs.join(", ")
+        )
+    }
+
+    // NotFound / HttpError { status: 404, url: u } -> Err(...)
+    pub fn compile_error_init(&mut self, name: &str, inits: Vec<FieldInit>) -> String {
+        let ErrorInfo { path, fields } = self.error_info(name);
+        if let Some(f) = inits
+            .iter()
+            .find(|f| !fields.iter().any(|d| d.name.value == f.name.value))
+        {
+            let message = format!("Error '{}' has no field '{}'.", name, f.name.value);
+            self.error_on(Kind::Compile, &f.name.value, message);
+            return String::new();
+        }
+        if let Some(d) = fields
+            .iter()
+            .find(|d| !inits.iter().any(|f| f.name.value == d.name.value))
+        {
+            let message = format!("Error '{}' is missing field '{}'.", name, d.name.value);
+            self.error_on(Kind::Compile, name, message);
+            return String::new();
+        }
+        if fields.is_empty() {
+            return format!("Err({}kiro_error_{}())", path, name);
+        }
+        let init_strs: Vec<String> = inits
+            .into_iter()
+            .map(|f| {
+                let field_type = fields
+                    .iter()
+                    .find(|d| d.name.value == f.name.value)
+                    .map(|d| d.field_type.clone())
+                    .unwrap();
+                format!(
+                    "{}: {}",
+                    f.name.value,
+                    self.compile_expecting(f.value, &field_type)
+                )
+            })
+            .collect();
+        format!(
+            "Err(kiro_error(\"{1}\", anyhow::Error::new({0}{1} {{ {2} }})))",
+            path,
+            name,
+            init_strs.join(", ")
+        )
+    }
+
+    pub fn compile_error_ref(&mut self, name: &str) -> String {
+        if self.has_payload(name) {
+            let message = format!("Error '{}' carries fields.", name);
+            let help = format!("Create it with {} {{ ... }}.", name);
+            self.error_help(Kind::Compile, message, &help);
+        }
+        format!("Err({}kiro_error_{}())", self.error_info(name).path, name)
+    }
+
+    // Condition selecting the handler for `error Name`; the name must be a known error
+    pub fn error_check(&mut self, name: &str) -> String {
+        if !self.errors.contains_key(name) {
+            let message = format!("Unknown error type '{}' in handler.", name);
+            self.error_on(Kind::Compile, name, message);
+        }
+        format!("kiro_error_is(&__kiro_err, \"{}\")", name)
+    }
+
+    // `error Name as e { ... }`: declares `e` for the handler body and gives the Rust
+    // statement that binds it
+    pub fn bind_error(&mut self, clause: &grammar::ErrorClause) -> String {
+        let Some(binding) = &clause.binding else {
+            return String::new();
+        };
+        let name = match &clause.error_type {
+            Some(name) if self.has_payload(name) => name,
+            Some(name) => {
+                let message = format!("Error '{}' has no fields to bind with 'as'.", name);
+                self.error_on(Kind::Compile, &binding.name, message);
+                return String::new();
+            }
+            None => {
+                self.error_help(
+                    Kind::Compile,
+                    "'as' needs an error type.",
+                    "error Name as e { ... }",
+                );
+                return String::new();
+            }
+        };
+        let error_type = KiroType::Custom(grammar::StructNameVal {
+            value: name.clone(),
+        });
+        self.declare_var(&binding.name, false, Some(error_type));
+        format!(
+            "let {} = {}{}::kiro_from(&__kiro_err);",
+            binding.name, self.errors[name].path, name
+        )
+    }
+}
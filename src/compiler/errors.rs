@@ -0,0 +1,23 @@
+use super::Compiler;
+use crate::diagnostics::{Diagnostic, Kind};
+
+impl Compiler {
+    // Records an error at the statement being compiled. Compilation goes on so one build
+    // reports every error; the generated code is thrown away.
+    pub fn error(&mut self, kind: Kind, message: impl Into<String>) {
+        self.diagnostics
+            .push(Diagnostic::new(kind, message, self.span));
+    }
+
+    // Same, underlining `name` inside the statement
+    pub fn error_on(&mut self, kind: Kind, name: &str, message: impl Into<String>) {
+        self.diagnostics
+            .push(Diagnostic::new(kind, message, self.span).focus(name));
+    }
+
+    // Same, with a suggested fix
+    pub fn error_help(&mut self, kind: Kind, message: impl Into<String>, help: &str) {
+        self.diagnostics
+            .push(Diagnostic::new(kind, message, self.span).help(help));
+    }
+}
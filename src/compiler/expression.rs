@@ -1,89 +1,151 @@
 use super::Compiler;
 
+use crate::diagnostics::Kind;
 use crate::grammar::grammar::{self, Expression};
+use crate::grammar::strings::StrPart;
 
 impl Compiler {
     pub fn compile_expr(&mut self, expr: Expression) -> String {
         match expr {
-            Expression::MoveExpr(_, ident) => {
-                let name = ident.value;
+            Expression::MoveExpr(_, ident) => self.compile_move(&ident.value),
 
-                // 1. Purity Check
-                if self.in_pure_context {
-                    panic!("Compiler Error: 'move' is forbidden in pure functions.");
-                }
+            // Error type reference - generate Err(kiro_error_Name())
+            Expression::ErrorRef(name) if self.consts.contains_key(&name.value) => {
+                format!("(*{}).clone()", name.value)
+            }
+            Expression::ErrorRef(name) => self.compile_error_ref(&name.value),
 
-                // 2. Mutable Check
-                if let Some(info) = self.known_vars.get(&name) {
-                    if !info.is_mutable {
-                        panic!("Compiler Error: Cannot move immutable variable '{}'.", name);
-                    }
-                } else {
-                    panic!("Compiler Error: Variable '{}' not found.", name);
+            Expression::Variable(v) if self.consts.contains_key(&v.value) => {
+                format!("(*{}).clone()", v.value)
+            }
+            Expression::Variable(v) => {
+                // Named function used as a value
+                if !self.known_vars.contains(&v.value) && self.functions.contains_key(&v.value) {
+                    return self.compile_function_ref(&v.value);
                 }
 
-                // 3. Mark as Moved
-                self.moved_vars.insert(name.clone());
-
-                // 4. Generate Rust: std::mem::take(&mut var)
-                // This swaps the value with default (void/empty)
-                format!("std::mem::take(&mut {})", name)
-            }
+                // var_types holds every binding in scope, parameters included
+                if self.var_types.is_out_of_scope(&v.value) {
+                    let message = format!("Use of out-of-scope variable '{}'.", v.value);
+                    self.error_on(Kind::Compile, &v.value, message);
+                }
 
-            Expression::Variable(v) => {
-                // Check if this is an error type (starts with uppercase)
-                if v.value
-                    .chars()
-                    .next()
-                    .map(|c| c.is_uppercase())
-                    .unwrap_or(false)
-                {
-                    // Assume it's an error type - generate Err(kiro_error_Name())
-                    return format!("Err(kiro_error_{}())", v.value);
+                if let Some(captures) = &mut self.captures {
+                    captures.insert(v.value.clone());
                 }
 
                 // Strict Purity: Ban capturing external variables
-                if self.in_pure_context && !self.pure_scope_params.contains(&v.value) {
-                    panic!(
-                        "Compiler Error: Pure function cannot capture external variable '{}'. Only parameters and local variables are allowed.",
+                if self.in_pure_context
+                    && self.captures.is_some()
+                    && !self.pure_scope_params.contains(&v.value)
+                    && self
+                        .known_vars
+                        .get(&v.value)
+                        .is_some_and(|info| info.is_mutable)
+                {
+                    let message = format!(
+                        "Pure closure cannot capture mutable variable '{}'.",
                         v.value
                     );
-                }
-
-                // Move Check: Ensure variable hasn't been moved
-                if self.moved_vars.contains(&v.value) {
-                    panic!(
-                        "Compiler Error: Variable '{}' was moved and cannot be used.",
+                    self.error_on(Kind::Purity, &v.value, message);
+                } else if self.in_pure_context && !self.pure_scope_params.contains(&v.value) {
+                    let message = format!(
+                        "Pure function cannot capture external variable '{}'. Only parameters and local variables are allowed.",
                         v.value
                     );
+                    self.error_on(Kind::Purity, &v.value, message);
                 }
 
+                // Move Check: Ensure variable hasn't been moved
+                self.check_not_moved(&v.value);
+
                 // Default Behavior: Clone variable access to ensure Copy Semantics
                 format!("({}).clone()", v.value)
             }
 
             // 2. Compile Struct Init
+            Expression::StructInit(name, _, fields, _) if self.errors.contains_key(&name.value) => {
+                self.compile_error_init(&name.value, fields)
+            }
             Expression::StructInit(name, _, fields, _) => {
+                let declared = self.structs.get(&name.value).cloned().unwrap_or_default();
                 let init_strs: Vec<String> = fields
                     .iter()
-                    .map(|f| format!("{}: {}", f.name.value, self.compile_expr(f.value.clone())))
+                    .map(|f| {
+                        let value = match declared.iter().find(|d| d.name.value == f.name.value) {
+                            Some(d) => self.compile_expecting(f.value.clone(), &d.field_type),
+                            None => self.compile_expr(f.value.clone()),
+                        };
+                        format!("{}: {}", f.name.value, value)
+                    })
                     .collect();
 
                 format!("{} {{ {} }}", name.value, init_strs.join(", "))
             }
 
+            // Compile Enum Variant Construction
+            Expression::EnumInit(enum_name, _, variant, args) => {
+                let Some(variants) = self.enums.get(&enum_name.value) else {
+                    let message = format!("Unknown enum '{}'.", enum_name.value);
+                    self.error_on(Kind::Compile, &enum_name.value, message);
+                    return String::new();
+                };
+                let Some(&(_, arity)) = variants.iter().find(|(name, _)| *name == variant.value)
+                else {
+                    let message =
+                        format!("'{}' has no variant '{}'.", enum_name.value, variant.value);
+                    self.error_on(Kind::Compile, &variant.value, message);
+                    return String::new();
+                };
+                let values = args.map(|a| a.values).unwrap_or_default();
+                if values.len() != arity {
+                    let message = format!(
+                        "Variant '{}.{}' expects {} value(s), got {}.",
+                        enum_name.value,
+                        variant.value,
+                        arity,
+                        values.len()
+                    );
+                    self.error_on(Kind::Compile, &variant.value, message);
+                    return String::new();
+                }
+
+                if values.is_empty() {
+                    format!("{}::{}", enum_name.value, variant.value)
+                } else {
+                    let value_strs: Vec<String> =
+                        values.into_iter().map(|v| self.compile_expr(v)).collect();
+                    format!(
+                        "{}::{}({})",
+                        enum_name.value,
+                        variant.value,
+                        value_strs.join(", ")
+                    )
+                }
+            }
+
+            Expression::Lambda(def) => self.compile_lambda(def),
+
+            Expression::Match(_, subject, _, arms, _) => {
+                self.compile_match(*subject, arms, false, None)
+            }
+
             // 3. Compile Field Access
             Expression::FieldAccess(target, _, field) => {
                 // Check if the target is a known module (e.g., "math")
-                if let Expression::Variable(v) = &*target {
-                    if self.imported_modules.contains(&v.value) {
-                        return format!("{}::{}", v.value, field.value);
+                if let Expression::Variable(v) = &*target
+                    && self.imported_modules.contains(&v.value)
+                {
+                    // Upper-case exports are constants
+                    if field.value.starts_with(|c: char| c.is_ascii_uppercase()) {
+                        return format!("(*{}::{}).clone()", v.value, field.value);
                     }
+                    return format!("{}::{}", v.value, field.value);
                 }
 
                 format!(
                     "{}.kiro_get(|v| v.{}.clone())",
-                    self.compile_expr(*target),
+                    self.compile_checked(*target),
                     field.value
                 )
             }
@@ -97,11 +159,41 @@ impl Compiler {
                 }
             }
 
-            Expression::StringLit(s) => format!("String::from({})", s.value),
+            // Interpolated strings lower to format!; the decoded text is re-escaped for it
+            Expression::StringLit(s) => {
+                let parts = s.parts().unwrap_or_else(|e| {
+                    self.error(Kind::Compile, e);
+                    Vec::new()
+                });
+                let mut plain = String::new();
+                let mut fmt = String::new();
+                let mut args = Vec::new();
+                for part in parts {
+                    match part {
+                        StrPart::Lit(text) => {
+                            fmt.push_str(&text.replace('{', "{{").replace('}', "}}"));
+                            plain.push_str(&text);
+                        }
+                        StrPart::Expr(expr, spec) => {
+                            fmt.push_str(&format!("{{{}}}", spec.to_rust()));
+                            self.note_displayed(&expr);
+                            args.push(self.compile_checked(expr));
+                        }
+                    }
+                }
+                // Debug formatting yields a valid Rust string literal for the decoded text
+                if args.is_empty() {
+                    format!("String::from({:?})", plain)
+                } else {
+                    format!("format!({:?}, {})", fmt, args.join(", "))
+                }
+            }
             Expression::BoolLit(b) => match b {
                 grammar::BoolVal::True(_) => "true".to_string(),
                 grammar::BoolVal::False(_) => "false".to_string(),
             },
+            Expression::NoneLit(_) => "None".to_string(),
+            Expression::SomeExpr(_, value) => format!("Some({})", self.compile_expr(*value)),
 
             // Adr Init (Lazy / Void)
             Expression::AdrInit(_, inner) => {
@@ -132,7 +224,7 @@ impl Compiler {
 
             Expression::Take(_, channel) => {
                 if self.in_pure_context {
-                    panic!("Pure Function Error: 'take' is forbidden.");
+                    self.error(Kind::Purity, "'take' is forbidden in pure functions.");
                 }
                 let ch = self.compile_expr(*channel);
                 format!("{}.rx.recv().await.unwrap()", ch)
@@ -151,97 +243,203 @@ impl Compiler {
                 )
             }
 
-            Expression::ListInit(_, _, _, items, _) => {
-                let elems: Vec<String> =
-                    items.iter().map(|e| self.compile_expr(e.clone())).collect();
+            // Empty literals name their type, which rustc can't infer from later uses
+            Expression::ListInit(_, item_type, _, items, _) if items.is_empty() => {
+                format!(
+                    "Vec::<{}>::new()",
+                    crate::compiler::types::compile_type(&item_type)
+                )
+            }
+            Expression::ListInit(_, item_type, _, items, _) => {
+                let elems: Vec<String> = items
+                    .iter()
+                    .map(|e| self.compile_expecting(e.clone(), &item_type))
+                    .collect();
                 format!("vec![{}]", elems.join(", "))
             }
 
-            Expression::MapInit(_, _, _, _, pairs, _) => {
+            // Tuples lower to native Rust tuples
+            Expression::TupleLit(list) => {
+                if list.items.len() < 2 {
+                    self.error(Kind::Compile, "A tuple needs at least two elements.");
+                }
+                let elems: Vec<String> = list
+                    .items
+                    .into_iter()
+                    .map(|e| self.compile_expr(e))
+                    .collect();
+                format!("({})", elems.join(", "))
+            }
+
+            Expression::MapInit(_, key_type, value_type, _, pairs, _) if pairs.is_empty() => {
+                format!(
+                    "std::collections::HashMap::<{}, {}>::new()",
+                    crate::compiler::types::compile_type(&key_type),
+                    crate::compiler::types::compile_type(&value_type)
+                )
+            }
+            Expression::MapInit(_, key_type, value_type, _, pairs, _) => {
                 let entries: Vec<String> = pairs
                     .iter()
                     .map(|p| {
                         format!(
                             "({}, {})",
-                            self.compile_expr(p.key.clone()),
-                            self.compile_expr(p.value.clone())
+                            self.compile_expecting(p.key.clone(), &key_type),
+                            self.compile_expecting(p.value.clone(), &value_type)
                         )
                     })
                     .collect();
                 format!("std::collections::HashMap::from([{}])", entries.join(", "))
             }
 
+            // Lists are indexed by int; maps by their key type
             Expression::At(col, _, key) => {
-                let col_str = self.compile_expr(*col);
-                let key_str = self.compile_expr(*key);
-                format!("{}.kiro_at({})", col_str, key_str)
+                let key_type = match self.static_type(&col) {
+                    Some(grammar::KiroType::Map(_, k, _)) => *k,
+                    _ => grammar::KiroType::Int,
+                };
+                let col_str = self.compile_checked(*col);
+                let key_str = self.compile_expecting(*key, &key_type);
+                self.raise_on_error(format!("{}.kiro_at({})", col_str, key_str))
             }
 
-            Expression::Push(list, _, val) => {
-                let list_str = self.compile_expr(*list);
-                let val_str = self.compile_expr(*val);
-                format!("{}.push({})", list_str, val_str)
-            }
+            expr @ (Expression::Push(..)
+            | Expression::Insert(..)
+            | Expression::Remove(..)
+            | Expression::Contains(..)
+            | Expression::Pop(..)
+            | Expression::Clear(..)) => self.compile_collection_op(expr),
 
-            Expression::Add(lhs, _, rhs) => format!(
-                "({}.kiro_add({}))",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
+            Expression::Add(lhs, _, rhs) => {
+                let (l, r, _) = self.compile_operands(*lhs, *rhs);
+                format!("({}.kiro_add({}))", l, r)
+            }
             Expression::Len(_, expr) => {
-                format!("{}.kiro_len()", self.compile_expr(*expr))
+                format!("{}.kiro_len()", self.compile_checked(*expr))
             }
-            Expression::Sub(lhs, _, rhs) => format!(
-                "({} - {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
-            Expression::Mul(lhs, _, rhs) => format!(
-                "({} * {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
-            Expression::Div(lhs, _, rhs) => format!(
-                "({} / {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
-            Expression::Eq(lhs, _, rhs) => format!(
-                "({} == {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
-            Expression::Neq(lhs, _, rhs) => format!(
-                "({} != {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
-            Expression::Gt(lhs, _, rhs) => format!(
-                "({} > {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
-            Expression::Lt(lhs, _, rhs) => format!(
-                "({} < {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
-            ),
-            Expression::Geq(lhs, _, rhs) => format!(
-                "({} >= {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
+            Expression::Sub(lhs, _, rhs) => match self.compile_operands(*lhs, *rhs) {
+                (l, r, true) => format!("({}.kiro_sub({}))", l, r),
+                (l, r, false) => format!("({} - {})", l, r),
+            },
+            Expression::Mul(lhs, _, rhs) => match self.compile_operands(*lhs, *rhs) {
+                (l, r, true) => format!("({}.kiro_mul({}))", l, r),
+                (l, r, false) => format!("({} * {})", l, r),
+            },
+            Expression::Div(lhs, _, rhs) => match self.compile_operands(*lhs, *rhs) {
+                (l, r, true) => format!("({}.kiro_div({}))", l, r),
+                (l, r, false) => format!("({} / {})", l, r),
+            },
+            Expression::Eq(lhs, _, rhs) => {
+                let (l, r, _) = self.compile_operands(*lhs, *rhs);
+                format!("({} == {})", l, r)
+            }
+            Expression::Neq(lhs, _, rhs) => {
+                let (l, r, _) = self.compile_operands(*lhs, *rhs);
+                format!("({} != {})", l, r)
+            }
+            Expression::Gt(lhs, _, rhs) => {
+                let (l, r, _) = self.compile_operands(*lhs, *rhs);
+                format!("({} > {})", l, r)
+            }
+            Expression::Mod(lhs, _, rhs) => match self.compile_operands(*lhs, *rhs) {
+                (l, r, true) => format!("({}.kiro_rem({}))", l, r),
+                (l, r, false) => format!("({} % {})", l, r),
+            },
+            Expression::Neg(_, operand) => match self.static_type(&operand) {
+                Some(grammar::KiroType::Int) => {
+                    format!("({}.kiro_neg())", self.compile_expr(*operand))
+                }
+                _ => format!("(-{})", self.compile_checked(*operand)),
+            },
+            Expression::Not(_, operand) => {
+                format!("(!({}).kiro_truthy())", self.compile_checked(*operand))
+            }
+            // try x -> x?, which needs the enclosing fn to return a Result
+            Expression::Try(_, operand) => {
+                if !self.in_failable_fn {
+                    self.error(
+                        Kind::Compile,
+                        "'try' can only be used in a failable function (-> T!).",
+                    );
+                }
+                match *operand {
+                    // A failable variable is used by value: its `anyhow::Error` can't be cloned
+                    Expression::Variable(v) if self.known_vars.contains(&v.value) => {
+                        self.check_not_moved(&v.value);
+                        format!("{}?", v.value)
+                    }
+                    operand => format!("({})?", self.compile_expr(operand)),
+                }
+            }
+            Expression::And(lhs, _, rhs) => format!(
+                "(({}).kiro_truthy() && ({}).kiro_truthy())",
+                self.compile_checked(*lhs),
+                self.compile_checked(*rhs)
             ),
-            Expression::Leq(lhs, _, rhs) => format!(
-                "({} <= {})",
-                self.compile_expr(*lhs),
-                self.compile_expr(*rhs)
+            Expression::Or(lhs, _, rhs) => format!(
+                "(({}).kiro_truthy() || ({}).kiro_truthy())",
+                self.compile_checked(*lhs),
+                self.compile_checked(*rhs)
             ),
-            Expression::Range(start, _, end) => {
-                let start_str = self.compile_expr(*start);
-                let end_str = self.compile_expr(*end);
-                format!("(({} as i64)..({} as i64))", start_str, end_str)
+            Expression::Lt(lhs, _, rhs) => {
+                let (l, r, _) = self.compile_operands(*lhs, *rhs);
+                format!("({} < {})", l, r)
+            }
+            Expression::Geq(lhs, _, rhs) => {
+                let (l, r, _) = self.compile_operands(*lhs, *rhs);
+                format!("({} >= {})", l, r)
+            }
+            Expression::Leq(lhs, _, rhs) => {
+                let (l, r, _) = self.compile_operands(*lhs, *rhs);
+                format!("({} <= {})", l, r)
             }
-            Expression::Call(func, _, args, _) => {
+            Expression::Range(start, _, end) => self.compile_range(*start, *end, false),
+            Expression::RangeInclusive(start, _, end) => self.compile_range(*start, *end, true),
+            Expression::Call(func, grammar::ParenList { items: args, .. }) => {
+                // Method calls on struct values (modules are resolved below)
+                if let Expression::FieldAccess(target, _, field) = &*func
+                    && !matches!(&**target, Expression::Variable(m) if self.imported_modules.contains(&m.value))
+                    && let Some(info) = self.lookup_method(&field.value)
+                {
+                    let Expression::FieldAccess(target, _, field) = *func else {
+                        unreachable!()
+                    };
+                    return self.compile_method_call(*target, &field.value, info, args);
+                }
+
+                // Conversion builtins: int(x), num(x)
+                if let Expression::Variable(v) = &*func
+                    && self.is_conversion(&v.value)
+                {
+                    if args.len() != 1 {
+                        let message = format!("'{}' expects 1 argument.", v.value);
+                        self.error_on(Kind::Compile, &v.value, message);
+                        return String::new();
+                    }
+                    let arg = args.into_iter().next().unwrap();
+                    // Integral literals convert exactly, without a trip through num
+                    if let (true, Some(i)) = (v.value == "int", arg.int_literal()) {
+                        return format!("({}i64)", i);
+                    }
+                    let arg = self.compile_expr(arg);
+                    return format!("({}).kiro_{}()", arg, v.value);
+                }
+
+                // Calls through variables, fields or call results use the function value
+                let is_named = match &*func {
+                    Expression::Variable(v) => {
+                        !self.known_vars.contains(&v.value) && self.functions.contains_key(&v.value)
+                    }
+                    Expression::FieldAccess(target, _, _) => matches!(
+                        &**target,
+                        Expression::Variable(m) if self.imported_modules.contains(&m.value)
+                    ),
+                    _ => false,
+                };
+                if !is_named {
+                    return self.compile_value_call(*func, args);
+                }
+
                 // Determine if we need .await (Access func by reference BEFORE move)
                 let needs_await = if let Expression::Variable(v) = &*func {
                     if let Some(info) = self.functions.get(&v.value) {
@@ -253,40 +451,56 @@ impl Compiler {
                     true
                 };
 
-                if let Expression::Variable(v) = &*func {
-                    if let Some(info) = self.functions.get(&v.value) {
-                        if self.in_pure_context && !info.is_pure {
-                            panic!(
-                                "Compiler Error: Pure function cannot call impure/async function '{}' inside a pure function.",
-                                v.value
-                            );
-                        }
+                let mut param_types = Vec::new();
+                if let Expression::Variable(v) = &*func
+                    && let Some(info) = self.functions.get(&v.value).cloned()
+                {
+                    if self.in_pure_context && !info.is_pure {
+                        let message = format!(
+                            "Pure function cannot call impure/async function '{}' inside a pure function.",
+                            v.value
+                        );
+                        self.error_on(Kind::Purity, &v.value, message);
+                    }
 
-                        if info.is_pure {
-                            for arg in &args {
-                                let mut current = arg;
-                                while let Expression::FieldAccess(target, _, _) = current {
-                                    current = target;
-                                }
-                                if let Expression::Variable(arg_v) = current {
-                                    if let Some(var_info) = self.known_vars.get(&arg_v.value) {
-                                        if var_info.is_mutable {
-                                            panic!(
-                                                "Compiler Error: Cannot pass mutable variable '{}' to pure function '{}'.",
-                                                arg_v.value, v.value
-                                            );
-                                        }
-                                    }
-                                }
+                    if info.is_pure {
+                        for arg in &args {
+                            let mut current = arg;
+                            while let Expression::FieldAccess(target, _, _) = current {
+                                current = target;
+                            }
+                            if let Expression::Variable(arg_v) = current
+                                && let Some(var_info) = self.known_vars.get(&arg_v.value)
+                                && var_info.is_mutable
+                            {
+                                let message = format!(
+                                    "Cannot pass mutable variable '{}' to pure function '{}'.",
+                                    arg_v.value, v.value
+                                );
+                                self.error_on(Kind::Purity, &arg_v.value, message);
                             }
                         }
                     }
+                    param_types = info.params.clone();
                 }
 
-                let func_name = self.compile_expr(*func);
+                let func_name = self.compile_callee(*func);
                 let arg_strs: Vec<String> = args
-                    .iter()
-                    .map(|a| format!("({}).clone()", self.compile_expr(a.clone())))
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, a)| {
+                        // Closures passed to `pure fn(...)` parameters are compiled pure
+                        self.expect_pure_fn = matches!(
+                            param_types.get(i),
+                            Some(grammar::KiroType::Func(Some(_), ..))
+                        );
+                        let arg = match param_types.get(i) {
+                            Some(t) => format!("({}).clone()", self.compile_expecting(a, t)),
+                            None => format!("({}).clone()", self.compile_expr(a)),
+                        };
+                        self.expect_pure_fn = false;
+                        arg
+                    })
                     .collect();
 
                 if needs_await {
@@ -302,22 +516,28 @@ impl Compiler {
                 // We need to strip the ".await" that compile_expr normally adds to calls!
                 // This is a bit tricky. Let's handle it manually:
 
-                if let Expression::Call(func, _, args, _) = *call_expr {
+                if let Expression::Call(func, grammar::ParenList { items: args, .. }) = *call_expr {
                     // Check if target is pure (Sync)
                     let is_pure_target = if let Expression::Variable(v) = &*func {
                         self.functions
                             .get(&v.value)
                             .map(|i| i.is_pure)
                             .unwrap_or(false)
+                            || self.pure_fn_vars.contains(&v.value)
                     } else {
                         false
                     };
 
-                    let func_name = self.compile_expr(*func);
-                    let arg_strs: Vec<String> = args
-                        .iter()
-                        .map(|a| format!("({}).clone()", self.compile_expr(a.clone())))
-                        .collect();
+                    let param_types = match &*func {
+                        Expression::Variable(v) => self
+                            .functions
+                            .get(&v.value)
+                            .map(|i| i.params.clone())
+                            .unwrap_or_default(),
+                        _ => Vec::new(),
+                    };
+                    let func_name = self.compile_callee(*func);
+                    let arg_strs = self.compile_args(args, &param_types);
 
                     // Spawn logic:
                     if is_pure_target {
@@ -340,16 +560,69 @@ impl Compiler {
         }
     }
 
+    // Function position of a call: named functions are referenced directly
+    fn compile_callee(&mut self, func: Expression) -> String {
+        match func {
+            Expression::Variable(v) if self.functions.contains_key(&v.value) => v.value,
+            other => self.compile_expr(other),
+        }
+    }
+
+    // a..b / a..=b; bounds of either numeric type are truncated to int
+    fn compile_range(&mut self, start: Expression, end: Expression, inclusive: bool) -> String {
+        let start_str = self.compile_expr(start);
+        let end_str = self.compile_expr(end);
+        format!(
+            "(KiroRange {{ start: ({}) as i64, end: ({}) as i64, inclusive: {} }})",
+            start_str, end_str, inclusive
+        )
+    }
+
+    // Target of a mutation (assignment, collection command): the root variable must be
+    // mutable
+    pub fn compile_place(&mut self, target: Expression) -> String {
+        let mut root = &target;
+        while let Expression::FieldAccess(inner, _, _) = root {
+            root = inner;
+        }
+        if let Expression::Variable(grammar::VariableVal { value: name })
+        | Expression::ErrorRef(grammar::StructNameVal { value: name }) = root
+        {
+            if self.consts.contains_key(name) {
+                let message = format!("'{}' is a constant.", name);
+                self.error_on(Kind::Mutability, name, message);
+            } else if self
+                .known_vars
+                .get(name)
+                .is_some_and(|info| !info.is_mutable)
+            {
+                let message = format!("Cannot mutate immutable variable '{}'.", name);
+                self.error_on(Kind::Mutability, name, message);
+            }
+        }
+        self.compile_lvalue(target)
+    }
+
     pub fn compile_lvalue(&mut self, expr: Expression) -> String {
         match expr {
-            Expression::Variable(v) => v.value,
+            Expression::Variable(v) => {
+                self.check_not_moved(&v.value);
+                if let Some(captures) = &mut self.captures {
+                    captures.insert(v.value.clone());
+                }
+                v.value
+            }
             Expression::FieldAccess(target, _, field) => {
                 format!("{}.{}", self.compile_lvalue(*target), field.value)
             }
             Expression::Deref(_, target) => {
                 format!("*({}.lock().unwrap())", self.compile_expr(*target))
             }
-            _ => panic!("Invalid lvalue: {:?}", expr),
+            _ => {
+                let message = format!("Cannot assign to {}.", expr.describe());
+                self.error(Kind::Compile, message);
+                String::new()
+            }
         }
     }
 }
@@ -0,0 +1,517 @@
+use super::types::{compile_fn_sig, compile_type, compile_void};
+use super::{Compiler, MethodInfo};
+use crate::diagnostics::Kind;
+use crate::grammar::grammar::{self, Expression, KiroType};
+use std::collections::HashSet;
+
+impl Compiler {
+    // Records parameter types. Parameters typed `pure fn(...)` hold sync closures; any other
+    // parameter shadows them.
+    pub fn bind_fn_params(&mut self, params: &[grammar::FuncParam]) {
+        for p in params {
+            self.var_types
+                .declare(p.name.clone(), Some(p.command_type.clone()));
+            if let KiroType::Func(Some(_), ..) = p.command_type {
+                self.pure_fn_vars.insert(p.name.clone());
+            } else {
+                self.pure_fn_vars.remove(&p.name);
+            }
+        }
+    }
+
+    // Locals bound to closures created in a pure context hold sync function values
+    pub fn track_fn_var(&mut self, name: &str, value: &Expression) {
+        if self.in_pure_context && matches!(value, Expression::Lambda(_)) {
+            self.pure_fn_vars.insert(name.to_string());
+        } else {
+            self.pure_fn_vars.remove(name);
+        }
+    }
+
+    // Closure literal -> KiroFn. Captured variables are cloned into the closure so the
+    // originals stay usable. Closures are pure inside pure functions or when passed
+    // to a `pure fn(...)` parameter.
+    pub fn compile_lambda(&mut self, def: grammar::LambdaDef) -> String {
+        let is_pure = self.in_pure_context || self.expect_pure_fn;
+        let param_names: HashSet<String> = def.params.iter().map(|p| p.name.clone()).collect();
+        let outer_vars = self.known_vars.clone();
+
+        let old_context = self.in_pure_context;
+        let old_pure_params = self.pure_scope_params.clone();
+        let old_failable = self.in_failable_fn;
+        let old_captures = self.captures.replace(HashSet::new());
+        let old_pure_fn_vars = self.pure_fn_vars.clone();
+        let old_var_types = self.var_types.clone();
+        let old_return_type = std::mem::replace(&mut self.return_type, def.return_type.clone());
+        // Moves in the body happen when the closure runs, not where it is created
+        let old_known_vars = self.known_vars.clone();
+        let old_loop_depths = std::mem::take(&mut self.loop_depths);
+        let old_loop_labels = std::mem::take(&mut self.loop_labels);
+        let old_in_defer = std::mem::replace(&mut self.in_defer, false);
+        self.expect_pure_fn = false;
+        self.in_failable_fn = false;
+
+        if is_pure {
+            // Pure closures may only see immutable bindings of the enclosing pure scope
+            if !self.in_pure_context {
+                self.pure_scope_params.clear();
+            }
+            self.pure_scope_params.retain(|name| {
+                outer_vars
+                    .get(name)
+                    .map(|info| !info.is_mutable)
+                    .unwrap_or(true)
+            });
+            self.in_pure_context = true;
+        }
+        self.pure_scope_params.extend(param_names.iter().cloned());
+        self.bind_fn_params(&def.params);
+
+        let body_str = self.compile_block(def.body);
+
+        let referenced = self.captures.take().unwrap_or_default();
+        self.captures = old_captures;
+        self.in_pure_context = old_context;
+        self.pure_scope_params = old_pure_params;
+        self.in_failable_fn = old_failable;
+        self.pure_fn_vars = old_pure_fn_vars;
+        self.var_types = old_var_types;
+        self.return_type = old_return_type;
+        self.known_vars = old_known_vars;
+        self.loop_depths = old_loop_depths;
+        self.loop_labels = old_loop_labels;
+        self.in_defer = old_in_defer;
+
+        let mut captured: Vec<String> = referenced
+            .into_iter()
+            .filter(|name| outer_vars.contains(name) && !param_names.contains(name))
+            .collect();
+        captured.sort();
+
+        // Nested closures capture through the enclosing one
+        if let Some(outer) = &mut self.captures {
+            outer.extend(captured.iter().cloned());
+        }
+
+        let clones: String = captured
+            .iter()
+            .map(|name| format!("let {0} = {0}.clone(); ", name))
+            .collect();
+        let param_strs: Vec<String> = def
+            .params
+            .iter()
+            .map(|p| format!("{}: {}", p.name, compile_type(&p.command_type)))
+            .collect();
+        let param_types: Vec<KiroType> =
+            def.params.iter().map(|p| p.command_type.clone()).collect();
+        let ret_str = def
+            .return_type
+            .as_ref()
+            .map(compile_type)
+            .unwrap_or_else(compile_void);
+        let fn_sig = compile_fn_sig(is_pure, &param_types, def.return_type.as_ref());
+
+        if is_pure {
+            format!(
+                "{{ {}KiroFn::<{}>(std::sync::Arc::new(move |{}| -> {} {})) }}",
+                clones,
+                fn_sig,
+                param_strs.join(", "),
+                ret_str,
+                body_str
+            )
+        } else {
+            // Each call clones the captures again so the returned future owns its data;
+            // the per-call copies are mutable so the body may assign to them
+            let call_clones: String = captured
+                .iter()
+                .map(|name| format!("let mut {0} = {0}.clone(); ", name))
+                .collect();
+            format!(
+                "{{ {0}KiroFn::<{1}>(std::sync::Arc::new(move |{2}| {{ {5}Box::pin(async move {3}) as KiroFuture<{4}> }})) }}",
+                clones,
+                fn_sig,
+                param_strs.join(", "),
+                body_str,
+                ret_str,
+                call_clones
+            )
+        }
+    }
+
+    // defer { ... } -> a KiroDefer guard that runs the body when the enclosing block ends,
+    // dropped last-first. Like a closure, the body works on copies of the variables it
+    // uses, taken when the `defer` runs.
+    pub fn compile_defer(&mut self, body: grammar::Block) -> String {
+        let outer_vars = self.known_vars.clone();
+        let old_captures = self.captures.replace(HashSet::new());
+        let old_failable = std::mem::replace(&mut self.in_failable_fn, false);
+        let old_in_defer = std::mem::replace(&mut self.in_defer, true);
+        let old_loop_depths = std::mem::take(&mut self.loop_depths);
+        let old_loop_labels = std::mem::take(&mut self.loop_labels);
+
+        let body_str = self.compile_block(body);
+
+        let referenced = self.captures.take().unwrap_or_default();
+        self.captures = old_captures;
+        self.in_failable_fn = old_failable;
+        self.in_defer = old_in_defer;
+        self.loop_depths = old_loop_depths;
+        self.loop_labels = old_loop_labels;
+        // Moves in the body happen when it runs
+        self.known_vars = outer_vars;
+
+        let mut captured: Vec<String> = referenced
+            .into_iter()
+            .filter(|name| self.var_types.contains(name))
+            .collect();
+        captured.sort();
+        if let Some(outer) = &mut self.captures {
+            outer.extend(captured.iter().cloned());
+        }
+
+        let clones: String = captured
+            .iter()
+            .map(|name| format!("let mut {0} = {0}.clone(); ", name))
+            .collect();
+        format!(
+            "let __kiro_defer = {{ {}KiroDefer::new(async move {{ {}; }}) }};",
+            clones, body_str
+        )
+    }
+
+    // Named function used as a value -> KiroFn wrapper with the same signature
+    pub fn compile_function_ref(&mut self, name: &str) -> String {
+        let info = self.functions[name].clone();
+        let is_pure = self.in_pure_context || self.expect_pure_fn;
+        let problem = if info.is_generic {
+            Some((Kind::Compile, "is generic"))
+        } else if info.can_error {
+            Some((Kind::Compile, "is failable"))
+        } else if info.params.iter().any(|t| self.is_interface_type(t)) {
+            Some((Kind::Compile, "takes interface parameters"))
+        } else if is_pure && !info.is_pure {
+            Some((Kind::Purity, "is impure, and a pure function is expected"))
+        } else {
+            None
+        };
+        if let Some((kind, reason)) = problem {
+            let message = format!(
+                "Function '{}' cannot be used as a value here: it {}.",
+                name, reason
+            );
+            self.error_on(kind, name, message);
+            return String::new();
+        }
+
+        let arg_names: Vec<String> = (0..info.params.len()).map(|i| format!("a{}", i)).collect();
+        let param_strs: Vec<String> = arg_names
+            .iter()
+            .zip(&info.params)
+            .map(|(a, t)| format!("{}: {}", a, compile_type(t)))
+            .collect();
+        let ret = info
+            .return_type
+            .as_ref()
+            .filter(|t| !matches!(t, KiroType::Void));
+        let ret_str = ret.map(compile_type).unwrap_or_else(compile_void);
+        let fn_sig = compile_fn_sig(is_pure, &info.params, ret);
+        let call = format!("{}({})", name, arg_names.join(", "));
+
+        if is_pure {
+            format!(
+                "KiroFn::<{}>(std::sync::Arc::new(|{}| -> {} {{ {} }}))",
+                fn_sig,
+                param_strs.join(", "),
+                ret_str,
+                call
+            )
+        } else {
+            let call = if info.is_pure {
+                call
+            } else {
+                format!("{}.await", call)
+            };
+            format!(
+                "KiroFn::<{}>(std::sync::Arc::new(|{}| {{ Box::pin(async move {{ {} }}) as KiroFuture<{}> }}))",
+                fn_sig,
+                param_strs.join(", "),
+                call,
+                ret_str
+            )
+        }
+    }
+
+    // Call through a function value. Pure values are called synchronously.
+    pub fn compile_value_call(&mut self, callee: Expression, args: Vec<Expression>) -> String {
+        let is_sync = match &callee {
+            Expression::Variable(v) => self.pure_fn_vars.contains(&v.value),
+            _ => self.in_pure_context,
+        };
+        if self.in_pure_context && !is_sync {
+            self.error_help(
+                Kind::Purity,
+                "Pure function cannot call an impure function value.",
+                "Declare the parameter as 'pure fn(...)'.",
+            );
+        }
+
+        let param_types = match self.static_type(&callee) {
+            Some(KiroType::Func(_, _, _, params, ..)) => params,
+            _ => Vec::new(),
+        };
+        let callee_str = self.compile_expr(callee);
+        let arg_strs: Vec<String> = self.compile_args(args, &param_types);
+        if is_sync {
+            format!("({})({})", callee_str, arg_strs.join(", "))
+        } else {
+            format!("({})({}).await", callee_str, arg_strs.join(", "))
+        }
+    }
+
+    // Shared by functions and methods: (params, return type, body) of the Rust fn item.
+    // `receiver` is `&self` / `&mut self` for methods.
+    pub fn compile_fn_parts(
+        &mut self,
+        receiver: Option<&str>,
+        params: &[grammar::FuncParam],
+        return_type: Option<KiroType>,
+        is_pure: bool,
+        can_error: bool,
+        body: grammar::Block,
+    ) -> (String, String, String) {
+        let old_context = self.in_pure_context;
+        let old_pure_params = self.pure_scope_params.clone();
+        let old_pure_fn_vars = self.pure_fn_vars.clone();
+        let old_var_types = self.var_types.clone();
+        let old_return_type = std::mem::replace(&mut self.return_type, return_type.clone());
+        let old_loop_depths = std::mem::take(&mut self.loop_depths);
+        let old_loop_labels = std::mem::take(&mut self.loop_labels);
+        let old_in_defer = std::mem::replace(&mut self.in_defer, false);
+        if is_pure {
+            self.in_pure_context = true;
+            // Populate allowed params for pure scope
+            self.pure_scope_params.clear();
+            for p in params {
+                self.pure_scope_params.insert(p.name.clone());
+            }
+            if receiver.is_some() {
+                self.pure_scope_params.insert("self".to_string());
+            }
+        }
+
+        self.bind_fn_params(params);
+        let mut param_strs: Vec<String> = receiver.iter().map(|r| r.to_string()).collect();
+        param_strs.extend(
+            params
+                .iter()
+                .map(|p| format!("{}: {}", p.name, self.compile_param_type(&p.command_type))),
+        );
+
+        let old_in_failable = self.in_failable_fn;
+        if can_error {
+            self.in_failable_fn = true;
+        }
+
+        let body_str = self.compile_block(body);
+
+        self.in_pure_context = old_context;
+        self.in_failable_fn = old_in_failable;
+        self.pure_scope_params = old_pure_params; // Restore
+        self.pure_fn_vars = old_pure_fn_vars;
+        self.var_types = old_var_types;
+        self.return_type = old_return_type;
+        self.loop_depths = old_loop_depths;
+        self.loop_labels = old_loop_labels;
+        self.in_defer = old_in_defer;
+
+        let ret_def = match return_type {
+            Some(KiroType::Void) | None => "()".to_string(),
+            Some(rt) => compile_type(&rt),
+        };
+
+        if can_error {
+            (
+                param_strs.join(", "),
+                format!("anyhow::Result<{}>", ret_def),
+                format!("{{ let __kiro_res = {}; Ok(__kiro_res) }}", body_str),
+            )
+        } else {
+            (param_strs.join(", "), ret_def, body_str)
+        }
+    }
+
+    pub fn register_impl(&mut self, block: &grammar::ImplBlock) {
+        for m in &block.methods {
+            if m.pure_kw.is_some() && m.var_kw.is_some() {
+                let message = format!(
+                    "Pure method '{}.{}' cannot take 'var self'.",
+                    block.struct_name(),
+                    m.name
+                );
+                self.error_on(Kind::Purity, &m.name, message);
+            }
+        }
+        let methods = self
+            .methods
+            .entry(block.struct_name().to_string())
+            .or_default();
+        for m in &block.methods {
+            methods.insert(
+                m.name.clone(),
+                MethodInfo {
+                    is_pure: m.pure_kw.is_some(),
+                    is_mutating: m.var_kw.is_some(),
+                    params: m
+                        .params
+                        .iter()
+                        .map(|p| p.param.command_type.clone())
+                        .collect(),
+                    return_type: m.return_type.clone(),
+                },
+            );
+        }
+    }
+
+    // impl block -> inherent Rust impl, or a trait impl for "impl Shape for Circle".
+    // Emitted in place (not hoisted) so method bodies can call functions declared in the
+    // same scope.
+    pub fn compile_impl(&mut self, block: grammar::ImplBlock) -> String {
+        self.register_impl(&block);
+        if block.interface().is_some() {
+            self.check_impl_conformance(&block);
+        }
+        let struct_name = block.struct_name().to_string();
+        let self_type = KiroType::Custom(grammar::StructNameVal {
+            value: struct_name.clone(),
+        });
+        let header = match block.interface() {
+            Some(interface) => format!("{} for {}", interface, struct_name),
+            None => struct_name,
+        };
+        // Trait items take their visibility from the trait
+        let vis = if block.interface().is_some() {
+            ""
+        } else {
+            "pub "
+        };
+
+        let mut method_strs = Vec::new();
+        for m in block.methods {
+            let is_pure = m.pure_kw.is_some();
+            let is_mutating = m.var_kw.is_some();
+            self.enter_scope();
+            self.declare_var("self", is_mutating, Some(self_type.clone()));
+
+            let params: Vec<grammar::FuncParam> = m.params.into_iter().map(|p| p.param).collect();
+            let receiver = if is_mutating { "&mut self" } else { "&self" };
+            let (param_str, ret_type, final_body) = self.compile_fn_parts(
+                Some(receiver),
+                &params,
+                m.return_type,
+                is_pure,
+                m.can_error.is_some(),
+                m.body,
+            );
+
+            self.exit_scope();
+
+            let async_kw = if is_pure { "" } else { "async" };
+            method_strs.push(format!(
+                "{}{} fn {}({}) -> {} {}",
+                vis, async_kw, m.name, param_str, ret_type, final_body
+            ));
+        }
+
+        format!("impl {} {{ {} }}", header, method_strs.join("\n"))
+    }
+
+    // Method named `name`, if any impl block declares it. Struct types are not tracked,
+    // so every declaration of the name must agree on purity and receiver kind.
+    pub fn lookup_method(&mut self, name: &str) -> Option<MethodInfo> {
+        let conflict = self
+            .methods
+            .values()
+            .filter_map(|methods| methods.get(name))
+            .collect::<Vec<_>>()
+            .windows(2)
+            .any(|w| w[0].is_pure != w[1].is_pure || w[0].is_mutating != w[1].is_mutating);
+        if conflict {
+            let message = format!(
+                "Method '{}' is declared with different purity or receivers on different types.",
+                name
+            );
+            self.error_on(Kind::Compile, name, message);
+        }
+        self.find_method(name)
+    }
+
+    // Same, without the agreement check, for working out types
+    pub fn find_method(&self, name: &str) -> Option<MethodInfo> {
+        self.methods
+            .values()
+            .filter_map(|methods| methods.get(name))
+            .last()
+            .cloned()
+    }
+
+    // target.method(args). Methods run on a copy of the (auto-dereferenced) receiver;
+    // `var self` methods write the copy back to the receiver's place afterwards.
+    pub fn compile_method_call(
+        &mut self,
+        target: Expression,
+        method: &str,
+        info: MethodInfo,
+        args: Vec<Expression>,
+    ) -> String {
+        if self.in_pure_context && !info.is_pure {
+            let message = format!("Pure function cannot call impure method '{}'.", method);
+            self.error_on(Kind::Purity, method, message);
+        }
+
+        let arg_strs = self.compile_args(args, &info.params);
+        let await_kw = if info.is_pure { "" } else { ".await" };
+        let call = format!("{}({}){}", method, arg_strs.join(", "), await_kw);
+
+        if info.is_mutating {
+            let mut root = &target;
+            while let Expression::FieldAccess(inner, _, _) = root {
+                root = inner;
+            }
+            if let Expression::Variable(v) = root
+                && self
+                    .known_vars
+                    .get(&v.value)
+                    .is_some_and(|var| !var.is_mutable)
+            {
+                let message = format!(
+                    "Cannot call mutating method '{}' on immutable variable '{}'.",
+                    method, v.value
+                );
+                self.error_on(Kind::Mutability, &v.value, message);
+            }
+            let place = self.compile_lvalue(target);
+            format!(
+                "{{ let mut __kiro_self = {0}.kiro_get(|v| v.clone()); let __kiro_ret = __kiro_self.{1}; {0}.kiro_set(__kiro_self); __kiro_ret }}",
+                place, call
+            )
+        } else {
+            format!(
+                "{}.kiro_get(|v| v.clone()).{}",
+                self.compile_expr(target),
+                call
+            )
+        }
+    }
+
+    // Cloned call arguments, checked against the declared parameter types where known
+    pub fn compile_args(&mut self, args: Vec<Expression>, params: &[KiroType]) -> Vec<String> {
+        args.into_iter()
+            .enumerate()
+            .map(|(i, a)| match params.get(i) {
+                Some(t) => format!("({}).clone()", self.compile_expecting(a, t)),
+                None => format!("({}).clone()", self.compile_expr(a)),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,144 @@
+use super::types::compile_type;
+use super::{Compiler, MethodInfo};
+use crate::diagnostics::Kind;
+use crate::grammar::grammar::{self, KiroType};
+
+impl Compiler {
+    // Interface methods are registered like struct methods so calls on interface-typed
+    // parameters resolve through `lookup_method`
+    pub fn register_interface(&mut self, def: &grammar::InterfaceDef) {
+        if self.interfaces.contains_key(&def.name.value) {
+            let message = format!("Interface '{}' is already declared.", def.name.value);
+            self.error_on(Kind::Compile, &def.name.value, message);
+        }
+        for sig in &def.methods {
+            if sig.pure_kw.is_some() && sig.var_kw.is_some() {
+                let message = format!(
+                    "Pure method '{}.{}' cannot take 'var self'.",
+                    def.name.value, sig.name
+                );
+                self.error_on(Kind::Purity, &sig.name, message);
+            }
+        }
+        let methods = self.methods.entry(def.name.value.clone()).or_default();
+        for sig in &def.methods {
+            methods.insert(
+                sig.name.clone(),
+                MethodInfo {
+                    is_pure: sig.pure_kw.is_some(),
+                    is_mutating: sig.var_kw.is_some(),
+                    params: sig
+                        .params
+                        .iter()
+                        .map(|p| p.param.command_type.clone())
+                        .collect(),
+                    return_type: sig.ret.as_ref().map(|r| (*r.ret).clone()),
+                },
+            );
+        }
+        self.interfaces
+            .insert(def.name.value.clone(), def.methods.clone());
+    }
+
+    pub fn is_interface_type(&self, t: &KiroType) -> bool {
+        matches!(t, KiroType::Custom(name) if self.interfaces.contains_key(&name.value))
+    }
+
+    // Interface-typed parameters are generic (static dispatch): `s: Shape` -> `s: impl Shape`
+    pub fn compile_param_type(&self, t: &KiroType) -> String {
+        match t {
+            KiroType::Custom(name) if self.interfaces.contains_key(&name.value) => {
+                format!("impl {}", name.value)
+            }
+            _ => compile_type(t),
+        }
+    }
+
+    // Interface -> Rust trait. Impure methods return Send futures so implementations can be
+    // awaited from spawned tasks.
+    pub fn compile_interface(&mut self, def: grammar::InterfaceDef) -> String {
+        if !self.interfaces.contains_key(&def.name.value) {
+            self.register_interface(&def);
+        }
+
+        let method_strs: Vec<String> = def
+            .methods
+            .iter()
+            .map(|sig| {
+                let receiver = if sig.var_kw.is_some() {
+                    "&mut self"
+                } else {
+                    "&self"
+                };
+                let mut params = vec![receiver.to_string()];
+                params.extend(sig.params.iter().map(|p| {
+                    format!(
+                        "{}: {}",
+                        p.param.name,
+                        self.compile_param_type(&p.param.command_type)
+                    )
+                }));
+
+                let mut ret = match sig.ret.as_ref().map(|r| &*r.ret) {
+                    Some(KiroType::Void) | None => "()".to_string(),
+                    Some(t) => compile_type(t),
+                };
+                if sig.can_error.is_some() {
+                    ret = format!("anyhow::Result<{}>", ret);
+                }
+                if sig.pure_kw.is_none() {
+                    ret = format!("impl std::future::Future<Output = {}> + Send", ret);
+                }
+                format!("fn {}({}) -> {};", sig.name, params.join(", "), ret)
+            })
+            .collect();
+
+        format!(
+            "pub trait {}: KiroGet<Inner = Self> + Clone + Send + Sync + 'static {{ {} }}",
+            def.name.value,
+            method_strs.join(" ")
+        )
+    }
+
+    // "impl Shape for Circle" must provide exactly the interface's methods
+    pub fn check_impl_conformance(&mut self, block: &grammar::ImplBlock) {
+        let struct_name = block.struct_name();
+        let interface = block.interface().unwrap_or_default();
+        let Some(sigs) = self.interfaces.get(interface).cloned() else {
+            let message = format!("Unknown interface '{}'.", interface);
+            self.error_on(Kind::Compile, interface, message);
+            return;
+        };
+
+        for sig in &sigs {
+            match block.methods.iter().find(|m| m.name == sig.name) {
+                None => {
+                    let message = format!(
+                        "'{}' does not implement '{}': missing method '{}'.",
+                        struct_name, interface, sig.name
+                    );
+                    self.error_on(Kind::Compile, interface, message);
+                }
+                Some(def) if !sig.matches(def) => {
+                    let message = format!(
+                        "Method '{}.{}' does not match its declaration in interface '{}'.",
+                        struct_name, def.name, interface
+                    );
+                    self.error_on(Kind::Compile, &def.name, message);
+                }
+                Some(_) => {}
+            }
+        }
+        for extra in block
+            .methods
+            .iter()
+            .filter(|m| !sigs.iter().any(|s| s.name == m.name))
+        {
+            let message = format!(
+                "Method '{}' is not declared by interface '{}'.",
+                extra.name, interface
+            );
+            self.error_on(Kind::Compile, &extra.name, message);
+        }
+    }
+}
@@ -1,13 +1,31 @@
+use crate::diagnostics::Kind;
 use crate::grammar::grammar;
+use crate::scope::Scopes;
 use std::collections::{HashMap, HashSet};
 
+pub mod collection;
+pub mod error;
+pub mod errors;
 pub mod expression;
+pub mod function;
+pub mod interface;
+pub mod moves;
+pub mod numeric;
+pub mod optional;
+pub mod pattern;
 pub mod statement;
 pub mod types;
 
 #[derive(Clone, Debug)]
 pub struct VarInfo {
     pub is_mutable: bool,
+    pub moved: Option<(usize, usize)>, // Span of the statement that moved the value, while it stays moved
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorInfo {
+    pub path: String, // Rust path of the declaring module: "" here, "std_fs::" when imported
+    pub fields: Vec<grammar::FieldDef>, // Payload fields (may be none)
 }
 
 #[derive(Clone, Debug)]
@@ -15,31 +33,137 @@ pub struct FunctionInfo {
     pub is_pure: bool,
     pub can_error: bool,
     pub doc: Option<String>,
+    // Signature, used when the function is passed around as a value
+    pub params: Vec<grammar::KiroType>,
+    pub return_type: Option<grammar::KiroType>,
+    pub is_generic: bool,
+}
+
+impl FunctionInfo {
+    pub fn from_def(def: &grammar::FunctionDef, doc: Option<String>) -> Self {
+        Self {
+            is_pure: def.pure_kw.is_some(),
+            can_error: def.can_error.is_some(),
+            doc,
+            params: def.params.iter().map(|p| p.command_type.clone()).collect(),
+            return_type: def.return_type.clone(),
+            is_generic: def.type_params.is_some(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MethodInfo {
+    pub is_pure: bool,
+    pub is_mutating: bool, // `var self` receiver
+    pub params: Vec<grammar::KiroType>,
+    pub return_type: Option<grammar::KiroType>,
 }
 
 pub struct Compiler {
-    pub known_vars: HashMap<String, VarInfo>,
+    pub known_vars: Scopes<VarInfo>,
+    pub var_types: Scopes<Option<grammar::KiroType>>, // Statically known variable types
     pub imported_modules: HashSet<String>,
     pub functions: HashMap<String, FunctionInfo>,
+    pub enums: HashMap<String, Vec<(String, usize)>>, // name -> (variant, payload arity)
+    pub structs: HashMap<String, Vec<grammar::FieldDef>>, // name -> declared fields
+    pub errors: HashMap<String, ErrorInfo>,           // Errors declared here, imported or built in
+    pub consts: HashMap<String, grammar::KiroType>,   // module constants -> declared type
+    pub methods: HashMap<String, HashMap<String, MethodInfo>>, // struct -> method name -> info
+    pub interfaces: HashMap<String, Vec<grammar::MethodSig>>, // interface -> required methods
     pub in_pure_context: bool,
     pub in_failable_fn: bool,
     pub pure_scope_params: HashSet<String>, // Parameters allowed in pure function scope
-    pub moved_vars: HashSet<String>,        // Track moved variables to prevent use-after-move
+    pub pure_fn_vars: HashSet<String>,      // Variables holding sync (pure) function values
+    pub captures: Option<HashSet<String>>,  // Variables referenced by the closure being compiled
+    pub expect_pure_fn: bool,               // Compiling an argument for a `pure fn(...)` parameter
+    pub return_type: Option<grammar::KiroType>, // Declared return type of the fn being compiled
+    pub loop_depths: Vec<usize>,            // Scope depth at the start of each enclosing loop body
+    pub loop_labels: Vec<String>,           // Labels of the enclosing loops, innermost last
+    pub in_defer: bool,                     // Compiling a `defer` body
+    pub displayed: HashSet<String>,         // Type names printed in the fn being compiled
+    pub span: (usize, usize),               // Byte span of that statement
+    pub diagnostics: Vec<crate::diagnostics::Diagnostic>,
+    pub inferred: crate::checker::Types, // Local types found by the checker
 }
 
 impl Compiler {
     pub fn new() -> Self {
         Self {
-            known_vars: HashMap::new(),
+            known_vars: Scopes::new(),
+            var_types: Scopes::new(),
             imported_modules: HashSet::new(),
             functions: HashMap::new(),
+            enums: HashMap::new(),
+            structs: HashMap::new(),
+            errors: crate::grammar::BUILTIN_ERRORS
+                .iter()
+                .map(|(name, _)| {
+                    let info = ErrorInfo {
+                        path: "crate::".to_string(),
+                        fields: Vec::new(),
+                    };
+                    (name.to_string(), info)
+                })
+                .collect(),
+            consts: HashMap::new(),
+            methods: HashMap::new(),
+            interfaces: HashMap::new(),
             in_pure_context: false,
             in_failable_fn: false,
             pure_scope_params: HashSet::new(),
-            moved_vars: HashSet::new(),
+            pure_fn_vars: HashSet::new(),
+            captures: None,
+            expect_pure_fn: false,
+            return_type: None,
+            loop_depths: Vec::new(),
+            loop_labels: Vec::new(),
+            in_defer: false,
+            displayed: HashSet::new(),
+            span: (0, 0),
+            diagnostics: Vec::new(),
+            inferred: HashMap::new(),
         }
     }
 
+    // Blocks scope their variables the same way the interpreter does
+    pub fn enter_scope(&mut self) {
+        self.known_vars.push();
+        self.var_types.push();
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.known_vars.pop();
+        self.var_types.pop();
+    }
+
+    // Declares `name` in the current block with its type: the one the checker inferred,
+    // else the static type found here
+    pub fn declare_var(
+        &mut self,
+        name: &str,
+        is_mutable: bool,
+        var_type: Option<grammar::KiroType>,
+    ) {
+        self.known_vars.declare(
+            name.to_string(),
+            VarInfo {
+                is_mutable,
+                moved: None,
+            },
+        );
+        let inferred = self.inferred.get(&(self.span.0, name.to_string())).cloned();
+        self.var_types
+            .declare(name.to_string(), inferred.or(var_type));
+        if self.in_pure_context {
+            self.pure_scope_params.insert(name.to_string());
+        }
+    }
+
+    pub fn var_type(&self, name: &str) -> Option<grammar::KiroType> {
+        self.var_types.get(name).cloned().flatten()
+    }
+
     pub fn compile(&mut self, program: grammar::Program, is_main: bool) -> String {
         let mut output = String::new();
         output.push_str("#![allow(unused)]\n");
@@ -72,49 +196,184 @@ impl Compiler {
                         f(&*guard)
                     }
                 }
+
+                // Nullable pointer (adr T)
+                impl<T> KiroGet for Option<std::sync::Arc<std::sync::Mutex<T>>> {
+                    type Inner = T;
+                    fn kiro_get<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+                        let guard = self.as_ref().expect("Dereferencing Void/Null Pointer").lock().unwrap();
+                        f(&*guard)
+                    }
+                }
+
+                // --- KIRO SET (write-back for `var self` methods) ---
+                pub trait KiroSet: KiroGet { fn kiro_set(&mut self, v: Self::Inner); }
+                impl<T> KiroSet for Option<std::sync::Arc<std::sync::Mutex<T>>> {
+                    fn kiro_set(&mut self, v: T) {
+                        *self.as_ref().expect("Dereferencing Void/Null Pointer").lock().unwrap() = v;
+                    }
+                }
     
-                // --- KIRO AT TRAIT (Access Command) ---
-                pub trait KiroAt<I, O> { fn kiro_at(&self, index: I) -> O; }
+                // --- KIRO AT TRAIT (Access Command; a missing index or key is a Kiro error) ---
+                pub trait KiroAt<I, O> { fn kiro_at(&self, index: I) -> anyhow::Result<O>; }
     
-                // List Implementation
-                impl<T: Clone> KiroAt<f64, T> for Vec<T> {
-                    fn kiro_at(&self, index: f64) -> T {
-                        self.get(index as usize).cloned().expect("Index out of bounds")
+                // List Implementation (int index)
+                impl<T: Clone> KiroAt<i64, T> for Vec<T> {
+                    fn kiro_at(&self, index: i64) -> anyhow::Result<T> {
+                        usize::try_from(index).ok().and_then(|i| self.get(i)).cloned().ok_or_else(kiro_error_IndexOutOfBounds)
                     }
                 }
     
                 // Map Implementation
                 impl<K, V> KiroAt<K, V> for std::collections::HashMap<K, V> 
                 where K: std::hash::Hash + Eq + Clone, V: Clone {
-                    fn kiro_at(&self, key: K) -> V {
-                        self.get(&key).cloned().expect("Key not found")
+                    fn kiro_at(&self, key: K) -> anyhow::Result<V> {
+                        self.get(&key).cloned().ok_or_else(kiro_error_KeyNotFound)
                     }
                 }
     
                 // --- KIRO ADD ---
                 pub trait KiroAdd<Rhs = Self> { type Output; fn kiro_add(self, rhs: Rhs) -> Self::Output; }
                 impl KiroAdd for f64 { type Output = f64; fn kiro_add(self, rhs: f64) -> f64 { self + rhs } }
+                impl KiroAdd for i64 { type Output = i64; fn kiro_add(self, rhs: i64) -> i64 { self.checked_add(rhs).expect("Integer overflow") } }
                 impl KiroAdd for String { type Output = String; fn kiro_add(self, rhs: String) -> String { format!("{}{}", self, rhs) } }
+
+                // --- KIRO INT (checked int arithmetic; division truncates) ---
+                pub trait KiroInt {
+                    fn kiro_sub(self, rhs: i64) -> i64;
+                    fn kiro_mul(self, rhs: i64) -> i64;
+                    fn kiro_div(self, rhs: i64) -> i64;
+                    fn kiro_rem(self, rhs: i64) -> i64;
+                    fn kiro_neg(self) -> i64;
+                }
+                impl KiroInt for i64 {
+                    fn kiro_sub(self, rhs: i64) -> i64 { self.checked_sub(rhs).expect("Integer overflow") }
+                    fn kiro_mul(self, rhs: i64) -> i64 { self.checked_mul(rhs).expect("Integer overflow") }
+                    fn kiro_div(self, rhs: i64) -> i64 { if rhs == 0 { panic!("Division by zero") } self.checked_div(rhs).expect("Integer overflow") }
+                    fn kiro_rem(self, rhs: i64) -> i64 { if rhs == 0 { panic!("Division by zero") } self.checked_rem(rhs).expect("Integer overflow") }
+                    fn kiro_neg(self) -> i64 { self.checked_neg().expect("Integer overflow") }
+                }
+
+                // --- KIRO CONVERT (int(x) / num(x)) ---
+                pub trait KiroConvert { fn kiro_int(self) -> i64; fn kiro_num(self) -> f64; }
+                impl KiroConvert for i64 { fn kiro_int(self) -> i64 { self } fn kiro_num(self) -> f64 { self as f64 } }
+                impl KiroConvert for f64 {
+                    fn kiro_int(self) -> i64 {
+                        if !self.is_finite() || self < i64::MIN as f64 || self.trunc() >= i64::MAX as f64 {
+                            panic!("Cannot convert {} to int.", self)
+                        }
+                        self as i64
+                    }
+                    fn kiro_num(self) -> f64 { self }
+                }
     
+                // --- KIRO EDIT (collection commands; indexes out of range are Kiro errors) ---
+                pub trait KiroEdit<K, V> {
+                    fn kiro_set_at(&mut self, key: K, value: V) -> anyhow::Result<()>;
+                    fn kiro_insert(&mut self, key: K, value: V) -> anyhow::Result<()>;
+                    fn kiro_remove(&mut self, key: K) -> anyhow::Result<V>;
+                }
+                impl<T> KiroEdit<i64, T> for Vec<T> {
+                    fn kiro_set_at(&mut self, index: i64, value: T) -> anyhow::Result<()> {
+                        let slot = usize::try_from(index).ok().and_then(|i| self.get_mut(i));
+                        *slot.ok_or_else(kiro_error_IndexOutOfBounds)? = value;
+                        Ok(())
+                    }
+                    fn kiro_insert(&mut self, index: i64, value: T) -> anyhow::Result<()> {
+                        match usize::try_from(index) {
+                            Ok(i) if i <= self.len() => { self.insert(i, value); Ok(()) }
+                            _ => Err(kiro_error_IndexOutOfBounds()),
+                        }
+                    }
+                    fn kiro_remove(&mut self, index: i64) -> anyhow::Result<T> {
+                        match usize::try_from(index) {
+                            Ok(i) if i < self.len() => Ok(self.remove(i)),
+                            _ => Err(kiro_error_IndexOutOfBounds()),
+                        }
+                    }
+                }
+                impl<K: std::hash::Hash + Eq, V> KiroEdit<K, V> for std::collections::HashMap<K, V> {
+                    fn kiro_set_at(&mut self, key: K, value: V) -> anyhow::Result<()> { self.insert(key, value); Ok(()) }
+                    fn kiro_insert(&mut self, key: K, value: V) -> anyhow::Result<()> { self.insert(key, value); Ok(()) }
+                    fn kiro_remove(&mut self, key: K) -> anyhow::Result<V> {
+                        self.remove(&key).ok_or_else(kiro_error_KeyNotFound)
+                    }
+                }
+                pub trait KiroContains<T> { fn kiro_contains(&self, item: T) -> bool; }
+                impl<T: PartialEq> KiroContains<T> for Vec<T> { fn kiro_contains(&self, item: T) -> bool { self.contains(&item) } }
+                impl<K: std::hash::Hash + Eq, V> KiroContains<K> for std::collections::HashMap<K, V> { fn kiro_contains(&self, key: K) -> bool { self.contains_key(&key) } }
+                pub trait KiroPop { type Item; fn kiro_pop(&mut self) -> anyhow::Result<Self::Item>; }
+                impl<T> KiroPop for Vec<T> {
+                    type Item = T;
+                    fn kiro_pop(&mut self) -> anyhow::Result<T> { self.pop().ok_or_else(kiro_error_EmptyList) }
+                }
+
                 // --- KIRO LEN ---
-                pub trait KiroLen { fn kiro_len(&self) -> f64; }
-                impl<T> KiroLen for Vec<T> { fn kiro_len(&self) -> f64 { self.len() as f64 } }
-                impl<K, V> KiroLen for std::collections::HashMap<K, V> { fn kiro_len(&self) -> f64 { self.len() as f64 } }
-                impl KiroLen for String { fn kiro_len(&self) -> f64 { self.len() as f64 } }
+                pub trait KiroLen { fn kiro_len(&self) -> i64; }
+                impl<T> KiroLen for Vec<T> { fn kiro_len(&self) -> i64 { self.len() as i64 } }
+                impl<K, V> KiroLen for std::collections::HashMap<K, V> { fn kiro_len(&self) -> i64 { self.len() as i64 } }
+                impl KiroLen for String { fn kiro_len(&self) -> i64 { self.len() as i64 } }
     
                 // --- KIRO ITER ---
-                pub trait KiroIter { type Item; type IntoIter: Iterator<Item = Self::Item>; fn kiro_iter(self) -> Self::IntoIter; }
-                impl KiroIter for std::ops::Range<i64> { type Item = i64; type IntoIter = std::ops::Range<i64>; fn kiro_iter(self) -> Self::IntoIter { self } }
+                // `per n` keeps every nth item; only ranges count down with a negative step
+                pub fn kiro_step(step: i64, can_count_down: bool) -> usize { if step == 0 { panic!("Loop Error: Step cannot be 0."); } if step < 0 && !can_count_down { panic!("Loop Error: Only ranges can count down with a negative step."); } step.unsigned_abs() as usize }
+                pub trait KiroIter: Sized { type Item; type IntoIter: Iterator<Item = Self::Item>; fn kiro_iter(self) -> Self::IntoIter; fn kiro_iter_by(self, step: i64) -> std::iter::StepBy<Self::IntoIter> { self.kiro_iter().step_by(kiro_step(step, false)) } }
+                // a..b and a..=b, counted from start toward end: up by a positive step, down by a negative one
+                #[derive(Debug, Clone, Copy, PartialEq)] pub struct KiroRange { pub start: i64, pub end: i64, pub inclusive: bool }
+                pub struct KiroRangeIter { next: Option<i64>, end: i64, inclusive: bool, step: i64 }
+                impl Iterator for KiroRangeIter { type Item = i64; fn next(&mut self) -> Option<i64> { let v = self.next?; let in_range = match (self.step > 0, self.inclusive) { (true, false) => v < self.end, (true, true) => v <= self.end, (false, false) => v > self.end, (false, true) => v >= self.end }; if !in_range { return None; } self.next = v.checked_add(self.step); Some(v) } }
+                impl KiroRange { fn kiro_counted(self, step: i64) -> KiroRangeIter { kiro_step(step, true); KiroRangeIter { next: Some(self.start), end: self.end, inclusive: self.inclusive, step } } }
+                impl KiroIter for KiroRange { type Item = i64; type IntoIter = KiroRangeIter; fn kiro_iter(self) -> KiroRangeIter { self.kiro_counted(1) } fn kiro_iter_by(self, step: i64) -> std::iter::StepBy<KiroRangeIter> { self.kiro_counted(step).step_by(1) } }
                 impl<T> KiroIter for Vec<T> { type Item = T; type IntoIter = std::vec::IntoIter<T>; fn kiro_iter(self) -> Self::IntoIter { self.into_iter() } }
                 impl KiroIter for String { type Item = char; type IntoIter = std::vec::IntoIter<char>; fn kiro_iter(self) -> Self::IntoIter { self.chars().collect::<Vec<_>>().into_iter() } }
+                // Maps give (key, value) ordered by key, like the interpreter
+                impl<K: Ord, V> KiroIter for std::collections::HashMap<K, V> { type Item = (K, V); type IntoIter = std::vec::IntoIter<(K, V)>; fn kiro_iter(self) -> Self::IntoIter { let mut entries: Vec<(K, V)> = self.into_iter().collect(); entries.sort_by(|a, b| a.0.cmp(&b.0)); entries.into_iter() } }
+
+                // --- KIRO PAIRS (loop i, x in list / loop k, v in map) ---
+                // Stepping keeps list indexes; a range's index counts the values it gave
+                pub trait KiroPairs: Sized { type Pair; fn kiro_pairs(self) -> std::vec::IntoIter<Self::Pair>; fn kiro_pairs_by(self, step: i64) -> std::iter::StepBy<std::vec::IntoIter<Self::Pair>> { self.kiro_pairs().step_by(kiro_step(step, false)) } }
+                impl KiroPairs for KiroRange { type Pair = (i64, i64); fn kiro_pairs(self) -> std::vec::IntoIter<(i64, i64)> { self.kiro_pairs_by(1).collect::<Vec<_>>().into_iter() } fn kiro_pairs_by(self, step: i64) -> std::iter::StepBy<std::vec::IntoIter<(i64, i64)>> { self.kiro_iter_by(step).enumerate().map(|(i, x)| (i as i64, x)).collect::<Vec<_>>().into_iter().step_by(1) } }
+                impl<T> KiroPairs for Vec<T> { type Pair = (i64, T); fn kiro_pairs(self) -> std::vec::IntoIter<(i64, T)> { self.into_iter().enumerate().map(|(i, x)| (i as i64, x)).collect::<Vec<_>>().into_iter() } }
+                impl KiroPairs for String { type Pair = (i64, String); fn kiro_pairs(self) -> std::vec::IntoIter<(i64, String)> { self.chars().enumerate().map(|(i, c)| (i as i64, c.to_string())).collect::<Vec<_>>().into_iter() } }
+                impl<K: Ord, V> KiroPairs for std::collections::HashMap<K, V> { type Pair = (K, V); fn kiro_pairs(self) -> std::vec::IntoIter<(K, V)> { self.kiro_iter() } }
     
                 // --- AS KIRO LOOP VAR ---
                 pub trait AsKiroLoopVar { type Out; fn as_kiro(self) -> Self::Out; }
-                impl AsKiroLoopVar for i64 { type Out = f64; fn as_kiro(self) -> f64 { self as f64 } }
+                impl AsKiroLoopVar for i64 { type Out = i64; fn as_kiro(self) -> i64 { self } }
                 impl AsKiroLoopVar for f64 { type Out = f64; fn as_kiro(self) -> f64 { self } }
                 impl AsKiroLoopVar for char { type Out = String; fn as_kiro(self) -> String { self.to_string() } }
                 impl AsKiroLoopVar for String { type Out = String; fn as_kiro(self) -> String { self } }
 
+                // --- KIRO CONST (module constants: literals at compile time, the rest on first use) ---
+                pub enum KiroConst<T: 'static> { Value(T), Lazy(std::sync::LazyLock<T>) }
+                impl<T> std::ops::Deref for KiroConst<T> { type Target = T; fn deref(&self) -> &T { match self { KiroConst::Value(v) => v, KiroConst::Lazy(l) => l } } }
+                macro_rules! kiro_tuple_loop_var { ($($t:ident),+) => { impl<$($t),+> AsKiroLoopVar for ($($t,)+) { type Out = Self; fn as_kiro(self) -> Self { self } } }; }
+                kiro_tuple_loop_var!(A, B); kiro_tuple_loop_var!(A, B, C); kiro_tuple_loop_var!(A, B, C, D);
+                kiro_tuple_loop_var!(A, B, C, D, E); kiro_tuple_loop_var!(A, B, C, D, E, F);
+
+                // --- KIRO ERRORS (each error carries its type name; handlers compare it exactly) ---
+                #[derive(Clone, Debug, PartialEq)]
+                pub struct KiroErrorName(pub String);
+                impl std::fmt::Display for KiroErrorName { fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { write!(f, "{}", self.0) } }
+                pub fn kiro_error(name: &str, error: anyhow::Error) -> anyhow::Error { error.context(KiroErrorName(name.to_string())) }
+                pub fn kiro_error_is(error: &anyhow::Error, name: &str) -> bool { error.downcast_ref::<KiroErrorName>().is_some_and(|n| n.0 == name) }
+                // An error raised where nothing can handle it ends the program
+                pub fn kiro_unhandled(error: anyhow::Error) -> ! { eprintln!("Runtime Error: Unhandled error {:#}", error); std::process::exit(1) }
+
+                // --- KIRO DEFER (drop guard: runs the deferred body when the block ends) ---
+                pub struct KiroDefer(Option<std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>>);
+                impl KiroDefer { pub fn new(body: impl std::future::Future<Output = ()> + Send + 'static) -> Self { KiroDefer(Some(Box::pin(body))) } }
+                impl Drop for KiroDefer { fn drop(&mut self) { if let Some(body) = self.0.take() { tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(body)) } } }
+
+                // --- KIRO FN (function values) ---
+                pub type KiroFuture<T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send>>;
+                pub struct KiroFn<F: ?Sized>(pub std::sync::Arc<F>);
+                impl<F: ?Sized> Clone for KiroFn<F> { fn clone(&self) -> Self { KiroFn(self.0.clone()) } }
+                impl<F: ?Sized> std::ops::Deref for KiroFn<F> { type Target = F; fn deref(&self) -> &F { &self.0 } }
+                impl<F: ?Sized> std::fmt::Debug for KiroFn<F> { fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { write!(f, "<Function>") } }
+                impl<F: ?Sized> std::fmt::Display for KiroFn<F> { fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { write!(f, "<Function>") } }
+                impl<F: ?Sized> AsKiroLoopVar for KiroFn<F> { type Out = Self; fn as_kiro(self) -> Self { self } }
+
                 // --- KIRO ASSIGN ---
                 pub trait KiroAssign<Rhs> { fn kiro_assign(&mut self, rhs: Rhs); }
                 // Default Assignment (Same Types)
@@ -131,9 +390,13 @@ impl Compiler {
                 pub trait KiroTruthy { fn kiro_truthy(&self) -> bool; }
                 impl KiroTruthy for bool { fn kiro_truthy(&self) -> bool { *self } }
                 impl KiroTruthy for f64 { fn kiro_truthy(&self) -> bool { *self != 0.0 } }
+                impl KiroTruthy for i64 { fn kiro_truthy(&self) -> bool { *self != 0 } }
                 impl<T, E> KiroTruthy for Result<T, E> { fn kiro_truthy(&self) -> bool { self.is_ok() } }
                 "#,
             );
+            for (name, desc) in crate::grammar::BUILTIN_ERRORS {
+                output.push_str(&format!("{}\n", error::plain_error_helper(name, desc)));
+            }
         } else {
             // Submodules use the shared runtime
             output.push_str("use crate::*;\n");
@@ -145,38 +408,61 @@ impl Compiler {
         // 0. Pre-Scan Functions for Metadata (Purity Check)
         // 0. Pre-Scan Functions for Metadata (Purity Check)
         for stmt in &program.statements {
-            match stmt {
-                grammar::Statement::Documented { doc, item } => {
-                    if let grammar::AnnotatableItem::FunctionDef(def) = item {
-                        let is_pure = def.pure_kw.is_some();
-                        let can_error = def.can_error.is_some();
-                        let doc_str = Some(
-                            doc.iter()
-                                .map(|d| d.content.trim_start_matches("///").trim().to_string())
-                                .collect::<Vec<_>>()
-                                .join("\n"),
-                        );
-                        self.functions.insert(
-                            def.name.clone(),
-                            FunctionInfo {
-                                is_pure,
-                                can_error,
-                                doc: doc_str,
-                            },
+            self.span = stmt.span;
+            match &stmt.value {
+                grammar::Statement::Documented {
+                    doc,
+                    item: grammar::AnnotatableItem::FunctionDef(def),
+                } => {
+                    let doc_str = Some(
+                        doc.iter()
+                            .map(|d| d.content.trim_start_matches("///").trim().to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                    self.functions
+                        .insert(def.name.clone(), FunctionInfo::from_def(def, doc_str));
+                }
+                grammar::Statement::FunctionDef(def) => {
+                    self.functions
+                        .insert(def.name.clone(), FunctionInfo::from_def(def, None));
+                }
+                // Enums are registered up-front so matches can be checked regardless of order
+                grammar::Statement::EnumDef(def)
+                | grammar::Statement::Documented {
+                    item: grammar::AnnotatableItem::EnumDef(def),
+                    ..
+                } => {
+                    self.register_enum(def);
+                }
+                grammar::Statement::StructDef(def)
+                | grammar::Statement::Documented {
+                    item: grammar::AnnotatableItem::StructDef(def),
+                    ..
+                } => {
+                    self.structs
+                        .insert(def.name.value.clone(), def.fields.clone());
+                }
+                grammar::Statement::ImplBlock(block) => {
+                    self.register_impl(block);
+                }
+                grammar::Statement::ErrorDef { name, fields, .. } => {
+                    self.register_error(name, fields);
+                }
+                grammar::Statement::ConstDecl {
+                    name, const_type, ..
+                } => {
+                    if self.consts.contains_key(name) {
+                        self.error_on(
+                            Kind::Compile,
+                            name,
+                            format!("'{}' is already declared.", name),
                         );
                     }
+                    self.consts.insert(name.clone(), const_type.clone());
                 }
-                grammar::Statement::FunctionDef(def) => {
-                    let is_pure = def.pure_kw.is_some();
-                    let can_error = def.can_error.is_some();
-                    self.functions.insert(
-                        def.name.clone(),
-                        FunctionInfo {
-                            is_pure,
-                            can_error,
-                            doc: None,
-                        },
-                    );
+                grammar::Statement::InterfaceDef(def) => {
+                    self.register_interface(def);
                 }
                 _ => {}
             }
@@ -184,15 +470,23 @@ impl Compiler {
 
         for statement in program.statements {
             // Check if it should be hoisted
-            let is_hoisted = match &statement {
-                grammar::Statement::Import { .. } | grammar::Statement::StructDef(_) => true,
+            let is_hoisted = match &statement.value {
+                grammar::Statement::Import { .. }
+                | grammar::Statement::ConstDecl { .. }
+                | grammar::Statement::StructDef(_)
+                | grammar::Statement::EnumDef(_)
+                | grammar::Statement::InterfaceDef(_) => true,
                 grammar::Statement::Documented { item, .. } => {
-                    matches!(item, grammar::AnnotatableItem::StructDef(_))
+                    matches!(
+                        item,
+                        grammar::AnnotatableItem::StructDef(_)
+                            | grammar::AnnotatableItem::EnumDef(_)
+                    )
                 }
                 _ => false,
             };
 
-            let line = self.compile_statement(statement);
+            let line = self.compile_spanned(statement);
 
             if is_hoisted {
                 top_level.push_str(&format!("{}\n", line));
@@ -0,0 +1,133 @@
+use super::{Compiler, VarInfo};
+use crate::diagnostics::{Diagnostic, Kind};
+use crate::scope::Scopes;
+
+// Move state when a set of alternative branches starts, and the joined state of the
+// branches that reach the code after them
+pub struct Branches {
+    start: Scopes<VarInfo>,
+    joined: Option<Scopes<VarInfo>>,
+}
+
+fn join_moved(info: &mut VarInfo, other: &VarInfo) {
+    if info.moved.is_none() {
+        info.moved = other.moved;
+    }
+}
+
+impl Compiler {
+    // move x -> std::mem::take. A variable declared outside the enclosing loop cannot be
+    // moved inside it: the next iteration would move it again.
+    pub fn compile_move(&mut self, name: &str) -> String {
+        if self.in_pure_context {
+            self.error(Kind::Purity, "'move' is forbidden in pure functions.");
+            return String::new();
+        }
+        match self.known_vars.get(name) {
+            Some(info) if !info.is_mutable => self.error_on(
+                Kind::Mutability,
+                name,
+                format!("Cannot move immutable variable '{}'.", name),
+            ),
+            Some(_) => {}
+            None => self.error_on(
+                Kind::Compile,
+                name,
+                format!("Variable '{}' not found.", name),
+            ),
+        }
+        self.check_not_moved(name);
+        if let Some(&depth) = self.loop_depths.last()
+            && self
+                .known_vars
+                .frame_of(name)
+                .is_some_and(|frame| frame < depth)
+        {
+            self.error_on(
+                Kind::Move,
+                name,
+                format!(
+                    "Cannot move '{}' inside a loop, it is declared outside the loop.",
+                    name
+                ),
+            );
+        }
+
+        let site = self.span;
+        if let Some(info) = self.known_vars.get_mut(name) {
+            info.moved = Some(site);
+        }
+        format!("std::mem::take(&mut {})", name)
+    }
+
+    // Reading a variable whose value was moved out on some path to here. Only the first
+    // such use is reported.
+    pub fn check_not_moved(&mut self, name: &str) {
+        if let Some(site) = self
+            .known_vars
+            .get_mut(name)
+            .and_then(|info| info.moved.take())
+        {
+            let message = format!("Variable '{}' was moved and cannot be used.", name);
+            let error = Diagnostic::new(Kind::Move, message, self.span)
+                .focus(name)
+                .label("used after the move")
+                .related(site, format!("move {}", name), "moved here");
+            self.diagnostics.push(error);
+        }
+    }
+
+    // Assigning a new value makes a moved variable usable again
+    pub fn restore_moved(&mut self, name: &str) {
+        if let Some(info) = self.known_vars.get_mut(name) {
+            info.moved = None;
+        }
+    }
+
+    pub fn start_branches(&self) -> Branches {
+        Branches {
+            start: self.known_vars.clone(),
+            joined: None,
+        }
+    }
+
+    // Ends one branch and rewinds to the starting state for the next. A branch that
+    // returns, breaks or continues doesn't reach the code after the branches.
+    pub fn end_branch(&mut self, branches: &mut Branches, diverges: bool) {
+        let ended = std::mem::replace(&mut self.known_vars, branches.start.clone());
+        if diverges {
+            return;
+        }
+        match &mut branches.joined {
+            Some(joined) => joined.merge(&ended, join_moved),
+            None => branches.joined = Some(ended),
+        }
+    }
+
+    // Afterwards a variable counts as moved if any branch reaching this point moved it.
+    // When the branches are not exhaustive (no `else`), skipping all of them is a path too.
+    pub fn join_branches(&mut self, branches: Branches, exhaustive: bool) {
+        let Branches { start, joined } = branches;
+        self.known_vars = match joined {
+            Some(mut joined) => {
+                if !exhaustive {
+                    joined.merge(&start, join_moved);
+                }
+                joined
+            }
+            None => start,
+        };
+    }
+
+    // A loop body may run zero or more times
+    pub fn enter_loop(&mut self) -> Branches {
+        self.loop_depths.push(self.known_vars.depth());
+        self.start_branches()
+    }
+
+    pub fn exit_loop(&mut self, mut branches: Branches) {
+        self.end_branch(&mut branches, false);
+        self.join_branches(branches, false);
+        self.loop_depths.pop();
+    }
+}
@@ -0,0 +1,209 @@
+use super::Compiler;
+use crate::diagnostics::Kind;
+use crate::grammar::grammar::{Expression, KiroType};
+
+// int and num never mix implicitly. Integral literals are `num` unless they sit next to an
+// int operand or flow into a place declared `int`; both cases need the static type of an
+// expression, which is tracked as far as declarations allow.
+impl Compiler {
+    // Best-effort static type of an expression; None when it cannot be known here
+    pub fn static_type(&self, expr: &Expression) -> Option<KiroType> {
+        match expr {
+            Expression::Number(_) => Some(KiroType::Num),
+            Expression::StringLit(_) => Some(KiroType::Str),
+            Expression::BoolLit(_)
+            | Expression::Eq(..)
+            | Expression::Neq(..)
+            | Expression::Gt(..)
+            | Expression::Lt(..)
+            | Expression::Geq(..)
+            | Expression::Leq(..)
+            | Expression::And(..)
+            | Expression::Or(..)
+            | Expression::Not(..)
+            | Expression::Contains(..) => Some(KiroType::Bool),
+            Expression::Len(..) => Some(KiroType::Int),
+            Expression::Variable(v) if self.consts.contains_key(&v.value) => {
+                self.consts.get(&v.value).cloned()
+            }
+            Expression::ErrorRef(name) => self.consts.get(&name.value).cloned(),
+            Expression::Variable(v) | Expression::MoveExpr(_, v) => self.var_type(&v.value),
+            Expression::Add(lhs, _, rhs)
+            | Expression::Sub(lhs, _, rhs)
+            | Expression::Mul(lhs, _, rhs)
+            | Expression::Div(lhs, _, rhs)
+            | Expression::Mod(lhs, _, rhs) => {
+                let (lt, rt) = (self.static_type(lhs), self.static_type(rhs));
+                // An int literal takes the type of the other side
+                if lhs.int_literal().is_some() {
+                    rt.or(lt)
+                } else {
+                    lt.or(rt)
+                }
+            }
+            Expression::Neg(_, operand) | Expression::Try(_, operand) => self.static_type(operand),
+            Expression::SomeExpr(_, value) => {
+                Some(KiroType::Opt((), Box::new(self.static_type(value)?)))
+            }
+            Expression::StructInit(name, ..) if self.errors.contains_key(&name.value) => None,
+            Expression::StructInit(name, ..) => Some(KiroType::Custom(name.clone())),
+            Expression::FieldAccess(target, _, field) => {
+                let name = match self.static_type(target)? {
+                    KiroType::Custom(name) => name.value,
+                    KiroType::Adr(_, inner) => match *inner {
+                        KiroType::Custom(name) => name.value,
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                self.structs
+                    .get(&name)?
+                    .iter()
+                    .find(|f| f.name.value == field.value)
+                    .map(|f| f.field_type.clone())
+            }
+            Expression::At(col, _, _) => match self.static_type(col)? {
+                KiroType::List(_, item) => Some(*item),
+                KiroType::Map(_, _, value) => Some(*value),
+                _ => None,
+            },
+            Expression::Deref(_, target) => match self.static_type(target)? {
+                KiroType::Adr(_, inner) => Some(*inner),
+                _ => None,
+            },
+            Expression::ListInit(_, item, ..) => Some(KiroType::List((), Box::new(item.clone()))),
+            Expression::MapInit(_, key, value, ..) => Some(KiroType::Map(
+                (),
+                Box::new(key.clone()),
+                Box::new(value.clone()),
+            )),
+            Expression::TupleLit(list) if list.items.len() >= 2 => {
+                let items: Option<Vec<KiroType>> =
+                    list.items.iter().map(|e| self.static_type(e)).collect();
+                Some(KiroType::tuple(items?))
+            }
+            Expression::Call(func, ..) => self.call_return_type(func),
+            Expression::Lambda(def) => Some(KiroType::func(
+                def.params.iter().map(|p| p.command_type.clone()).collect(),
+                def.return_type.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn call_return_type(&self, func: &Expression) -> Option<KiroType> {
+        match func {
+            Expression::Variable(v) if self.is_conversion(&v.value) => Some(if v.value == "int" {
+                KiroType::Int
+            } else {
+                KiroType::Num
+            }),
+            Expression::Variable(v) if !self.known_vars.contains(&v.value) => {
+                self.functions.get(&v.value)?.return_type.clone()
+            }
+            Expression::FieldAccess(target, _, field) if !self.is_module(target) => {
+                match self.find_method(&field.value) {
+                    Some(info) => info.return_type,
+                    None => self.fn_value_return_type(func),
+                }
+            }
+            _ => self.fn_value_return_type(func),
+        }
+    }
+
+    fn is_module(&self, expr: &Expression) -> bool {
+        matches!(expr, Expression::Variable(m) if self.imported_modules.contains(&m.value))
+    }
+
+    fn fn_value_return_type(&self, func: &Expression) -> Option<KiroType> {
+        match self.static_type(func)? {
+            KiroType::Func(.., ret) => ret.map(|r| *r.ret),
+            _ => None,
+        }
+    }
+
+    // `int(x)` / `num(x)`, unless a function or variable of that name shadows the builtin
+    pub fn is_conversion(&self, name: &str) -> bool {
+        (name == "int" || name == "num")
+            && !self.functions.contains_key(name)
+            && !self.known_vars.contains(name)
+    }
+
+    // Printing a value of a type parameter gives that parameter a Display bound
+    pub fn note_displayed(&mut self, expr: &Expression) {
+        if let Some(KiroType::Custom(name)) = self.static_type(expr) {
+            self.displayed.insert(name.value);
+        }
+    }
+
+    // A value headed for a declared type (parameter, field, element, return value,
+    // reassignment)
+    pub fn compile_expecting(&mut self, expr: Expression, expected: &KiroType) -> String {
+        if let Expression::Match(_, subject, _, arms, _) = expr {
+            return self.compile_match(*subject, arms, false, Some(expected));
+        }
+        if let KiroType::Opt(_, inner) = expected {
+            return self.compile_optional(expr, inner);
+        }
+        if let (KiroType::Int, Some(v)) = (expected, expr.int_literal()) {
+            return format!("({}i64)", v);
+        }
+        if let (Some(types), Expression::TupleLit(list)) = (expected.tuple_items(), &expr)
+            && types.len() == list.items.len()
+        {
+            let Expression::TupleLit(list) = expr else {
+                unreachable!()
+            };
+            let items: Vec<String> = list
+                .items
+                .into_iter()
+                .zip(&types)
+                .map(|(item, t)| self.compile_expecting(item, t))
+                .collect();
+            return format!("({})", items.join(", "));
+        }
+        match (expected, self.static_type(&expr)) {
+            (KiroType::Int, Some(KiroType::Num)) => {
+                self.error_help(Kind::Type, "Expected int, got num.", "Convert with int(x).");
+                self.compile_expr(expr)
+            }
+            (KiroType::Num, Some(KiroType::Int)) => {
+                self.error_help(Kind::Type, "Expected num, got int.", "Convert with num(x).");
+                self.compile_expr(expr)
+            }
+            // Generic parameters accept any value, optionals included
+            (KiroType::Custom(_), _) => self.compile_expr(expr),
+            _ => self.compile_checked(expr),
+        }
+    }
+
+    // Operands of a binary operator. Returns whether the operation is on ints, which
+    // use the checked `KiroInt` arithmetic.
+    pub fn compile_operands(&mut self, lhs: Expression, rhs: Expression) -> (String, String, bool) {
+        let (lt, rt) = (self.static_type(&lhs), self.static_type(&rhs));
+        let is_int = |t: &Option<KiroType>| matches!(t, Some(KiroType::Int));
+        let is_num = |t: &Option<KiroType>| matches!(t, Some(KiroType::Num));
+
+        let lhs_int = is_int(&lt) || (lhs.int_literal().is_some() && is_int(&rt));
+        let rhs_int = is_int(&rt) || (rhs.int_literal().is_some() && is_int(&lt));
+        if (lhs_int && is_num(&rt) && !rhs_int) || (rhs_int && is_num(&lt) && !lhs_int) {
+            self.error_help(
+                Kind::Type,
+                "Cannot mix int and num.",
+                "Convert with int(x) or num(x).",
+            );
+        }
+
+        let l = if lhs_int {
+            self.compile_expecting(lhs, &KiroType::Int)
+        } else {
+            self.compile_checked(lhs)
+        };
+        let r = if rhs_int {
+            self.compile_expecting(rhs, &KiroType::Int)
+        } else {
+            self.compile_checked(rhs)
+        };
+        (l, r, lhs_int || rhs_int)
+    }
+}
@@ -0,0 +1,40 @@
+use super::Compiler;
+use crate::diagnostics::Kind;
+use crate::grammar::grammar::{Expression, KiroType};
+
+// `opt T` lowers to `Option<T>`. An optional only yields its value through
+// `on (x is some v)`; any other use of it is rejected here instead of by rustc.
+impl Compiler {
+    // A value used directly: operands, conditions, field access, printing
+    pub fn compile_checked(&mut self, expr: Expression) -> String {
+        if let Some(KiroType::Opt(..)) = self.static_type(&expr) {
+            self.error_help(
+                Kind::Type,
+                "Optional value used without checking it.",
+                "Use `on (x is some v)`.",
+            );
+        }
+        self.compile_expr(expr)
+    }
+
+    // A value headed for an `opt T` place: `none`, `some x` or another optional
+    pub fn compile_optional(&mut self, expr: Expression, inner: &KiroType) -> String {
+        match expr {
+            Expression::NoneLit(_) => "None".to_string(),
+            Expression::SomeExpr(_, value) => {
+                format!("Some({})", self.compile_expecting(*value, inner))
+            }
+            other => match self.static_type(&other) {
+                Some(KiroType::Opt(..)) | None => self.compile_expr(other),
+                Some(_) => {
+                    self.error_help(
+                        Kind::Type,
+                        "Expected an optional value.",
+                        "Wrap it with `some x`.",
+                    );
+                    self.compile_expr(other)
+                }
+            },
+        }
+    }
+}
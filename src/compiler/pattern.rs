@@ -0,0 +1,168 @@
+use super::Compiler;
+use crate::diagnostics::Kind;
+use crate::exhaustive::check_match_exhaustive;
+use crate::grammar::grammar::{self, ArmBody, Expression, KiroType, Pattern};
+
+impl Compiler {
+    pub fn register_enum(&mut self, def: &grammar::EnumDef) {
+        let mut variants: Vec<(String, usize)> = Vec::new();
+        for v in &def.variants {
+            if variants.iter().any(|(name, _)| *name == v.name.value) {
+                self.error(
+                    Kind::Compile,
+                    format!(
+                        "Duplicate variant '{}' in enum '{}'.",
+                        v.name.value, def.name.value
+                    ),
+                );
+                continue;
+            }
+            let arity = v.payload.as_ref().map(|p| p.types.len()).unwrap_or(0);
+            variants.push((v.name.value.clone(), arity));
+        }
+        self.enums.insert(def.name.value.clone(), variants);
+    }
+
+    // Compiles a pattern to a Rust pattern plus guard conditions on the matched value.
+    // Literals and ranges go through guards since f64/String can't be matched directly.
+    pub fn compile_pattern(&mut self, pattern: &Pattern) -> (String, Vec<String>) {
+        match pattern {
+            Pattern::Wildcard(_) => ("_".to_string(), vec![]),
+            Pattern::Binding(name) => {
+                self.declare_var(&name.value, false, None);
+                (name.value.clone(), vec![])
+            }
+            Pattern::Variant(enum_name, _, variant, bindings) => {
+                let names: Vec<String> = bindings
+                    .as_ref()
+                    .map(|b| b.names.iter().map(|n| n.value.clone()).collect())
+                    .unwrap_or_default();
+                for name in names.iter().filter(|n| *n != "_") {
+                    self.declare_var(name, false, None);
+                }
+                if names.is_empty() {
+                    (format!("{}::{}", enum_name.value, variant.value), vec![])
+                } else {
+                    (
+                        format!(
+                            "{}::{}({})",
+                            enum_name.value,
+                            variant.value,
+                            names.join(", ")
+                        ),
+                        vec![],
+                    )
+                }
+            }
+            Pattern::Struct(struct_name, _, fields, _) => {
+                let names: Vec<String> = fields.iter().map(|f| f.value.clone()).collect();
+                for name in &names {
+                    self.declare_var(name, false, None);
+                }
+                let mut parts = names;
+                parts.push("..".to_string());
+                (
+                    format!("{} {{ {} }}", struct_name.value, parts.join(", ")),
+                    vec![],
+                )
+            }
+            // Numeric patterns compare as num so they match int and num subjects alike
+            Pattern::Range(start, _, end) => {
+                let start = self.compile_expr(Expression::Number(start.clone()));
+                let end = self.compile_expr(Expression::Number(end.clone()));
+                (
+                    "__kiro_m".to_string(),
+                    vec![format!("({}..{}).contains(&(__kiro_m as f64))", start, end)],
+                )
+            }
+            Pattern::Number(num) => {
+                let lit = self.compile_expr(Expression::Number(num.clone()));
+                (
+                    "__kiro_m".to_string(),
+                    vec![format!("(__kiro_m as f64) == {}", lit)],
+                )
+            }
+            Pattern::StringLit(s) => {
+                let text = s.text().unwrap_or_else(|e| {
+                    self.error(Kind::Compile, e);
+                    String::new()
+                });
+                (
+                    "__kiro_m".to_string(),
+                    vec![format!("__kiro_m == {:?}", text)],
+                )
+            }
+            Pattern::BoolLit(b) => {
+                let lit = self.compile_expr(Expression::BoolLit(b.clone()));
+                (lit, vec![])
+            }
+        }
+    }
+
+    // In statement position arm values are discarded so bodies of different types still unify.
+    // Arm values headed for a declared type are compiled against it.
+    pub fn compile_match(
+        &mut self,
+        subject: Expression,
+        arms: Vec<grammar::MatchArm>,
+        as_statement: bool,
+        expected: Option<&KiroType>,
+    ) -> String {
+        let coverage: Vec<(&Pattern, bool)> = arms
+            .iter()
+            .map(|arm| (&arm.pattern, arm.guard.is_some()))
+            .collect();
+        let subject_type = self.static_type(&subject);
+        if let Err(e) =
+            check_match_exhaustive(&self.enums, &self.structs, subject_type.as_ref(), &coverage)
+        {
+            match e.focus {
+                Some(name) => self.error_on(Kind::Compile, &name, e.message),
+                None => self.error(Kind::Compile, e.message),
+            }
+        }
+
+        let subject_str = self.compile_expr(subject);
+        let mut branches = self.start_branches();
+        let arm_strs: Vec<String> = arms
+            .into_iter()
+            .map(|arm| {
+                // Pattern bindings are scoped to their arm
+                self.enter_scope();
+                let (pat, mut conds) = self.compile_pattern(&arm.pattern);
+                if let Some(guard) = arm.guard {
+                    conds.push(format!(
+                        "({}).kiro_truthy()",
+                        self.compile_expr(guard.condition)
+                    ));
+                }
+                let guard_str = if conds.is_empty() {
+                    String::new()
+                } else {
+                    format!(" if {}", conds.join(" && "))
+                };
+
+                let (body, diverges) = match arm.body {
+                    ArmBody::Block(block) => {
+                        let diverges = block.diverges();
+                        (self.compile_block(block), diverges)
+                    }
+                    ArmBody::Value(expr) => match expected {
+                        Some(t) => (self.compile_expecting(expr, t), false),
+                        None => (self.compile_expr(expr), false),
+                    },
+                };
+                self.exit_scope();
+                self.end_branch(&mut branches, diverges);
+                if as_statement {
+                    format!("{}{} => {{ {}; }}", pat, guard_str, body)
+                } else {
+                    format!("{}{} => {},", pat, guard_str, body)
+                }
+            })
+            .collect();
+        // Matches are exhaustive, so one of the arms always runs
+        self.join_branches(branches, true);
+        format!("match {} {{ {} }}", subject_str, arm_strs.join("\n"))
+    }
+}
@@ -1,26 +1,40 @@
 use super::Compiler;
-use super::types::compile_type;
+use super::moves::Branches;
+use super::types::{compile_type, compile_type_param_names, compile_type_params};
+use crate::diagnostics::Kind;
 use crate::grammar::grammar::{self, Statement};
+use rust_sitter::Spanned;
 
 impl Compiler {
+    // Errors found while compiling a statement point at its span
+    pub fn compile_spanned(&mut self, statement: Spanned<Statement>) -> String {
+        let outer_span = std::mem::replace(&mut self.span, statement.span);
+        let out = self.compile_statement(statement.value);
+        self.span = outer_span;
+        out
+    }
+
     pub fn compile_statement(&mut self, statement: Statement) -> String {
         match statement {
             // Error Definition: error NotFound = "Description"
             Statement::ErrorDef {
                 name, description, ..
             } => {
-                // d.value.value contains the raw string WITH quotes, we need to strip them
                 let desc = description
-                    .map(|d| d.value.value.trim_matches('"').to_string())
+                    .map(|d| {
+                        d.value.text().unwrap_or_else(|e| {
+                            self.error(Kind::Compile, e);
+                            String::new()
+                        })
+                    })
                     .unwrap_or_else(|| name.clone());
-                // Generate a helper function that creates an anyhow error
-                format!(
-                    "fn kiro_error_{name}() -> anyhow::Error {{ anyhow::anyhow!(\"{desc}\").context(\"{name}\") }}"
-                )
+                self.compile_error_def(&name, &desc)
             }
             // 1. Compile Struct Definition
             // 1. Compile Struct Definition
             Statement::StructDef(def) => {
+                self.structs
+                    .insert(def.name.value.clone(), def.fields.clone());
                 let name = def.name;
                 let fields = def.fields;
                 let field_strs: Vec<String> = fields
@@ -28,11 +42,43 @@ impl Compiler {
                     .map(|f| format!("pub {}: {}", f.name.value, compile_type(&f.field_type)))
                     .collect();
 
-                // We add #[derive(Clone, Debug, PartialEq)] and impl KiroGet
+                let generics = compile_type_param_names(&def.type_params);
+
+                // We add #[derive(Clone, Debug, PartialEq)] and impl KiroGet/KiroSet
                 format!(
-                    "#[derive(Clone, Debug)]\npub struct {0} {{ {1} }}\nimpl KiroGet for {0} {{ type Inner = Self; fn kiro_get<R>(&self, f: impl FnOnce(&Self::Inner) -> R) -> R {{ f(self) }} }}",
+                    "#[derive(Clone, Debug)]\npub struct {0}{2} {{ {1} }}\nimpl{2} KiroGet for {0}{2} {{ type Inner = Self; fn kiro_get<R>(&self, f: impl FnOnce(&Self::Inner) -> R) -> R {{ f(self) }} }}\nimpl{2} KiroSet for {0}{2} {{ fn kiro_set(&mut self, v: Self) {{ *self = v; }} }}",
                     name.value,
-                    field_strs.join(", ")
+                    field_strs.join(", "),
+                    generics
+                )
+            }
+            // Enum Definition -> Rust enum (registered during pre-scan)
+            Statement::EnumDef(def) => {
+                if !self.enums.contains_key(&def.name.value) {
+                    self.register_enum(&def);
+                }
+                let variant_strs: Vec<String> = def
+                    .variants
+                    .iter()
+                    .map(|v| match &v.payload {
+                        Some(p) => format!(
+                            "{}({})",
+                            v.name.value,
+                            p.types
+                                .iter()
+                                .map(compile_type)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                        None => v.name.value.clone(),
+                    })
+                    .collect();
+
+                // impl AsKiroLoopVar so enum values can be iterated from lists
+                format!(
+                    "#[derive(Clone, Debug)]\npub enum {0} {{ {1} }}\nimpl AsKiroLoopVar for {0} {{ type Out = Self; fn as_kiro(self) -> Self {{ self }} }}",
+                    def.name.value,
+                    variant_strs.join(", ")
                 )
             }
             // 6. Import Statement
@@ -42,69 +88,235 @@ impl Compiler {
             }
             // 1. Variable Declaration
             Statement::VarDecl { ident, value, .. } => {
+                self.track_fn_var(&ident, &value);
+                let var_type = self.static_type(&value);
                 let val_str = self.compile_expr(value);
-                self.known_vars
-                    .insert(ident.clone(), super::VarInfo { is_mutable: true });
-                // Track local vars in pure scope
-                if self.in_pure_context {
-                    self.pure_scope_params.insert(ident.clone());
-                }
+                self.declare_var(&ident, true, var_type);
                 // In Kiro, vars are mutable by default
                 format!("let mut {} = {};", ident, val_str)
             }
 
+            // const NAME: T = v -> a static; literals are built by rustc, anything else on
+            // first use. Initializers are compiled as pure code.
+            Statement::ConstDecl {
+                name,
+                const_type,
+                value,
+                ..
+            } => {
+                let is_literal = matches!(
+                    const_type,
+                    grammar::KiroType::Num | grammar::KiroType::Int | grammar::KiroType::Bool
+                ) && match &value {
+                    grammar::Expression::Number(_) | grammar::Expression::BoolLit(_) => true,
+                    grammar::Expression::Neg(_, operand) => {
+                        matches!(**operand, grammar::Expression::Number(_))
+                    }
+                    _ => false,
+                };
+                let old_context = std::mem::replace(&mut self.in_pure_context, true);
+                let old_pure_params = std::mem::take(&mut self.pure_scope_params);
+                let val_str = self.compile_expecting(value, &const_type);
+                self.in_pure_context = old_context;
+                self.pure_scope_params = old_pure_params;
+
+                let init = if is_literal {
+                    format!("KiroConst::Value({})", val_str)
+                } else {
+                    format!("KiroConst::Lazy(std::sync::LazyLock::new(|| {}))", val_str)
+                };
+                format!(
+                    "pub static {}: KiroConst<{}> = {};",
+                    name,
+                    compile_type(&const_type),
+                    init
+                )
+            }
+
+            // var (a, b) = pair -> let (mut a, mut b) = pair;
+            Statement::VarTuple { names, value, .. } => {
+                let names = names.values();
+                let value_type = self.static_type(&value);
+                let val_str = self.compile_expr(value);
+                self.bind_tuple(&names, value_type, true);
+                let pattern: Vec<String> = names
+                    .iter()
+                    .map(|n| match n.as_str() {
+                        "_" => "_".to_string(),
+                        _ => format!("mut {}", n),
+                    })
+                    .collect();
+                format!("let ({}) = {};", pattern.join(", "), val_str)
+            }
+
+            // (a, b) = pair: each name is assigned or declared like `a = ...`
+            Statement::AssignStmt {
+                lhs: grammar::Expression::TupleLit(list),
+                rhs,
+                ..
+            } => {
+                let mut names = Vec::new();
+                for item in list.items {
+                    match item {
+                        grammar::Expression::Variable(grammar::VariableVal { value: name })
+                        | grammar::Expression::ErrorRef(grammar::StructNameVal { value: name })
+                            if self.consts.contains_key(&name) =>
+                        {
+                            let message = format!("'{}' is a constant.", name);
+                            self.error_on(Kind::Mutability, &name, message);
+                            names.push("_".to_string());
+                        }
+                        grammar::Expression::Variable(v) => names.push(v.value),
+                        _ => {
+                            self.error(Kind::Compile, "Only names can be destructured.");
+                            names.push("_".to_string());
+                        }
+                    }
+                }
+                let value_type = self.static_type(&rhs);
+                let mut out = format!("let __kiro_tuple = {};", self.compile_expr(rhs));
+
+                for (i, name) in names.iter().enumerate().filter(|(_, n)| *n != "_") {
+                    match self.known_vars.get(name) {
+                        Some(info) if info.is_mutable => {
+                            out.push_str(&format!(" {}.kiro_assign(__kiro_tuple.{});", name, i));
+                            self.restore_moved(name);
+                        }
+                        Some(_) => {
+                            let message = format!("Cannot mutate immutable variable '{}'.", name);
+                            self.error_on(Kind::Mutability, name, message);
+                        }
+                        None => out.push_str(&format!(" let {} = __kiro_tuple.{};", name, i)),
+                    }
+                }
+
+                // Names already declared keep their type; new ones take the element type
+                let new_names: Vec<String> = names
+                    .iter()
+                    .map(|n| match self.known_vars.contains(n) {
+                        true => "_".to_string(),
+                        false => n.clone(),
+                    })
+                    .collect();
+                self.bind_tuple(&new_names, value_type, false);
+                out
+            }
+
             // ... (Middle assignments kept same, just copying context) ...
             Statement::AssignStmt { lhs, rhs, .. } => {
-                let rhs_str = self.compile_expr(rhs);
+                if let grammar::Expression::Variable(grammar::VariableVal { value: name })
+                | grammar::Expression::ErrorRef(grammar::StructNameVal { value: name }) = &lhs
+                    && self.consts.contains_key(name)
+                {
+                    let message = format!("'{}' is a constant.", name);
+                    self.error_on(Kind::Mutability, name, message);
+                    return String::new();
+                }
+                // A new name takes the type of its value
+                let new_var_type = match &lhs {
+                    grammar::Expression::Variable(v) if !self.known_vars.contains(&v.value) => {
+                        self.track_fn_var(&v.value, &rhs);
+                        Some(self.static_type(&rhs))
+                    }
+                    _ => None,
+                };
+                // Reassignments keep the declared type of the target
+                let rhs_str = match new_var_type.is_none().then(|| self.static_type(&lhs)) {
+                    Some(Some(t)) => self.compile_expecting(rhs, &t),
+                    _ => self.compile_expr(rhs),
+                };
 
                 match lhs {
                     grammar::Expression::Variable(v) => {
                         let name = v.value;
                         if let Some(info) = self.known_vars.get(&name) {
                             if info.is_mutable {
-                                // Mutable Assignment
+                                // Mutable Assignment, which also refills a moved variable
+                                self.restore_moved(&name);
                                 format!("{}.kiro_assign({});", name, rhs_str)
                             } else {
                                 // Immutable Assignment -> Error
-                                panic!(
-                                    "Compiler Error: Cannot mutate immutable variable '{}'.",
-                                    name
-                                );
+                                let message =
+                                    format!("Cannot mutate immutable variable '{}'.", name);
+                                self.error_on(Kind::Mutability, &name, message);
+                                String::new()
                             }
                         } else {
                             // Implicit Immutable Declaration (x = 10)
-                            self.known_vars
-                                .insert(name.clone(), super::VarInfo { is_mutable: false });
-                            // Track local vars in pure scope
-                            if self.in_pure_context {
-                                self.pure_scope_params.insert(name.clone());
-                            }
+                            self.declare_var(&name, false, new_var_type.flatten());
                             format!("let {} = {};", name, rhs_str)
                         }
                     }
+                    grammar::Expression::At(col, _, key) => {
+                        self.compile_set_at(*col, *key, rhs_str)
+                    }
                     _ => {
-                        // Complex LValue (e.g. x.y = 10)
-                        let lhs_str = self.compile_lvalue(lhs);
+                        // Complex LValue (e.g. x.y = 10): the root variable must be mutable
+                        let lhs_str = self.compile_place(lhs);
                         format!("{}.kiro_assign({});", lhs_str, rhs_str)
                     }
                 }
             }
+            Statement::CompoundAssign { lhs, op, rhs } => {
+                self.compile_statement(op.desugar(lhs, rhs))
+            }
             Statement::Print(_, expr) => {
                 if self.in_pure_context {
-                    panic!("Pure Function Error: 'print' is forbidden.");
+                    self.error(Kind::Purity, "'print' is forbidden in pure functions.");
                 }
-                let val = self.compile_expr(expr);
+                self.note_displayed(&expr);
+                let val = self.compile_checked(expr);
                 format!("println!(\"{{}}\", {});", val)
             }
+            // on (x is some v) -> if let Some(v) = x
             Statement::On {
                 condition,
+                some_check: Some(check),
                 body,
                 else_clause,
                 error_clauses,
                 ..
             } => {
-                let cond_str = self.compile_expr(condition.clone());
+                if error_clauses.is_some() {
+                    self.error(
+                        Kind::Compile,
+                        "'is some' cannot be combined with error handlers.",
+                    );
+                }
+                let inner = match self.static_type(&condition) {
+                    Some(grammar::KiroType::Opt(_, inner)) => Some(*inner),
+                    Some(_) => {
+                        self.error(Kind::Type, "'is some' needs an optional value.");
+                        None
+                    }
+                    None => None,
+                };
+                let value_str = self.compile_expr(condition);
+                let mut branches = self.start_branches();
+                let body_diverges = body.diverges();
+                self.enter_scope();
+                self.declare_var(&check.binding, false, inner);
                 let body_str = self.compile_block(body);
+                self.exit_scope();
+                self.end_branch(&mut branches, body_diverges);
+                let else_str = self.compile_else(else_clause, branches);
+                format!(
+                    "if let Some({}) = {} {} {}",
+                    check.binding, value_str, body_str, else_str
+                )
+            }
+            Statement::On {
+                condition,
+                body,
+                else_clause,
+                error_clauses,
+                ..
+            } => {
+                let cond_str = self.compile_checked(condition.clone());
+                let mut branches = self.start_branches();
+                let body_diverges = body.diverges();
+                let body_str = self.compile_block(body);
+                self.end_branch(&mut branches, body_diverges);
 
                 // Helper to flatten ErrorClauseList into Vec<&ErrorClause>
                 fn flatten_clauses(list: &grammar::ErrorClauseList) -> Vec<&grammar::ErrorClause> {
@@ -118,28 +330,41 @@ impl Compiler {
                 // If there are error clauses, generate a match on Result
                 if let Some(ref error_list) = error_clauses {
                     let clauses = flatten_clauses(error_list);
-                    let shadowing = if let grammar::Expression::Variable(v) = &condition {
-                        format!("let {} = __kiro_val;", v.value)
-                    } else {
-                        String::new()
+                    // A failable variable is matched by value: its `anyhow::Error` can't be cloned
+                    let (scrutinee, shadowing) = match &condition {
+                        grammar::Expression::Variable(v) => {
+                            (v.value.clone(), format!("let {} = __kiro_val;", v.value))
+                        }
+                        _ => (cond_str, String::new()),
                     };
 
                     // Build chained if/else if for multiple error handlers
                     let mut err_branches = Vec::new();
                     let mut has_catch_all = false;
 
+                    // A handler that finishes re-throws the error to the caller. Outside a
+                    // failable fn that leaves the function, or fails when it owes a value.
+                    let rethrow = match &self.return_type {
+                        _ if self.in_failable_fn => "return Err(__kiro_err);",
+                        Some(t) if !matches!(t, grammar::KiroType::Void) => {
+                            "kiro_unhandled(__kiro_err);"
+                        }
+                        _ => "return;",
+                    };
                     for clause in clauses.iter() {
-                        let block_body = self.compile_block(clause.body.clone());
-                        let clause_body = if self.in_failable_fn {
-                            format!("{} return Err(__kiro_err);", block_body)
-                        } else {
-                            block_body
-                        };
+                        self.enter_scope();
+                        let binding = self.bind_error(clause);
+                        let block_body =
+                            format!("{} {}", binding, self.compile_block(clause.body.clone()));
+                        self.exit_scope();
+                        self.end_branch(&mut branches, true);
+                        let clause_body = format!("{} {}", block_body, rethrow);
 
                         if let Some(ref err_type) = clause.error_type {
                             err_branches.push(format!(
-                                "if __kiro_err.to_string().contains(\"{}\") {{ {} }}",
-                                err_type, clause_body
+                                "if {} {{ {} }}",
+                                self.error_check(err_type),
+                                clause_body
                             ));
                         } else {
                             // Catch-all (must be last)
@@ -160,29 +385,35 @@ impl Compiler {
 
                     // Join with "else"
                     let err_branch = err_branches.join(" else ");
+                    // Unhandled errors propagate, so the handlers cover every error
+                    self.join_branches(branches, true);
 
                     format!(
                         "match {} {{ Ok(__kiro_val) => {{ {} {} }} Err(__kiro_err) => {{ {} }} }}",
-                        cond_str, shadowing, body_str, err_branch
+                        scrutinee, shadowing, body_str, err_branch
                     )
                 } else {
                     // Standard if/else
-                    let else_str = match else_clause {
-                        Some(clause) => format!("else {}", self.compile_block(clause.body)),
-                        None => String::new(),
-                    };
+                    let else_str = self.compile_else(else_clause, branches);
                     format!("if ({}).kiro_truthy() {} {}", cond_str, body_str, else_str)
                 }
             }
             Statement::LoopOn {
-                condition, body, ..
+                head,
+                condition,
+                body,
+                ..
             } => {
-                let cond_str = self.compile_expr(condition);
+                let cond_str = self.compile_checked(condition);
+                let label = self.enter_label(head);
+                let loop_moves = self.enter_loop();
                 let body_str = self.compile_block(body);
-                format!("while {} {}", cond_str, body_str)
+                self.exit_loop(loop_moves);
+                format!("{}while {} {}", self.exit_label(label), cond_str, body_str)
             }
             // 2. Iterator Loop -> Rust 'for' with injected logic
             Statement::LoopIter {
+                head,
                 iterator,
                 iterable,
                 step,
@@ -191,16 +422,56 @@ impl Compiler {
                 else_clause,
                 ..
             } => {
-                let range_str = self.compile_expr(iterable);
+                // Range loops count in int; list loops bind the element type, map loops
+                // (key, value)
+                let (item_type, is_map) = match &iterable {
+                    grammar::Expression::Range(..) | grammar::Expression::RangeInclusive(..) => {
+                        (Some(grammar::KiroType::Int), false)
+                    }
+                    other => match self.static_type(other) {
+                        Some(grammar::KiroType::List(_, item)) => (Some(*item), false),
+                        Some(grammar::KiroType::Str) => (Some(grammar::KiroType::Str), false),
+                        Some(grammar::KiroType::Map(_, key, value)) => {
+                            (Some(grammar::KiroType::tuple(vec![*key, *value])), true)
+                        }
+                        _ => (None, false),
+                    },
+                };
+                let range_str = self.compile_checked(iterable);
+
+                // `i, x` iterates (index, element) pairs; a map's items already are pairs
+                let iter_fn = match &iterator {
+                    grammar::LoopBinding::Pair(..) => "kiro_pairs",
+                    _ => "kiro_iter",
+                };
 
-                // Handle "per 5" -> .step_by(5)
+                // Handle "per 5" -> .kiro_iter_by(5), which checks the step at runtime
                 let iter_call = if let Some(s) = step {
-                    let step_val = self.compile_expr(s.value);
-                    format!("{}.kiro_iter().step_by({} as usize)", range_str, step_val)
+                    let step_val = self.compile_expecting(s.value, &grammar::KiroType::Int);
+                    format!("{}.{}_by({})", range_str, iter_fn, step_val)
                 } else {
-                    format!("{}.kiro_iter()", range_str)
+                    format!("{}.{}()", range_str, iter_fn)
                 };
 
+                // Loop variables are immutable and scoped to one iteration
+                let label = self.enter_label(head);
+                let loop_moves = self.enter_loop();
+                self.enter_scope();
+                match &iterator {
+                    grammar::LoopBinding::Name(name) => self.declare_var(name, false, item_type),
+                    grammar::LoopBinding::Tuple(names) => {
+                        self.bind_tuple(&names.values(), item_type, false)
+                    }
+                    grammar::LoopBinding::Pair(first, _, second) => {
+                        let pair_type = match is_map {
+                            true => item_type,
+                            false => item_type
+                                .map(|t| grammar::KiroType::tuple(vec![grammar::KiroType::Int, t])),
+                        };
+                        self.bind_tuple(&[first.clone(), second.value.clone()], pair_type, false)
+                    }
+                }
+
                 // Handle "on (cond)" -> Inject 'if/else' inside the loop body
                 let inner_logic = if let Some(f) = filter {
                     let cond_str = self.compile_expr(f.condition);
@@ -217,94 +488,63 @@ impl Compiler {
                     self.compile_block(body)
                 };
 
-                // Implicit Mutability Rule:
-                self.known_vars
-                    .insert(iterator.clone(), super::VarInfo { is_mutable: false });
-
-                format!(
-                    "for {}_temp in {} {{ let {} = {}_temp.as_kiro(); {} }}",
-                    iterator, iter_call, iterator, iterator, inner_logic
-                )
+                self.exit_scope();
+                self.exit_loop(loop_moves);
+                let label_str = self.exit_label(label);
+
+                let for_loop = match iterator {
+                    grammar::LoopBinding::Name(name) => format!(
+                        "for {}_temp in {} {{ let {} = {}_temp.as_kiro(); {} }}",
+                        name, iter_call, name, name, inner_logic
+                    ),
+                    grammar::LoopBinding::Tuple(names) => format!(
+                        "for __kiro_item in {} {{ let ({}) = __kiro_item.as_kiro(); {} }}",
+                        iter_call,
+                        names.values().join(", "),
+                        inner_logic
+                    ),
+                    grammar::LoopBinding::Pair(first, _, second) => format!(
+                        "for ({}, {}) in {} {}",
+                        first, second.value, iter_call, inner_logic
+                    ),
+                };
+                format!("{}{}", label_str, for_loop)
             }
             Statement::FunctionDef(def) => {
-                let name = def.name;
-                let params = def.params;
-                let return_type = def.return_type;
-                let body = def.body;
-                let pure_kw = def.pure_kw;
-                let can_error = def.can_error;
-                let is_pure = pure_kw.is_some();
-
                 // Preserve existing doc if present (from pre-scan)
-                let existing_doc = self.functions.get(&name).and_then(|f| f.doc.clone());
+                let existing_doc = self.functions.get(&def.name).and_then(|f| f.doc.clone());
 
                 self.functions.insert(
-                    name.clone(),
-                    super::FunctionInfo {
-                        is_pure,
-                        can_error: can_error.is_some(),
-                        doc: existing_doc,
-                    },
+                    def.name.clone(),
+                    super::FunctionInfo::from_def(&def, existing_doc),
                 );
 
-                let old_context = self.in_pure_context;
-                let old_pure_params = self.pure_scope_params.clone();
-                if is_pure {
-                    self.in_pure_context = true;
-                    // Populate allowed params for pure scope
-                    self.pure_scope_params.clear();
-                    for p in &params {
-                        self.pure_scope_params.insert(p.name.clone());
-                    }
-                }
-
-                let param_strs: Vec<String> = params
-                    .iter()
-                    .map(|p| format!("{}: {}", p.name, compile_type(&p.command_type)))
-                    .collect();
-
-                let can_error = can_error.is_some();
-                let old_in_failable = self.in_failable_fn;
-                if can_error {
-                    self.in_failable_fn = true;
-                }
-
-                let body_str = self.compile_block(body);
-
-                self.in_pure_context = old_context;
-                self.in_failable_fn = old_in_failable;
-                self.pure_scope_params = old_pure_params; // Restore
-
-                let ret_def = if let Some(rt) = return_type {
-                    if let crate::grammar::grammar::KiroType::Void = rt {
-                        "()".to_string()
-                    } else {
-                        compile_type(&rt)
-                    }
-                } else {
-                    "()".to_string()
-                };
-
-                let (ret_type, final_body) = if can_error {
-                    (
-                        format!("anyhow::Result<{}>", ret_def),
-                        format!("{{ let __kiro_res = {}; Ok(__kiro_res) }}", body_str),
-                    )
-                } else {
-                    (ret_def, body_str)
-                };
+                let is_pure = def.pure_kw.is_some();
+                let outer_displayed = std::mem::take(&mut self.displayed);
+                let (param_str, ret_type, final_body) = self.compile_fn_parts(
+                    None,
+                    &def.params,
+                    def.return_type,
+                    is_pure,
+                    def.can_error.is_some(),
+                    def.body,
+                );
 
                 let async_kw = if is_pure { "" } else { "async" };
+                let displayed = std::mem::replace(&mut self.displayed, outer_displayed);
 
                 format!(
-                    "pub {} fn {}({}) -> {} {}",
+                    "pub {} fn {}{}({}) -> {} {}",
                     async_kw,
-                    name,
-                    param_strs.join(", "),
+                    def.name,
+                    compile_type_params(&def.type_params, &displayed),
+                    param_str,
                     ret_type,
                     final_body
                 )
             }
+            Statement::ImplBlock(block) => self.compile_impl(block),
+            Statement::InterfaceDef(def) => self.compile_interface(def),
 
             // Rust-backed function (external glue)
             Statement::RustFnDecl(def) => {
@@ -319,6 +559,9 @@ impl Compiler {
                         is_pure: false,
                         can_error: can_error.is_some(),
                         doc: existing_doc,
+                        params: params.iter().map(|p| p.command_type.clone()).collect(),
+                        return_type: Some(return_type.clone()),
+                        is_generic: false,
                     },
                 );
 
@@ -352,7 +595,7 @@ impl Compiler {
 
                 let final_body = if can_error {
                     format!(
-                        "{{ match header::{}({}).await {{ Ok(v) => Ok(v.try_into()?), Err(e) => Err(anyhow::anyhow!(e.name.clone()).context(e.name)) }} }}",
+                        "{{ match header::{}({}).await {{ Ok(v) => Ok(v.try_into()?), Err(e) => {{ let name = e.name.clone(); Err(kiro_error(&name, e.into())) }} }} }}",
                         name, args_vec
                     )
                 } else {
@@ -377,14 +620,17 @@ impl Compiler {
                 )
             }
 
-            // 2. Expression Statement (Standard Call on its own line)
+            // Match in statement position: arm values are discarded
+            Statement::ExprStmt(grammar::Expression::Match(_, subject, _, arms, _)) => {
+                format!("{};", self.compile_match(*subject, arms, true, None))
+            }
             Statement::ExprStmt(expr) => {
                 let val = self.compile_expr(expr);
                 format!("{};", val)
             }
             Statement::Give(_, channel, value) => {
                 if self.in_pure_context {
-                    panic!("Pure Function Error: 'give' is forbidden.");
+                    self.error(Kind::Purity, "'give' is forbidden in pure functions.");
                 }
                 let ch = self.compile_expr(channel);
                 let val = self.compile_expr(value);
@@ -398,9 +644,26 @@ impl Compiler {
                 format!("{}.tx.close();", ch)
             }
             // 3. Return -> return ...
+            Statement::Return(..) if self.in_defer => {
+                self.error(Kind::Compile, "'return' cannot leave a 'defer' block.");
+                String::new()
+            }
+            Statement::Break(..) | Statement::Continue(..)
+                if self.in_defer && self.loop_depths.is_empty() =>
+            {
+                self.error(
+                    Kind::Compile,
+                    "'break' and 'continue' cannot leave a 'defer' block.",
+                );
+                String::new()
+            }
+            Statement::Defer(_, body) => self.compile_defer(body),
             Statement::Return(_, expr) => {
                 if let Some(e) = expr {
-                    let val = self.compile_expr(e);
+                    let val = match self.return_type.clone() {
+                        Some(ret) => self.compile_expecting(e, &ret),
+                        None => self.compile_expr(e),
+                    };
                     if self.in_failable_fn && !val.starts_with("Err(") {
                         // In failable context, wrap non-error returns in Ok(...)
                         // Unless it's already an Err(...) creation
@@ -417,12 +680,13 @@ impl Compiler {
                 }
             }
             // 4. Break -> break
-            Statement::Break(_) => "break;".to_string(),
+            Statement::Break(_, label) => format!("break{};", self.loop_target(label)),
             // 5. Continue -> continue
-            Statement::Continue(_) => "continue;".to_string(),
+            Statement::Continue(_, label) => format!("continue{};", self.loop_target(label)),
             Statement::Documented { item, .. } => {
                 let stmt = match item {
                     grammar::AnnotatableItem::StructDef(s) => Statement::StructDef(s),
+                    grammar::AnnotatableItem::EnumDef(e) => Statement::EnumDef(e),
                     grammar::AnnotatableItem::FunctionDef(f) => Statement::FunctionDef(f),
                     grammar::AnnotatableItem::RustFnDecl(r) => Statement::RustFnDecl(r),
                 };
@@ -431,17 +695,36 @@ impl Compiler {
         }
     }
 
+    // `else` branch of an `on`; without one, skipping the body is the other path
+    fn compile_else(
+        &mut self,
+        else_clause: Option<grammar::OffClause>,
+        mut branches: Branches,
+    ) -> String {
+        let Some(clause) = else_clause else {
+            self.join_branches(branches, false);
+            return String::new();
+        };
+        let diverges = clause.body.diverges();
+        let else_str = format!("else {}", self.compile_block(clause.body));
+        self.end_branch(&mut branches, diverges);
+        self.join_branches(branches, true);
+        else_str
+    }
+
+    // Every block is a scope: names declared inside are dropped when it ends
     pub fn compile_block(&mut self, block: grammar::Block) -> String {
+        self.enter_scope();
         let len = block.statements.len();
         let mut lines = Vec::new();
 
         for (i, stmt) in block.statements.iter().enumerate() {
-            let mut line = self.compile_statement(stmt.clone());
+            let mut line = self.compile_spanned(stmt.clone());
 
             // LOGIC: If this is the LAST statement...
             if i == len - 1 {
                 // Check if it's an ExprStmt (standalone expression)
-                if let grammar::Statement::ExprStmt(_) = stmt {
+                if let grammar::Statement::ExprStmt(_) = &stmt.value {
                     // Remove the trailing semicolon if it exists
                     if line.ends_with(';') {
                         line.pop();
@@ -450,6 +733,74 @@ impl Compiler {
             }
             lines.push(line);
         }
+        self.exit_scope();
         format!("{{\n{}\n}}", lines.join("\n"))
     }
+
+    // Declares the names of a destructured tuple with their element types. A known arity
+    // mismatch is an error.
+    // `loop@outer` makes `outer` a target for `break@outer` / `continue@outer` in the body
+    fn enter_label(&mut self, head: grammar::LoopHead) -> Option<String> {
+        let label = head.label.map(|l| l.name);
+        self.loop_labels.extend(label.clone());
+        label
+    }
+
+    // The Rust label to put before the loop: 'outer:
+    fn exit_label(&mut self, label: Option<String>) -> String {
+        match label {
+            Some(l) => {
+                self.loop_labels.pop();
+                format!("'{}: ", l)
+            }
+            None => String::new(),
+        }
+    }
+
+    fn loop_target(&mut self, label: Option<grammar::LoopLabel>) -> String {
+        match label {
+            Some(l) if !self.loop_labels.contains(&l.name) => {
+                let message = format!("No enclosing loop is labeled '{}'.", l.name);
+                self.error_on(Kind::Compile, &l.name, message);
+                String::new()
+            }
+            Some(l) => format!(" '{}", l.name),
+            None => String::new(),
+        }
+    }
+
+    fn bind_tuple(
+        &mut self,
+        names: &[String],
+        value_type: Option<grammar::KiroType>,
+        is_mutable: bool,
+    ) {
+        let items = match value_type {
+            Some(t) => match t.tuple_items() {
+                Some(items) => Some(items),
+                None => {
+                    self.error(
+                        Kind::Type,
+                        "Cannot destructure a value that is not a tuple.",
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(items) = &items
+            && items.len() != names.len()
+        {
+            let message = format!(
+                "Cannot destructure a {}-tuple into {} names.",
+                items.len(),
+                names.len()
+            );
+            self.error(Kind::Type, message);
+        }
+        for (i, name) in names.iter().enumerate().filter(|(_, n)| *n != "_") {
+            let item_type = items.as_ref().and_then(|items| items.get(i).cloned());
+            self.declare_var(name, is_mutable, item_type);
+        }
+    }
 }
@@ -1,23 +1,73 @@
-use crate::grammar::grammar::{KiroType, StructNameVal};
+use std::collections::HashSet;
+
+use crate::grammar::grammar::{KiroType, StructNameVal, TypeParams};
 
 pub fn compile_type(t: &KiroType) -> String {
     match t {
         KiroType::Num => compile_num(),
+        KiroType::Int => compile_int(),
         KiroType::Str => compile_str(),
         KiroType::Bool => compile_bool(),
         KiroType::Void => compile_void(),
         KiroType::Adr(_, inner) => compile_adr(inner),
         KiroType::Pipe(_, inner) => compile_pipe(inner),
+        KiroType::Opt(_, inner) => compile_opt(inner),
         KiroType::List(_, inner) => compile_list(inner),
         KiroType::Map(_, k, v) => compile_map(k, v),
+        KiroType::Tuple(..) => compile_tuple(&t.tuple_items().unwrap()),
+        KiroType::Func(pure_kw, _, _, params, _, ret) => {
+            compile_func(pure_kw.is_some(), params, ret.as_ref().map(|r| &*r.ret))
+        }
+        KiroType::Generic(s, _, args, _) => compile_generic(s, args),
         KiroType::Custom(s) => compile_custom(s),
     }
 }
 
+// Type parameters of generic fns: values are cloned freely and may cross `run` tasks.
+// Parameters the body prints also need Display.
+pub fn compile_type_params(params: &Option<TypeParams>, displayed: &HashSet<String>) -> String {
+    match params {
+        Some(p) => format!(
+            "<{}>",
+            p.names
+                .iter()
+                .map(|n| match displayed.contains(&n.value) {
+                    true => format!(
+                        "{}: Clone + Send + Sync + std::fmt::Display + 'static",
+                        n.value
+                    ),
+                    false => format!("{}: Clone + Send + Sync + 'static", n.value),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => String::new(),
+    }
+}
+
+// Type parameters of generic structs: bounds come from the derives
+pub fn compile_type_param_names(params: &Option<TypeParams>) -> String {
+    match params {
+        Some(p) => format!(
+            "<{}>",
+            p.names
+                .iter()
+                .map(|n| n.value.clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => String::new(),
+    }
+}
+
 pub fn compile_num() -> String {
     "f64".to_string()
 }
 
+pub fn compile_int() -> String {
+    "i64".to_string()
+}
+
 pub fn compile_str() -> String {
     "String".to_string()
 }
@@ -51,10 +101,50 @@ pub fn compile_custom(name: &StructNameVal) -> String {
     name.value.clone()
 }
 
+pub fn compile_func(is_pure: bool, params: &[KiroType], ret: Option<&KiroType>) -> String {
+    format!("KiroFn<{}>", compile_fn_sig(is_pure, params, ret))
+}
+
+// Pure function values are plain sync closures; impure ones return a boxed future
+pub fn compile_fn_sig(is_pure: bool, params: &[KiroType], ret: Option<&KiroType>) -> String {
+    let param_strs: Vec<String> = params.iter().map(compile_type).collect();
+    let ret_str = ret.map(compile_type).unwrap_or_else(compile_void);
+    if is_pure {
+        format!(
+            "dyn Fn({}) -> {} + Send + Sync",
+            param_strs.join(", "),
+            ret_str
+        )
+    } else {
+        format!(
+            "dyn Fn({}) -> KiroFuture<{}> + Send + Sync",
+            param_strs.join(", "),
+            ret_str
+        )
+    }
+}
+
+pub fn compile_generic(name: &StructNameVal, args: &[KiroType]) -> String {
+    format!(
+        "{}<{}>",
+        name.value,
+        args.iter().map(compile_type).collect::<Vec<_>>().join(", ")
+    )
+}
+
+pub fn compile_opt(inner: &KiroType) -> String {
+    format!("Option<{}>", compile_type(inner))
+}
+
 pub fn compile_list(inner: &KiroType) -> String {
     format!("Vec<{}>", compile_type(inner))
 }
 
+pub fn compile_tuple(items: &[KiroType]) -> String {
+    let items: Vec<String> = items.iter().map(compile_type).collect();
+    format!("({})", items.join(", "))
+}
+
 pub fn compile_map(key: &KiroType, value: &KiroType) -> String {
     format!(
         "std::collections::HashMap<{}, {}>",
@@ -0,0 +1,194 @@
+use miette::{GraphicalReportHandler, LabeledSpan, NamedSource, SourceSpan};
+use std::fmt;
+
+// Errors found in a source file, shown as labeled snippets of that file. Parse errors,
+// type errors and the backends' errors all go through here, so they look alike.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Parse,
+    Type,
+    Compile,
+    Purity,
+    Move,
+    Mutability,
+    Runtime,
+}
+
+impl Kind {
+    fn code(self) -> &'static str {
+        match self {
+            Kind::Parse => "kiro::parse",
+            Kind::Type => "kiro::type",
+            Kind::Compile => "kiro::compile",
+            Kind::Purity => "kiro::purity",
+            Kind::Move => "kiro::move",
+            Kind::Mutability => "kiro::mutability",
+            Kind::Runtime => "kiro::runtime",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub kind: Kind,
+    pub message: String,
+    pub span: (usize, usize), // Byte span, usually of the statement containing the error
+    pub focus: Option<String>, // Name inside the span to underline instead
+    pub label: Option<String>, // Text next to the underline
+    pub help: Option<String>,
+    pub related: Vec<Related>, // Other places the error involves
+}
+
+// A second labeled place in the source, such as where a value was moved
+#[derive(Debug, Clone)]
+pub struct Related {
+    pub span: (usize, usize),
+    pub focus: String,
+    pub label: String,
+}
+
+impl Diagnostic {
+    pub fn new(kind: Kind, message: impl Into<String>, span: (usize, usize)) -> Self {
+        Diagnostic {
+            kind,
+            message: message.into(),
+            span,
+            focus: None,
+            label: None,
+            help: None,
+            related: Vec::new(),
+        }
+    }
+
+    pub fn focus(mut self, name: impl Into<String>) -> Self {
+        self.focus = Some(name.into());
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn related(
+        mut self,
+        span: (usize, usize),
+        focus: impl Into<String>,
+        label: impl Into<String>,
+    ) -> Self {
+        self.related.push(Related {
+            span,
+            focus: focus.into(),
+            label: label.into(),
+        });
+        self
+    }
+}
+
+// The part of the source to underline: the focused text if the span contains it,
+// otherwise the span up to the end of its first line (a statement may be a whole block)
+fn underline(source: &str, span: (usize, usize), focus: Option<&str>) -> SourceSpan {
+    let start = floor_char(source, span.0);
+    let end = floor_char(source, span.1.max(start));
+    let text = &source[start..end];
+    if let Some(name) = focus
+        && let Some(offset) = find_word(text, name)
+    {
+        return (start + offset, name.len()).into();
+    }
+    let first_line = text.find('\n').unwrap_or(text.len());
+    (start, text[..first_line].trim_end().len()).into()
+}
+
+// Start of a whole-word occurrence of `name` in `text`
+fn find_word(text: &str, name: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(name).map(|(i, _)| i).find(|&i| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + name.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+fn floor_char(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+// One diagnostic tied to the file it points into, in the shape miette renders
+#[derive(Debug)]
+struct Report<'a> {
+    diagnostic: &'a Diagnostic,
+    source: NamedSource<String>,
+    spans: Vec<LabeledSpan>,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.diagnostic.message)
+    }
+}
+
+impl std::error::Error for Report<'_> {}
+
+impl miette::Diagnostic for Report<'_> {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.diagnostic.kind.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.diagnostic
+            .help
+            .as_ref()
+            .map(|h| Box::new(h) as Box<dyn fmt::Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.source)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(self.spans.iter().cloned()))
+    }
+}
+
+// Renders every diagnostic against the file's source, in the order given
+pub fn render(diagnostics: &[Diagnostic], filename: &str, source: &str) -> String {
+    let handler = GraphicalReportHandler::new();
+    let mut out = String::new();
+    for diagnostic in diagnostics {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let main = underline(source, diagnostic.span, diagnostic.focus.as_deref());
+        let mut spans = vec![LabeledSpan::new_with_span(diagnostic.label.clone(), main)];
+        for related in &diagnostic.related {
+            let span = underline(source, related.span, Some(&related.focus));
+            spans.push(LabeledSpan::new_with_span(
+                Some(related.label.clone()),
+                span,
+            ));
+        }
+        let report = Report {
+            diagnostic,
+            source: NamedSource::new(filename, source.to_string()),
+            spans,
+        };
+        if handler.render_report(&mut out, &report).is_err() {
+            out.push_str(&format!("{}: {}\n", filename, diagnostic.message));
+        }
+    }
+    if diagnostics.len() > 1 {
+        out.push_str(&format!("{} errors in {}\n", diagnostics.len(), filename));
+    }
+    out.trim_end().to_string()
+}
@@ -0,0 +1,202 @@
+use crate::grammar::grammar::{BoolVal, FieldDef, KiroType, Pattern};
+use std::collections::{HashMap, HashSet};
+
+// Match coverage shared by the compiler and the interpreter, so both accept the same
+// matches and report the same problems.
+
+// Enum name -> (variant, payload arity)
+pub type Enums = HashMap<String, Vec<(String, usize)>>;
+// Struct name -> declared fields
+pub type Structs = HashMap<String, Vec<FieldDef>>;
+
+// What is wrong with a match's arms, and the name in the statement to underline if any
+#[derive(Debug)]
+pub struct MatchError {
+    pub message: String,
+    pub focus: Option<String>,
+}
+
+impl MatchError {
+    fn new(message: String, focus: Option<&str>) -> Self {
+        MatchError {
+            message,
+            focus: focus.map(str::to_string),
+        }
+    }
+}
+
+// The struct or enum name a type refers to, if any
+fn type_name(ty: &KiroType) -> Option<&str> {
+    match ty {
+        KiroType::Custom(name) | KiroType::Generic(name, ..) => Some(name.value.as_str()),
+        _ => None,
+    }
+}
+
+fn is_named(ty: &KiroType, name: &str) -> bool {
+    type_name(ty) == Some(name)
+}
+
+// Checks a pattern against the type of the matched value: a struct pattern names the
+// subject's struct and its fields, a variant the subject's enum, a literal the subject's
+// kind. Enums are only looked up by name, so the checker can pass its own table.
+pub fn check_pattern<V>(
+    pattern: &Pattern,
+    subject: &KiroType,
+    structs: &Structs,
+    enums: &HashMap<String, V>,
+) -> Result<(), MatchError> {
+    let named = type_name(subject);
+    // Type parameters and interfaces may hold any value
+    if let Some(name) = named
+        && !structs.contains_key(name)
+        && !enums.contains_key(name)
+    {
+        return Ok(());
+    }
+    let cannot_match = |what: String, focus: Option<&str>| {
+        let message = format!("{} cannot match a value of type {}.", what, subject);
+        Err(MatchError::new(message, focus))
+    };
+    match pattern {
+        Pattern::Wildcard(_) | Pattern::Binding(_) => Ok(()),
+        Pattern::Struct(name, _, fields, _) => {
+            let Some(defs) = structs.get(&name.value) else {
+                let message = format!("Unknown struct '{}'.", name.value);
+                return Err(MatchError::new(message, Some(&name.value)));
+            };
+            if !is_named(subject, &name.value) {
+                return cannot_match(format!("Pattern '{}'", name.value), Some(&name.value));
+            }
+            match fields
+                .iter()
+                .find(|f| !defs.iter().any(|d| d.name.value == f.value))
+            {
+                Some(field) => {
+                    let message =
+                        format!("Struct '{}' has no field '{}'.", name.value, field.value);
+                    Err(MatchError::new(message, Some(&field.value)))
+                }
+                None => Ok(()),
+            }
+        }
+        // Unknown enums are reported with the coverage check
+        Pattern::Variant(enum_name, ..)
+            if enums.contains_key(&enum_name.value) && !is_named(subject, &enum_name.value) =>
+        {
+            cannot_match(
+                format!("Pattern '{}'", enum_name.value),
+                Some(&enum_name.value),
+            )
+        }
+        Pattern::Variant(..) => Ok(()),
+        Pattern::Number(_) | Pattern::Range(..) => match subject {
+            KiroType::Int | KiroType::Num => Ok(()),
+            _ if matches!(pattern, Pattern::Range(..)) => {
+                cannot_match("A range pattern".to_string(), None)
+            }
+            _ => cannot_match("A number pattern".to_string(), None),
+        },
+        Pattern::StringLit(_) => match subject {
+            KiroType::Str => Ok(()),
+            _ => cannot_match("A string pattern".to_string(), None),
+        },
+        Pattern::BoolLit(_) => match subject {
+            KiroType::Bool => Ok(()),
+            _ => cannot_match("A bool pattern".to_string(), None),
+        },
+    }
+}
+
+// Validates the patterns against the subject's type when it is known, and ensures the
+// arms cover every value. Guarded arms never count towards coverage.
+pub fn check_match_exhaustive(
+    enums: &Enums,
+    structs: &Structs,
+    subject: Option<&KiroType>,
+    arms: &[(&Pattern, bool)],
+) -> Result<(), MatchError> {
+    let mut matched_enum: Option<&str> = None;
+    let mut covered = HashSet::new();
+    let mut bools = HashSet::new();
+    let mut has_catch_all = false;
+
+    for (pattern, guarded) in arms {
+        if let Some(subject) = subject {
+            check_pattern(pattern, subject, structs, enums)?;
+        }
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Binding(_) => has_catch_all |= !guarded,
+            // A struct pattern covers every value only of its own struct type
+            Pattern::Struct(name, ..) => {
+                has_catch_all |= !guarded && subject.is_some_and(|t| is_named(t, &name.value))
+            }
+            Pattern::BoolLit(b) => {
+                if !guarded {
+                    bools.insert(matches!(b, BoolVal::True(_)));
+                }
+            }
+            Pattern::Number(_) | Pattern::StringLit(_) | Pattern::Range(..) => {}
+            Pattern::Variant(enum_name, _, variant, bindings) => {
+                let name = enum_name.value.as_str();
+                let Some(variants) = enums.get(name) else {
+                    let message = format!("Unknown enum '{}'.", name);
+                    return Err(MatchError::new(message, Some(name)));
+                };
+
+                if let Some(prev) = matched_enum
+                    && prev != name
+                {
+                    let message = format!(
+                        "Cannot mix variants of '{}' and '{}' in one match.",
+                        prev, name
+                    );
+                    return Err(MatchError::new(message, Some(name)));
+                }
+                matched_enum = Some(name);
+
+                let Some(&(_, arity)) = variants.iter().find(|(v, _)| *v == variant.value) else {
+                    let message = format!("'{}' has no variant '{}'.", name, variant.value);
+                    return Err(MatchError::new(message, Some(&variant.value)));
+                };
+                let given = bindings.as_ref().map(|b| b.names.len()).unwrap_or(0);
+                if given != arity {
+                    let message = format!(
+                        "Variant '{}.{}' has {} field(s), pattern binds {}.",
+                        name, variant.value, arity, given
+                    );
+                    return Err(MatchError::new(message, Some(&variant.value)));
+                }
+                if !guarded {
+                    covered.insert(variant.value.as_str());
+                }
+            }
+        }
+    }
+
+    if has_catch_all || bools.len() == 2 {
+        return Ok(());
+    }
+    match matched_enum {
+        Some(name) => {
+            let missing: Vec<&str> = enums[name]
+                .iter()
+                .map(|(v, _)| v.as_str())
+                .filter(|v| !covered.contains(v))
+                .collect();
+            if missing.is_empty() {
+                return Ok(());
+            }
+            let message = format!(
+                "Non-exhaustive match on '{}'. Missing: {}",
+                name,
+                missing.join(", ")
+            );
+            Err(MatchError::new(message, None))
+        }
+        None => Err(MatchError::new(
+            "Non-exhaustive match. Add a '_' arm to cover the remaining values.".to_string(),
+            None,
+        )),
+    }
+}
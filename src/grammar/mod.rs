@@ -1,8 +1,12 @@
+#![allow(clippy::module_inception)]
 #[rust_sitter::grammar("kiro")]
 pub mod grammar {
+    use rust_sitter::Spanned;
+
     #[rust_sitter::language]
     pub struct Program {
-        pub statements: Vec<Statement>,
+        // Statements carry their byte span for diagnostics
+        pub statements: Vec<Spanned<Statement>>,
     }
     // 1. The Wrapper Struct
     #[derive(Debug, Clone)]
@@ -10,29 +14,33 @@ pub mod grammar {
         #[rust_sitter::leaf(pattern = r"\d+(\.\d+)?", transform = |s| s.to_string())]
         pub value: String,
     }
+    // 3. For Struct Names (Capitalized: "User")
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct StructNameVal {
+        #[rust_sitter::leaf(pattern = r"[A-Z][a-zA-Z0-9_]*", transform = |s| s.to_string())]
+        pub value: String,
+    }
     #[derive(Debug, Clone)]
     pub struct VariableVal {
         #[rust_sitter::leaf(pattern = r"[a-zA-Z_][a-zA-Z0-9_]*", transform = |s| s.to_string())]
         pub value: String,
     }
 
-    // 2. Wrapper for String Literals ("hello")
+    // 2. Wrapper for String Literals: "hello", """multi-line""", r"raw", r"""raw multi-line"""
     #[derive(Debug, Clone)]
     pub struct StringVal {
-        #[rust_sitter::leaf(pattern = r#""([^"\\]|\\.)*""#, transform = |s| s.to_string())]
-        pub value: String,
-    }
-    // 3. For Struct Names (Capitalized: "User")
-    #[derive(Debug, Clone)]
-    pub struct StructNameVal {
-        #[rust_sitter::leaf(pattern = r"[A-Z][a-zA-Z0-9_]*", transform = |s| s.to_string())]
+        #[rust_sitter::leaf(
+            pattern = r#"r"""([^"]|"[^"]|""[^"])*"""|r"[^"]*"|"""([^"]|"[^"]|""[^"])*"""|"([^"\\]|\\.)*""#,
+            transform = |s| s.to_string()
+        )]
         pub value: String,
     }
 
     // 4. For Field Names (Lowercase: "age")
     #[derive(Debug, Clone)]
     pub struct FieldNameVal {
-        #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())]
+        // Upper case for module constants: math.PI
+        #[rust_sitter::leaf(pattern = r"[a-zA-Z_][a-zA-Z0-9_]*", transform = |s| s.to_string())]
         pub value: String,
     }
 
@@ -41,10 +49,12 @@ pub mod grammar {
         True(#[rust_sitter::leaf(text = "true")] ()),
         False(#[rust_sitter::leaf(text = "false")] ()),
     }
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, PartialEq)]
     pub enum KiroType {
         #[rust_sitter::leaf(text = "num")]
-        Num, // Replaces Int
+        Num,
+        #[rust_sitter::leaf(text = "int")]
+        Int,
         #[rust_sitter::leaf(text = "str")]
         Str, // New
         #[rust_sitter::leaf(text = "bool")]
@@ -56,6 +66,9 @@ pub mod grammar {
         #[rust_sitter::leaf(text = "pipe")]
         Pipe(#[rust_sitter::leaf(text = "pipe")] (), Box<KiroType>),
 
+        // Optional value: opt num
+        Opt(#[rust_sitter::leaf(text = "opt")] (), Box<KiroType>),
+
         // 1. Recursive Types for Collections
         // list <type>
         List(#[rust_sitter::leaf(text = "list")] (), Box<KiroType>),
@@ -66,11 +79,63 @@ pub mod grammar {
             Box<KiroType>,
         ),
 
+        // Function type: fn(num, str) -> bool / pure fn(num) -> num
+        Func(
+            #[rust_sitter::leaf(text = "pure")] Option<()>,
+            #[rust_sitter::leaf(text = "fn")] (),
+            #[rust_sitter::leaf(text = "(")] (),
+            #[rust_sitter::delimited(#[rust_sitter::leaf(text = ",")] ())] Vec<KiroType>,
+            #[rust_sitter::leaf(text = ")")] (),
+            Option<FuncTypeReturn>,
+        ),
+
+        // Generic struct instance: Pair<num, str>
+        #[rust_sitter::prec_left(1)]
+        Generic(
+            StructNameVal,
+            #[rust_sitter::leaf(text = "<")] (),
+            #[rust_sitter::delimited(#[rust_sitter::leaf(text = ",")] ())] Vec<KiroType>,
+            #[rust_sitter::leaf(text = ">")] (),
+        ),
+
+        // Tuple: (num, str). At least two elements.
+        Tuple(
+            #[rust_sitter::leaf(text = "(")] (),
+            Box<KiroType>,
+            #[rust_sitter::leaf(text = ",")] (),
+            #[rust_sitter::repeat(non_empty = true)]
+            #[rust_sitter::delimited(#[rust_sitter::leaf(text = ",")] ())]
+            Vec<KiroType>,
+            #[rust_sitter::leaf(text = ")")] (),
+        ),
+
         // 1. Custom Types (e.g., "User")
         // We use a high priority to ensure it doesn't conflict with keywords
+        // Type parameters (e.g., "T") also parse as Custom
         Custom(StructNameVal),
     }
 
+    // "-> type" in a function type
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FuncTypeReturn {
+        #[rust_sitter::leaf(text = "->")]
+        pub _arrow: (),
+        pub ret: Box<KiroType>,
+    }
+
+    // Type parameter list: <T> or <A, B>
+    #[derive(Debug, Clone)]
+    pub struct TypeParams {
+        #[rust_sitter::leaf(text = "<")]
+        _l: (),
+        #[rust_sitter::delimited(
+            #[rust_sitter::leaf(text = ",")] ()
+        )]
+        pub names: Vec<StructNameVal>,
+        #[rust_sitter::leaf(text = ">")]
+        _r: (),
+    }
+
     // --- MAP PAIR (No colon, just space) ---
     // "Key Value"
     #[derive(Debug, Clone)]
@@ -114,13 +179,21 @@ pub mod grammar {
         // 2. Struct Definition (No commas, whitespace separated)
         // struct User { name: str age: num }
         StructDef(StructDef),
+        // Enum Definition (whitespace separated variants)
+        // enum Shape { Circle(num) Rect(num, num) Empty }
+        EnumDef(EnumDef),
+        // Methods: impl User { fn greet(self) -> str { ... } }
+        ImplBlock(ImplBlock),
+        InterfaceDef(InterfaceDef),
         // Error Definition: error NotFound = "Description"
+        // With payload fields: error HttpError = "Request failed" { status: int url: str }
         ErrorDef {
             #[rust_sitter::leaf(text = "error")]
             _error: (),
             #[rust_sitter::leaf(pattern = r"[A-Z][a-zA-Z0-9]*", transform = |s| s.to_string())]
             name: String,
             description: Option<ErrorDesc>,
+            fields: Option<ErrorFields>,
         },
         // 1. Variable Declaration: var x = 10
         VarDecl {
@@ -132,6 +205,29 @@ pub mod grammar {
             _eq: (),
             value: Expression,
         },
+        // Module-level constant: const PI: num = 3.14159
+        ConstDecl {
+            #[rust_sitter::leaf(text = "const")]
+            _const: (),
+            // Upper case, so `math.PI` reads as a constant
+            #[rust_sitter::leaf(pattern = r"[A-Z][A-Z0-9_]*", transform = |s| s.to_string())]
+            name: String,
+            #[rust_sitter::leaf(text = ":")]
+            _colon: (),
+            const_type: KiroType,
+            #[rust_sitter::leaf(text = "=")]
+            _eq: (),
+            value: Expression,
+        },
+        // Destructuring declaration: var (a, b) = pair()
+        VarTuple {
+            #[rust_sitter::leaf(text = "var")]
+            _var: (),
+            names: PatternBindings,
+            #[rust_sitter::leaf(text = "=")]
+            _eq: (),
+            value: Expression,
+        },
         // 2. Assignment (Mutation): x = 10 OR x.y = 10
         AssignStmt {
             lhs: Expression,
@@ -139,6 +235,12 @@ pub mod grammar {
             _eq: (),
             rhs: Expression,
         },
+        // Compound assignment: x += 1 OR x.y *= 2
+        CompoundAssign {
+            lhs: Expression,
+            op: CompoundOp,
+            rhs: Expression,
+        },
         #[rust_sitter::prec_right(1)]
         On {
             #[rust_sitter::leaf(text = "on")]
@@ -146,6 +248,8 @@ pub mod grammar {
             #[rust_sitter::leaf(text = "(")]
             _l: (),
             condition: Expression,
+            // Optional check: on (x is some v)
+            some_check: Option<SomeCheck>,
             #[rust_sitter::leaf(text = ")")]
             _r: (),
             body: Block,
@@ -155,8 +259,7 @@ pub mod grammar {
             error_clauses: Option<ErrorClauseList>,
         },
         LoopOn {
-            #[rust_sitter::leaf(text = "loop")]
-            _loop: (),
+            head: LoopHead,
             #[rust_sitter::leaf(text = "on")]
             _on: (),
             #[rust_sitter::leaf(text = "(")]
@@ -167,18 +270,16 @@ pub mod grammar {
             body: Block,
         },
 
-        // 4. The "For" Loop: loop x in y [per z] [on (cond)] { } [off { }]
+        // 4. The "For" Loop: loop[@label] x in y [per z] [on (cond)] { } [off { }]
         LoopIter {
-            #[rust_sitter::leaf(text = "loop")]
-            _loop: (),
-            #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())]
-            iterator: String,
+            head: LoopHead,
+            iterator: LoopBinding,
             #[rust_sitter::leaf(text = "in")]
             _in: (),
             iterable: Expression, // This handles 'arr' or '0..10'
 
-            step: Option<StepClause>,   // Optional "per 5"
-            filter: Option<LoopFilter>, // Optional "on (x % 2 == 0)"
+            step: Option<Box<StepClause>>, // Optional "per 5"
+            filter: Option<LoopFilter>,    // Optional "on (x % 2 == 0)"
 
             body: Block,
 
@@ -208,10 +309,15 @@ pub mod grammar {
         // 3. Return Statement
         #[rust_sitter::prec_right(1)]
         Return(#[rust_sitter::leaf(text = "return")] (), Option<Expression>),
-        // 4. Break Statement
-        Break(#[rust_sitter::leaf(text = "break")] ()),
+        // 4. Break Statement: `break@outer` leaves the loop labeled outer
+        Break(#[rust_sitter::leaf(text = "break")] (), Option<LoopLabel>),
         // 5. Continue Statement
-        Continue(#[rust_sitter::leaf(text = "continue")] ()),
+        Continue(
+            #[rust_sitter::leaf(text = "continue")] (),
+            Option<LoopLabel>,
+        ),
+        // Runs when the enclosing block exits, last deferred first
+        Defer(#[rust_sitter::leaf(text = "defer")] (), Block),
 
         // 6. Import Statement
         Import {
@@ -235,7 +341,7 @@ pub mod grammar {
     pub enum Expression {
         // 3. Struct Initialization
         // User { name: "Kiro", age: 10 }
-        #[rust_sitter::prec_left(5)]
+        #[rust_sitter::prec_left(8)]
         StructInit(
             StructNameVal, // Struct Name
             #[rust_sitter::leaf(text = "{")] (),
@@ -246,12 +352,41 @@ pub mod grammar {
             #[rust_sitter::leaf(text = "}")] (),
         ),
 
+        // Enum Variant Construction
+        // Shape.Circle(5) OR Shape.Empty
+        #[rust_sitter::prec_right(9)]
+        EnumInit(
+            StructNameVal, // Enum Name
+            #[rust_sitter::leaf(text = ".")] (),
+            StructNameVal, // Variant Name
+            Option<EnumArgs>,
+        ),
+
+        // Match: arms are blocks (statement use) or values (expression use)
+        // Anonymous function: fn(x: num) -> num { return x * 2 }
+        Lambda(LambdaDef),
+
+        // match shape { Shape.Circle(r) => { ... } _ => { ... } }
+        // match n { 0 => "zero" 1..10 => "small" _ => "big" }
+        #[rust_sitter::prec_left(3)]
+        Match(
+            #[rust_sitter::leaf(text = "match")] (),
+            Box<Expression>, // Subject
+            #[rust_sitter::leaf(text = "{")] (),
+            #[rust_sitter::repeat(non_empty = true)] Vec<MatchArm>,
+            #[rust_sitter::leaf(text = "}")] (),
+        ),
+
+        // Tuple literal: (1, "a"). At least two elements.
+        #[rust_sitter::prec_left(4)]
+        TupleLit(ParenList),
+
         // 2. List Initialization
         // list num { 1, 2, 3 }
-        #[rust_sitter::prec_left(2)]
+        #[rust_sitter::prec_left(4)]
         ListInit(
             #[rust_sitter::leaf(text = "list")] (),
-            #[allow(dead_code)] KiroType, // The inner type (e.g. num)
+            KiroType, // The inner type (e.g. num)
             #[rust_sitter::leaf(text = "{")] (),
             #[rust_sitter::delimited(#[rust_sitter::leaf(text = ",")] ())] Vec<Expression>,
             #[rust_sitter::leaf(text = "}")] (),
@@ -259,11 +394,11 @@ pub mod grammar {
 
         // 3. Map Initialization
         // map str num { "A" 1, "B" 2 }
-        #[rust_sitter::prec_left(2)]
+        #[rust_sitter::prec_left(4)]
         MapInit(
             #[rust_sitter::leaf(text = "map")] (),
-            #[allow(dead_code)] KiroType, // Key Type
-            #[allow(dead_code)] KiroType, // Value Type
+            KiroType, // Key Type
+            KiroType, // Value Type
             #[rust_sitter::leaf(text = "{")] (),
             #[rust_sitter::delimited(#[rust_sitter::leaf(text = ",")] ())] Vec<MapPair>,
             #[rust_sitter::leaf(text = "}")] (),
@@ -271,14 +406,14 @@ pub mod grammar {
 
         // 4. Field Access (Dot Notation)
         // user.name OR ptr.name (Auto-Deref)
-        #[rust_sitter::prec_left(6)] // High precedence
+        #[rust_sitter::prec_left(9)] // High precedence
         FieldAccess(
             Box<Expression>,
             #[rust_sitter::leaf(text = ".")] (),
             FieldNameVal, // Field Name
         ),
         // 4. Access Command: list at index
-        #[rust_sitter::prec_left(5)] // High precedence
+        #[rust_sitter::prec_left(8)] // High precedence
         At(
             Box<Expression>, // The Collection
             #[rust_sitter::leaf(text = "at")] (),
@@ -286,139 +421,233 @@ pub mod grammar {
         ),
 
         // 5. Modification Command: list push value
-        #[rust_sitter::prec_left(5)]
+        #[rust_sitter::prec_left(8)]
         Push(
             Box<Expression>, // The List
             #[rust_sitter::leaf(text = "push")] (),
             Box<Expression>, // The Value
         ),
+        // list insert (index, value) / map insert (key, value)
+        #[rust_sitter::prec_left(8)]
+        Insert(
+            Box<Expression>, // The Collection
+            #[rust_sitter::leaf(text = "insert")] (),
+            ParenList, // (Index/Key, Value)
+        ),
+        // list remove index / map remove key: the removed value, or an error
+        #[rust_sitter::prec_left(8)]
+        Remove(
+            Box<Expression>, // The Collection
+            #[rust_sitter::leaf(text = "remove")] (),
+            Box<Expression>, // The Index/Key
+        ),
+        // list contains value / map contains key
+        #[rust_sitter::prec_left(8)]
+        Contains(
+            Box<Expression>, // The Collection
+            #[rust_sitter::leaf(text = "contains")] (),
+            Box<Expression>, // The Value/Key
+        ),
+        // pop list: the last element, or an error when empty
+        #[rust_sitter::prec_right(7)]
+        Pop(#[rust_sitter::leaf(text = "pop")] (), Box<Expression>),
+        // clear list / clear map
+        #[rust_sitter::prec_right(7)]
+        Clear(#[rust_sitter::leaf(text = "clear")] (), Box<Expression>),
         // 2. New Literals
-        #[rust_sitter::prec_left(1)]
+        #[rust_sitter::prec_left(3)]
         BoolLit(BoolVal),
 
-        #[rust_sitter::prec_left(1)]
+        #[rust_sitter::prec_left(3)]
         Number(NumberVal),
 
-        #[rust_sitter::prec_left(1)]
+        #[rust_sitter::prec_left(3)]
         StringLit(StringVal),
 
-        #[rust_sitter::prec_left(1)]
+        #[rust_sitter::prec_left(3)]
         // 5. Variable Reference
         Variable(VariableVal),
 
+        // Error Reference: return NotFound
+        #[rust_sitter::prec_left(3)]
+        ErrorRef(StructNameVal),
+
         // 6. Move Expression: move x
-        #[rust_sitter::prec_right(10)]
+        #[rust_sitter::prec_right(13)]
         MoveExpr(#[rust_sitter::leaf(text = "move")] (), VariableVal),
 
-        #[rust_sitter::prec_left(1)]
+        // Optional values: none / some x
+        #[rust_sitter::prec_left(3)]
+        NoneLit(#[rust_sitter::leaf(text = "none")] ()),
+
+        // `some` wraps a whole arithmetic expression: some n / 2
+        #[rust_sitter::prec_right(3)]
+        SomeExpr(#[rust_sitter::leaf(text = "some")] (), Box<Expression>),
+
+        #[rust_sitter::prec_left(3)]
         AdrInit(#[rust_sitter::leaf(text = "adr")] (), KiroType),
 
-        #[rust_sitter::prec_left(1)]
+        #[rust_sitter::prec_left(3)]
         PipeInit(#[rust_sitter::leaf(text = "pipe")] (), KiroType),
 
         // 4. Take: take <channel>
         // Example: var x = take p
-        #[rust_sitter::prec_right(4)]
+        #[rust_sitter::prec_right(7)]
         Take(#[rust_sitter::leaf(text = "take")] (), Box<Expression>),
 
         // 5. Len: len <collection>
-        #[rust_sitter::prec_right(4)]
+        #[rust_sitter::prec_right(7)]
         Len(#[rust_sitter::leaf(text = "len")] (), Box<Expression>),
 
         // 3. Pointer Logic
         // ref x
-        #[rust_sitter::prec_right(4)] // Right-associative
+        #[rust_sitter::prec_right(7)] // Right-associative
         Ref(#[rust_sitter::leaf(text = "ref")] (), Box<Expression>),
 
         // deref x
-        #[rust_sitter::prec_right(4)]
+        #[rust_sitter::prec_right(7)]
         Deref(#[rust_sitter::leaf(text = "deref")] (), Box<Expression>),
-        #[rust_sitter::prec_left(3)] // High precedence
+        #[rust_sitter::prec_left(6)] // High precedence
         Call(
             Box<Expression>, // The function name (usually a Variable)
-            #[rust_sitter::leaf(text = "(")] (),
-            #[rust_sitter::delimited(
-                #[rust_sitter::leaf(text = ",")] ()
-            )]
-            Vec<Expression>, // Arguments
-            #[rust_sitter::leaf(text = ")")] (),
+            ParenList,       // Arguments
         ),
 
         // 5. Async "Run" Call
         // Syntax: run foo(1, 2)
-        #[rust_sitter::prec_left(2)]
+        #[rust_sitter::prec_left(4)]
         RunCall(
             #[rust_sitter::leaf(text = "run")] (),
             Box<Expression>, // Should be a Call expression
         ),
-        #[rust_sitter::prec_left(2)]
+        #[rust_sitter::prec_left(4)]
         Mul(
             Box<Expression>,
             #[rust_sitter::leaf(text = "*")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(2)]
+        #[rust_sitter::prec_left(4)]
         Div(
             Box<Expression>,
             #[rust_sitter::leaf(text = "/")] (),
             Box<Expression>,
         ),
-        // Level 1: Addition & Subtraction (Happens Last)
-        #[rust_sitter::prec_left(1)]
-        Add(
+        #[rust_sitter::prec_left(4)]
+        Mod(
             Box<Expression>,
-            #[rust_sitter::leaf(text = "+")] (),
+            #[rust_sitter::leaf(text = "%")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(1)]
-        Sub(
+        // Unary operators bind tighter than arithmetic but looser than calls: -f(x)
+        #[rust_sitter::prec_right(5)]
+        Neg(Minus, Box<Expression>),
+        #[rust_sitter::prec_right(5)]
+        Not(#[rust_sitter::leaf(text = "!")] (), Box<Expression>),
+        // Error propagation: try fetch(url) gives the value, or returns the error
+        #[rust_sitter::prec_right(5)]
+        Try(#[rust_sitter::leaf(text = "try")] (), Box<Expression>),
+        // Level 1: Addition & Subtraction (Happens Last)
+        #[rust_sitter::prec_left(3)]
+        Add(
             Box<Expression>,
-            #[rust_sitter::leaf(text = "-")] (),
+            #[rust_sitter::leaf(text = "+")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(0)]
+        #[rust_sitter::prec_left(3)]
+        Sub(Box<Expression>, Minus, Box<Expression>),
+        #[rust_sitter::prec_left(2)]
         Eq(
             Box<Expression>,
             #[rust_sitter::leaf(text = "==")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(0)]
+        #[rust_sitter::prec_left(2)]
         Neq(
             Box<Expression>,
             #[rust_sitter::leaf(text = "!=")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(0)]
+        #[rust_sitter::prec_left(2)]
         Gt(
             Box<Expression>,
             #[rust_sitter::leaf(text = ">")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(0)]
+        #[rust_sitter::prec_left(2)]
         Lt(
             Box<Expression>,
             #[rust_sitter::leaf(text = "<")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(0)]
+        #[rust_sitter::prec_left(2)]
         Geq(
             Box<Expression>,
             #[rust_sitter::leaf(text = ">=")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(0)]
+        #[rust_sitter::prec_left(2)]
         Leq(
             Box<Expression>,
             #[rust_sitter::leaf(text = "<=")] (),
             Box<Expression>,
         ),
-        #[rust_sitter::prec_left(0)] // Low priority
+        #[rust_sitter::prec_left(2)] // Low priority
         Range(
             Box<Expression>,
             #[rust_sitter::leaf(text = "..")] (),
             Box<Expression>,
         ),
+        // a..=b includes b
+        #[rust_sitter::prec_left(2)]
+        RangeInclusive(
+            Box<Expression>,
+            #[rust_sitter::leaf(text = "..=")] (),
+            Box<Expression>,
+        ),
+        // Logical operators (lowest, short-circuiting)
+        #[rust_sitter::prec_left(1)]
+        And(
+            Box<Expression>,
+            #[rust_sitter::leaf(text = "&&")] (),
+            Box<Expression>,
+        ),
+        #[rust_sitter::prec_left(0)]
+        Or(
+            Box<Expression>,
+            #[rust_sitter::leaf(text = "||")] (),
+            Box<Expression>,
+        ),
+    }
+    // "-" is shared by subtraction and negation so a statement boundary before `-x`
+    // is resolved by precedence (`a - b` always continues the expression)
+    #[derive(Debug, Clone)]
+    pub struct Minus {
+        #[rust_sitter::leaf(text = "-")]
+        pub _minus: (),
     }
+
+    // "(a, b)" is shared by call arguments and tuple literals for the same reason:
+    // `give p (1, 2)` is a call, resolved by precedence
+    #[derive(Debug, Clone)]
+    pub struct ParenList {
+        #[rust_sitter::leaf(text = "(")]
+        _l: (),
+        #[rust_sitter::delimited(
+            #[rust_sitter::leaf(text = ",")] ()
+        )]
+        pub items: Vec<Expression>,
+        #[rust_sitter::leaf(text = ")")]
+        _r: (),
+    }
+
+    #[derive(Debug, Clone)]
+    pub enum CompoundOp {
+        Add(#[rust_sitter::leaf(text = "+=")] ()),
+        Sub(#[rust_sitter::leaf(text = "-=")] ()),
+        Mul(#[rust_sitter::leaf(text = "*=")] ()),
+        Div(#[rust_sitter::leaf(text = "/=")] ()),
+    }
+
     // 7. Documentation Comments (/// ...)
     #[derive(Debug, Clone)]
     pub struct DocComment {
@@ -446,7 +675,7 @@ pub mod grammar {
         #[rust_sitter::leaf(text = "{")]
         _l: (),
         #[rust_sitter::repeat(non_empty = false)]
-        pub statements: Vec<Statement>,
+        pub statements: Vec<Spanned<Statement>>,
         #[rust_sitter::leaf(text = "}")]
         _r: (),
     }
@@ -462,6 +691,16 @@ pub mod grammar {
         _eq: (),
         pub value: StringVal,
     }
+    // Payload fields of an error, whitespace separated like struct fields
+    #[derive(Debug, Clone)]
+    pub struct ErrorFields {
+        #[rust_sitter::leaf(text = "{")]
+        _l: (),
+        #[rust_sitter::repeat(non_empty = false)]
+        pub fields: Vec<FieldDef>,
+        #[rust_sitter::leaf(text = "}")]
+        _r: (),
+    }
     #[derive(Debug, Clone)]
     #[rust_sitter::prec_right(2)]
     pub struct ErrorClause {
@@ -470,8 +709,17 @@ pub mod grammar {
         // Optional error type (e.g., NotFound). None = catch-all handler.
         #[rust_sitter::leaf(pattern = r"[A-Z][a-zA-Z0-9]*", transform = |s| s.to_string())]
         pub error_type: Option<String>,
+        pub binding: Option<ErrorBinding>,
         pub body: Block,
     }
+    // Names the handled error's payload: error HttpError as e { print e.status }
+    #[derive(Debug, Clone)]
+    pub struct ErrorBinding {
+        #[rust_sitter::leaf(text = "as")]
+        _as: (),
+        #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())]
+        pub name: String,
+    }
     // Recursive linked-list pattern for multiple error clauses
     #[derive(Debug, Clone)]
     #[rust_sitter::prec_right(2)]
@@ -486,6 +734,21 @@ pub mod grammar {
         _per: (),
         pub value: Expression,
     }
+    // `loop`, optionally labeled: `loop@outer`
+    #[derive(Debug, Clone)]
+    pub struct LoopHead {
+        #[rust_sitter::leaf(text = "loop")]
+        _loop: (),
+        pub label: Option<LoopLabel>,
+    }
+    // `@outer` after `loop`, `break` or `continue`
+    #[derive(Debug, Clone)]
+    pub struct LoopLabel {
+        #[rust_sitter::leaf(text = "@")]
+        _at: (),
+        #[rust_sitter::leaf(pattern = r"[a-z_][a-z0-9_]*", transform = |s| s.to_string())]
+        pub name: String,
+    }
     #[derive(Debug, Clone)]
     pub struct LoopFilter {
         #[rust_sitter::leaf(text = "on")]
@@ -496,9 +759,111 @@ pub mod grammar {
         #[rust_sitter::leaf(text = ")")]
         _r: (),
     }
+    // Payload of an enum variant construction: Shape.Rect(2, 3)
+    #[derive(Debug, Clone)]
+    pub struct EnumArgs {
+        #[rust_sitter::leaf(text = "(")]
+        _l: (),
+        #[rust_sitter::delimited(
+            #[rust_sitter::leaf(text = ",")] ()
+        )]
+        pub values: Vec<Expression>,
+        #[rust_sitter::leaf(text = ")")]
+        _r: (),
+    }
+    #[derive(Debug, Clone)]
+    pub struct MatchArm {
+        pub pattern: Pattern,
+        pub guard: Option<MatchGuard>,
+        #[rust_sitter::leaf(text = "=>")]
+        _arrow: (),
+        pub body: ArmBody,
+    }
+    #[derive(Debug, Clone)]
+    pub enum ArmBody {
+        Block(Block),
+        Value(Expression),
+    }
+    // Optional arm guard: n on (n > 5) => ...
+    #[derive(Debug, Clone)]
+    pub struct MatchGuard {
+        #[rust_sitter::leaf(text = "on")]
+        _on: (),
+        #[rust_sitter::leaf(text = "(")]
+        _l: (),
+        pub condition: Expression,
+        #[rust_sitter::leaf(text = ")")]
+        _r: (),
+    }
+    #[derive(Debug, Clone)]
+    pub enum Pattern {
+        // Catch-all: _
+        Wildcard(#[rust_sitter::leaf(text = "_")] ()),
+        // Enum variant with optional bindings: Shape.Rect(w, h)
+        Variant(
+            StructNameVal, // Enum Name
+            #[rust_sitter::leaf(text = ".")] (),
+            StructNameVal, // Variant Name
+            Option<PatternBindings>,
+        ),
+        // Struct destructuring (whitespace separated fields): User { name age }
+        Struct(
+            StructNameVal,
+            #[rust_sitter::leaf(text = "{")] (),
+            #[rust_sitter::repeat(non_empty = false)] Vec<BindingName>,
+            #[rust_sitter::leaf(text = "}")] (),
+        ),
+        // Half-open numeric range: 0..10
+        Range(NumberVal, #[rust_sitter::leaf(text = "..")] (), NumberVal),
+        Number(NumberVal),
+        StringLit(StringVal),
+        BoolLit(BoolVal),
+        // Binds the whole value: n
+        Binding(BindingName),
+    }
+    // "is some v" in `on (x is some v)`: runs the body with the value bound to v
+    #[derive(Debug, Clone)]
+    pub struct SomeCheck {
+        #[rust_sitter::leaf(text = "is")]
+        _is: (),
+        #[rust_sitter::leaf(text = "some")]
+        _some: (),
+        #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |v| v.to_string())]
+        pub binding: String,
+    }
+    // Loop variable: a name, (a, b) to destructure tuple items, or `i, x` for the
+    // index and element of a list (key and value of a map)
+    #[derive(Debug, Clone)]
+    pub enum LoopBinding {
+        Name(#[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())] String),
+        Tuple(PatternBindings),
+        Pair(
+            #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())] String,
+            #[rust_sitter::leaf(text = ",")] (),
+            Box<BindingName>,
+        ),
+    }
+    // Parenthesized names: variant payloads, `var (a, b) = ...` and `loop (k, v) in ...`
+    #[derive(Debug, Clone)]
+    pub struct PatternBindings {
+        #[rust_sitter::leaf(text = "(")]
+        _l: (),
+        #[rust_sitter::delimited(
+            #[rust_sitter::leaf(text = ",")] ()
+        )]
+        pub names: Vec<BindingName>,
+        #[rust_sitter::leaf(text = ")")]
+        _r: (),
+    }
+    #[derive(Debug, Clone)]
+    pub struct BindingName {
+        #[rust_sitter::leaf(pattern = r"[a-z_][a-z0-9_]*", transform = |s| s.to_string())]
+        pub value: String,
+    }
     #[derive(Debug, Clone)]
     pub enum AnnotatableItem {
         StructDef(StructDef),
+        EnumDef(EnumDef),
         FunctionDef(FunctionDef),
         RustFnDecl(RustFnDecl),
     }
@@ -510,6 +875,7 @@ pub mod grammar {
 
         // Struct names must be Capitalized to distinguish from variables
         pub name: StructNameVal,
+        pub type_params: Option<TypeParams>,
 
         #[rust_sitter::leaf(text = "{")]
         pub _l: (),
@@ -521,6 +887,42 @@ pub mod grammar {
         pub _r: (),
     }
 
+    #[derive(Debug, Clone)]
+    pub struct EnumDef {
+        #[rust_sitter::leaf(text = "enum")]
+        pub _enum: (),
+
+        pub name: StructNameVal,
+
+        #[rust_sitter::leaf(text = "{")]
+        pub _l: (),
+
+        #[rust_sitter::repeat(non_empty = true)]
+        pub variants: Vec<EnumVariant>,
+
+        #[rust_sitter::leaf(text = "}")]
+        pub _r: (),
+    }
+
+    // A single variant: "Circle(num)" or "Empty"
+    #[derive(Debug, Clone)]
+    pub struct EnumVariant {
+        pub name: StructNameVal,
+        pub payload: Option<VariantPayload>,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct VariantPayload {
+        #[rust_sitter::leaf(text = "(")]
+        pub _l: (),
+        #[rust_sitter::delimited(
+            #[rust_sitter::leaf(text = ",")] ()
+        )]
+        pub types: Vec<KiroType>,
+        #[rust_sitter::leaf(text = ")")]
+        pub _r: (),
+    }
+
     #[derive(Debug, Clone)]
     pub struct FunctionDef {
         #[rust_sitter::leaf(text = "pure")]
@@ -531,6 +933,7 @@ pub mod grammar {
 
         #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())]
         pub name: String,
+        pub type_params: Option<TypeParams>,
 
         #[rust_sitter::leaf(text = "(")]
         pub _l: (),
@@ -550,6 +953,143 @@ pub mod grammar {
         pub body: Block, // Required body for normal functions
     }
 
+    #[derive(Debug, Clone)]
+    pub struct ImplBlock {
+        #[rust_sitter::leaf(text = "impl")]
+        pub _impl: (),
+
+        pub name: StructNameVal,
+        // "impl Shape for Circle": `name` is the interface
+        pub target: Option<ImplTarget>,
+
+        #[rust_sitter::leaf(text = "{")]
+        pub _l: (),
+
+        #[rust_sitter::repeat(non_empty = false)]
+        pub methods: Vec<MethodDef>,
+
+        #[rust_sitter::leaf(text = "}")]
+        pub _r: (),
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct ImplTarget {
+        #[rust_sitter::leaf(text = "for")]
+        pub _for: (),
+        pub name: StructNameVal,
+    }
+
+    // interface Shape { fn area(self) -> num }
+    #[derive(Debug, Clone)]
+    pub struct InterfaceDef {
+        #[rust_sitter::leaf(text = "interface")]
+        pub _interface: (),
+
+        pub name: StructNameVal,
+
+        #[rust_sitter::leaf(text = "{")]
+        pub _l: (),
+
+        #[rust_sitter::repeat(non_empty = false)]
+        pub methods: Vec<MethodSig>,
+
+        #[rust_sitter::leaf(text = "}")]
+        pub _r: (),
+    }
+
+    // A method signature without a body, declared by an interface
+    #[derive(Debug, Clone)]
+    pub struct MethodSig {
+        #[rust_sitter::leaf(text = "pure")]
+        pub pure_kw: Option<()>,
+
+        #[rust_sitter::leaf(text = "fn")]
+        pub _fn: (),
+
+        #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())]
+        pub name: String,
+
+        #[rust_sitter::leaf(text = "(")]
+        pub _l: (),
+        #[rust_sitter::leaf(text = "var")]
+        pub var_kw: Option<()>,
+        #[rust_sitter::leaf(text = "self")]
+        pub _self: (),
+        #[rust_sitter::repeat(non_empty = false)]
+        pub params: Vec<MethodParam>,
+        #[rust_sitter::leaf(text = ")")]
+        pub _r: (),
+
+        pub ret: Option<FuncTypeReturn>,
+        #[rust_sitter::leaf(text = "!")]
+        pub can_error: Option<()>,
+    }
+
+    // A method: "fn greet(self) -> str { ... }" or "fn grow(var self, n: num) { ... }"
+    #[derive(Debug, Clone)]
+    pub struct MethodDef {
+        #[rust_sitter::leaf(text = "pure")]
+        pub pure_kw: Option<()>,
+
+        #[rust_sitter::leaf(text = "fn")]
+        pub _fn: (),
+
+        #[rust_sitter::leaf(pattern = r"[a-z_]+", transform = |s| s.to_string())]
+        pub name: String,
+
+        #[rust_sitter::leaf(text = "(")]
+        pub _l: (),
+        // "var self" receives the value mutably
+        #[rust_sitter::leaf(text = "var")]
+        pub var_kw: Option<()>,
+        #[rust_sitter::leaf(text = "self")]
+        pub _self: (),
+        #[rust_sitter::repeat(non_empty = false)]
+        pub params: Vec<MethodParam>,
+        #[rust_sitter::leaf(text = ")")]
+        pub _r: (),
+
+        #[rust_sitter::leaf(text = "->")]
+        pub _arrow: Option<()>,
+        pub return_type: Option<KiroType>,
+        #[rust_sitter::leaf(text = "!")]
+        pub can_error: Option<()>,
+
+        pub body: Block,
+    }
+
+    // ", name: type" after the receiver
+    #[derive(Debug, Clone)]
+    pub struct MethodParam {
+        #[rust_sitter::leaf(text = ",")]
+        _comma: (),
+        pub param: FuncParam,
+    }
+
+    // Function literal; captures a copy of the variables it uses
+    #[derive(Debug, Clone)]
+    pub struct LambdaDef {
+        #[rust_sitter::leaf(text = "fn")]
+        pub _fn: (),
+
+        #[rust_sitter::leaf(text = "(")]
+        pub _l: (),
+        #[rust_sitter::delimited(
+            #[rust_sitter::leaf(text = ",")] ()
+        )]
+        pub params: Vec<FuncParam>,
+        #[rust_sitter::leaf(text = ")")]
+        pub _r: (),
+
+        #[rust_sitter::leaf(text = "->")]
+        pub _arrow: Option<()>,
+        pub return_type: Option<KiroType>,
+
+        pub body: Block,
+    }
+
+    // Right-associative so a trailing "!" is the error marker, not a `!x` statement
+    #[rust_sitter::prec_right(1)]
     #[derive(Debug, Clone)]
     pub struct RustFnDecl {
         #[rust_sitter::leaf(text = "rust")]
@@ -578,4 +1118,234 @@ pub mod grammar {
         // No body - this is an external function
     }
 }
+pub mod strings;
+pub mod syntax;
 pub use grammar::*;
+
+// Errors raised by the collection commands (name, description); every program knows them
+pub const BUILTIN_ERRORS: [(&str, &str); 3] = [
+    ("IndexOutOfBounds", "Index out of bounds"),
+    ("KeyNotFound", "Key not found"),
+    ("EmptyList", "List is empty"),
+];
+
+impl AnnotatableItem {
+    pub fn into_statement(self) -> Statement {
+        match self {
+            AnnotatableItem::StructDef(s) => Statement::StructDef(s),
+            AnnotatableItem::EnumDef(e) => Statement::EnumDef(e),
+            AnnotatableItem::FunctionDef(f) => Statement::FunctionDef(f),
+            AnnotatableItem::RustFnDecl(r) => Statement::RustFnDecl(r),
+        }
+    }
+}
+
+impl ImplBlock {
+    // The struct receiving the methods
+    pub fn struct_name(&self) -> &str {
+        match &self.target {
+            Some(target) => &target.name.value,
+            None => &self.name.value,
+        }
+    }
+
+    // The interface being implemented, for "impl Shape for Circle"
+    pub fn interface(&self) -> Option<&str> {
+        self.target.as_ref().map(|_| self.name.value.as_str())
+    }
+}
+
+impl Expression {
+    // Integral literals (`5`, `-3`) are `num` by default and become `int` next to an int
+    pub fn int_literal(&self) -> Option<i64> {
+        match self {
+            Expression::Number(n) if !n.value.contains('.') => n.value.parse().ok(),
+            Expression::Neg(_, inner) => inner.int_literal().map(|v| -v),
+            _ => None,
+        }
+    }
+
+    // Short source-like rendering for error messages; anything beyond names, moves, fields
+    // and calls is elided
+    pub fn describe(&self) -> String {
+        match self {
+            Expression::Variable(v) => v.value.clone(),
+            Expression::Number(n) => n.value.clone(),
+            Expression::MoveExpr(_, v) => format!("move {}", v.value),
+            Expression::FieldAccess(target, _, field) => {
+                format!("{}.{}", target.describe(), field.value)
+            }
+            Expression::Call(func, args) if args.items.is_empty() => {
+                format!("{}()", func.describe())
+            }
+            Expression::Call(func, _) => format!("{}(…)", func.describe()),
+            Expression::RunCall(_, call) => format!("run {}", call.describe()),
+            Expression::Try(_, inner) => format!("try {}", inner.describe()),
+            Expression::TupleLit(list) => format!(
+                "({})",
+                list.items
+                    .iter()
+                    .map(|item| item.describe())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => "…".to_string(),
+        }
+    }
+}
+
+impl Statement {
+    // Short source-like rendering of the statement head, for error messages
+    pub fn describe(&self) -> String {
+        match self {
+            Statement::VarDecl { ident, value, .. } => {
+                format!("var {} = {}", ident, value.describe())
+            }
+            Statement::VarTuple { names, value, .. } => {
+                format!("var ({}) = {}", names.values().join(", "), value.describe())
+            }
+            Statement::AssignStmt { lhs, rhs, .. } => {
+                format!("{} = {}", lhs.describe(), rhs.describe())
+            }
+            Statement::Print(_, expr) => format!("print {}", expr.describe()),
+            Statement::Return(_, Some(expr)) => format!("return {}", expr.describe()),
+            Statement::Give(_, channel, value) => {
+                format!("give {} {}", channel.describe(), value.describe())
+            }
+            Statement::On { condition, .. } => format!("on ({})", condition.describe()),
+            Statement::LoopOn { condition, .. } => format!("loop on ({})", condition.describe()),
+            Statement::LoopIter { iterable, .. } => format!("loop … in {}", iterable.describe()),
+            Statement::ExprStmt(expr) => expr.describe(),
+            _ => "…".to_string(),
+        }
+    }
+}
+
+impl Block {
+    // Ends with return, break or continue, so control never reaches the end of the block
+    pub fn diverges(&self) -> bool {
+        matches!(
+            self.statements.last().map(|s| &s.value),
+            Some(Statement::Return(..) | Statement::Break(..) | Statement::Continue(..))
+        )
+    }
+}
+
+impl KiroType {
+    // (a, b, ...) from at least two element types
+    pub fn tuple(mut items: Vec<KiroType>) -> KiroType {
+        let first = items.remove(0);
+        KiroType::Tuple((), Box::new(first), (), items, ())
+    }
+
+    // fn(params) -> ret
+    pub fn func(params: Vec<KiroType>, ret: Option<KiroType>) -> KiroType {
+        let ret = ret.map(|t| FuncTypeReturn {
+            _arrow: (),
+            ret: Box::new(t),
+        });
+        KiroType::Func(None, (), (), params, (), ret)
+    }
+
+    // Element types of a tuple type
+    pub fn tuple_items(&self) -> Option<Vec<KiroType>> {
+        match self {
+            KiroType::Tuple(_, first, _, rest, _) => Some(
+                std::iter::once((**first).clone())
+                    .chain(rest.clone())
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    // Error payload fields cross the host boundary as kiro_runtime values
+    pub fn is_payload(&self) -> bool {
+        matches!(
+            self,
+            KiroType::Num | KiroType::Int | KiroType::Str | KiroType::Bool
+        )
+    }
+}
+
+// Kiro syntax of the type, for error messages: `list int`, `fn(num) -> bool`
+impl std::fmt::Display for KiroType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let join = |types: &[KiroType]| {
+            types
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            KiroType::Num => write!(f, "num"),
+            KiroType::Int => write!(f, "int"),
+            KiroType::Str => write!(f, "str"),
+            KiroType::Bool => write!(f, "bool"),
+            KiroType::Void => write!(f, "void"),
+            KiroType::Adr(_, inner) => write!(f, "adr {}", inner),
+            KiroType::Pipe(_, inner) => write!(f, "pipe {}", inner),
+            KiroType::Opt(_, inner) => write!(f, "opt {}", inner),
+            KiroType::List(_, inner) => write!(f, "list {}", inner),
+            KiroType::Map(_, k, v) => write!(f, "map {} {}", k, v),
+            KiroType::Func(pure_kw, _, _, params, _, ret) => {
+                if pure_kw.is_some() {
+                    write!(f, "pure ")?;
+                }
+                write!(f, "fn({})", join(params))?;
+                match ret {
+                    Some(r) => write!(f, " -> {}", r.ret),
+                    None => Ok(()),
+                }
+            }
+            KiroType::Generic(name, _, args, _) => write!(f, "{}<{}>", name.value, join(args)),
+            KiroType::Tuple(..) => write!(f, "({})", join(&self.tuple_items().unwrap())),
+            KiroType::Custom(name) => write!(f, "{}", name.value),
+        }
+    }
+}
+
+impl PatternBindings {
+    pub fn values(&self) -> Vec<String> {
+        self.names.iter().map(|n| n.value.clone()).collect()
+    }
+}
+
+impl CompoundOp {
+    // `x op= v` is evaluated as `x = x op v`
+    pub fn desugar(self, lhs: Expression, rhs: Expression) -> Statement {
+        let (lhs_box, rhs_box) = (Box::new(lhs.clone()), Box::new(rhs));
+        let value = match self {
+            CompoundOp::Add(_) => Expression::Add(lhs_box, (), rhs_box),
+            CompoundOp::Sub(_) => Expression::Sub(lhs_box, Minus { _minus: () }, rhs_box),
+            CompoundOp::Mul(_) => Expression::Mul(lhs_box, (), rhs_box),
+            CompoundOp::Div(_) => Expression::Div(lhs_box, (), rhs_box),
+        };
+        Statement::AssignStmt {
+            lhs,
+            _eq: (),
+            rhs: value,
+        }
+    }
+}
+
+impl MethodSig {
+    // Same receiver, purity, failability and parameter/return types as `def`
+    pub fn matches(&self, def: &MethodDef) -> bool {
+        let sig_params = self.params.iter().map(|p| &p.param.command_type);
+        let def_params = def.params.iter().map(|p| &p.param.command_type);
+        // A missing return type and `-> void` are the same
+        let sig_ret = self
+            .ret
+            .as_ref()
+            .map(|r| &*r.ret)
+            .filter(|t| **t != KiroType::Void);
+        let def_ret = def.return_type.as_ref().filter(|t| **t != KiroType::Void);
+        self.pure_kw.is_some() == def.pure_kw.is_some()
+            && self.var_kw.is_some() == def.var_kw.is_some()
+            && self.can_error.is_some() == def.can_error.is_some()
+            && sig_params.eq(def_params)
+            && sig_ret == def_ret
+    }
+}
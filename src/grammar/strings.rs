@@ -0,0 +1,226 @@
+use super::grammar::{Expression, FieldNameVal, StringVal, VariableVal};
+
+// Piece of a string literal: plain text, or a `{path:spec}` placeholder
+#[derive(Debug, Clone)]
+pub enum StrPart {
+    // Decoded text (escapes applied, `{{` / `}}` collapsed)
+    Lit(String),
+    Expr(Expression, FormatSpec),
+}
+
+// Subset of Rust's format spec: [<|>|^][0][width][.precision]
+#[derive(Debug, Clone, Default)]
+pub struct FormatSpec {
+    pub align: Option<char>,
+    pub zero: bool,
+    pub width: Option<usize>,
+    pub precision: Option<usize>,
+}
+
+impl FormatSpec {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut rest = spec;
+        let mut out = FormatSpec::default();
+        if let Some(c) = rest.chars().next().filter(|c| matches!(c, '<' | '>' | '^')) {
+            out.align = Some(c);
+            rest = &rest[1..];
+        }
+        if let Some(r) = rest.strip_prefix('0') {
+            out.zero = true;
+            rest = r;
+        }
+        let (width, precision) = match rest.split_once('.') {
+            Some((w, p)) => (w, Some(p)),
+            None => (rest, None),
+        };
+        let number = |s: &str| -> Result<usize, String> {
+            s.parse()
+                .map_err(|_| format!("Invalid format spec ':{}' in string.", spec))
+        };
+        if !width.is_empty() {
+            out.width = Some(number(width)?);
+        }
+        if let Some(p) = precision {
+            out.precision = Some(number(p)?);
+        }
+        if out.zero && out.align.is_some() {
+            return Err(format!(
+                "Invalid format spec ':{}' in string: '0' padding cannot be combined with alignment.",
+                spec
+            ));
+        }
+        Ok(out)
+    }
+
+    // The same spec in Rust's format! syntax, e.g. ":>8.2"
+    pub fn to_rust(&self) -> String {
+        let mut out = String::new();
+        if let Some(a) = self.align {
+            out.push(a);
+        }
+        if self.zero {
+            out.push('0');
+        }
+        if let Some(w) = self.width {
+            out.push_str(&w.to_string());
+        }
+        if let Some(p) = self.precision {
+            out.push_str(&format!(".{}", p));
+        }
+        if out.is_empty() {
+            out
+        } else {
+            format!(":{}", out)
+        }
+    }
+}
+
+// `name` or `user.address.city`
+fn parse_path(path: &str) -> Option<Expression> {
+    let valid = |s: &str| {
+        s.chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+            && s.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+    };
+    let mut segments = path.split('.');
+    let root = segments.next().filter(|s| valid(s))?;
+    let mut expr = Expression::Variable(VariableVal {
+        value: root.to_string(),
+    });
+    for field in segments {
+        if !valid(field) {
+            return None;
+        }
+        expr = Expression::FieldAccess(
+            Box::new(expr),
+            (),
+            FieldNameVal {
+                value: field.to_string(),
+            },
+        );
+    }
+    Some(expr)
+}
+
+// Decodes the escape at the start of `rest` (just after the backslash).
+// Returns the character and how many bytes of `rest` it used.
+fn decode_escape(rest: &str) -> Result<(char, usize), String> {
+    let c = rest
+        .chars()
+        .next()
+        .ok_or("Unterminated escape sequence in string.")?;
+    let simple = match c {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        '0' => Some('\0'),
+        '\\' => Some('\\'),
+        '"' => Some('"'),
+        _ => None,
+    };
+    if let Some(decoded) = simple {
+        return Ok((decoded, 1));
+    }
+    if c == 'u'
+        && let Some(hex) = rest.strip_prefix("u{")
+        && let Some(end) = hex.find('}')
+    {
+        let decoded = u32::from_str_radix(&hex[..end], 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("Invalid unicode escape '\\u{{{}}}' in string.", &hex[..end]))?;
+        return Ok((decoded, end + 3));
+    }
+    Err(format!("Unknown escape sequence '\\{}' in string.", c))
+}
+
+impl StringVal {
+    pub fn is_raw(&self) -> bool {
+        self.value.starts_with('r')
+    }
+
+    // Source text between the quotes. A newline right after an opening """ is dropped.
+    fn body(&self) -> &str {
+        let quoted = self.value.strip_prefix('r').unwrap_or(&self.value);
+        if quoted.len() >= 6 && quoted.starts_with("\"\"\"") {
+            let inner = &quoted[3..quoted.len() - 3];
+            inner
+                .strip_prefix("\r\n")
+                .or_else(|| inner.strip_prefix('\n'))
+                .unwrap_or(inner)
+        } else {
+            &quoted[1..quoted.len() - 1]
+        }
+    }
+
+    // The literal's text with escapes decoded, for places that do not interpolate
+    // (match patterns, error descriptions)
+    pub fn text(&self) -> Result<String, String> {
+        if self.is_raw() {
+            return Ok(self.body().to_string());
+        }
+        let mut out = String::new();
+        let mut rest = self.body();
+        while let Some(c) = rest.chars().next() {
+            if c == '\\' {
+                let (decoded, used) = decode_escape(&rest[1..])?;
+                out.push(decoded);
+                rest = &rest[1 + used..];
+            } else {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        Ok(out)
+    }
+
+    // Splits `"user {name} is {age:.1} years"` into text and placeholders, decoding escapes.
+    // Braces that do not form a placeholder are kept as text. Raw strings are never split.
+    pub fn parts(&self) -> Result<Vec<StrPart>, String> {
+        if self.is_raw() {
+            return Ok(vec![StrPart::Lit(self.body().to_string())]);
+        }
+        let mut parts = Vec::new();
+        let mut lit = String::new();
+        let mut rest = self.body();
+
+        while let Some(c) = rest.chars().next() {
+            if c == '\\' {
+                let (decoded, used) = decode_escape(&rest[1..])?;
+                lit.push(decoded);
+                rest = &rest[1 + used..];
+                continue;
+            }
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                lit.push(c);
+                rest = &rest[2..];
+                continue;
+            }
+            if c == '{'
+                && let Some(end) = rest.find('}')
+            {
+                let inner = &rest[1..end];
+                let (path, spec) = match inner.split_once(':') {
+                    Some((p, s)) => (p, Some(s)),
+                    None => (inner, None),
+                };
+                if let Some(expr) = parse_path(path.trim()) {
+                    let spec = spec.map(FormatSpec::parse).transpose()?.unwrap_or_default();
+                    if !lit.is_empty() {
+                        parts.push(StrPart::Lit(std::mem::take(&mut lit)));
+                    }
+                    parts.push(StrPart::Expr(expr, spec));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+            lit.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        if !lit.is_empty() || parts.is_empty() {
+            parts.push(StrPart::Lit(lit));
+        }
+        Ok(parts)
+    }
+}
@@ -0,0 +1,327 @@
+use super::grammar::{self, Program};
+use crate::diagnostics::{Diagnostic, Kind};
+use tree_sitter::{Language, Node, Parser};
+
+// STRING_TOKENS / PATTERN_TOKENS: rule name -> text or regex of each token (see build.rs)
+include!(concat!(env!("OUT_DIR"), "/tokens.rs"));
+
+// Parses a file, or reports every syntax error in it
+pub fn parse_file(source: &str) -> Result<Program, Vec<Diagnostic>> {
+    grammar::parse(source).map_err(|_| syntax_errors(source))
+}
+
+// One error per ERROR or MISSING node of tree-sitter's recovered tree, saying what
+// could have come instead and, for common mistakes, how to fix it
+pub fn syntax_errors(source: &str) -> Vec<Diagnostic> {
+    let language = grammar::language();
+    let mut parser = Parser::new();
+    let tree = parser
+        .set_language(&language)
+        .ok()
+        .and_then(|_| parser.parse(source, None));
+    let mut errors = Vec::new();
+    if let Some(tree) = &tree {
+        collect(tree.root_node(), source, &language, &mut errors);
+    }
+    if errors.is_empty() {
+        errors.push(Diagnostic::new(Kind::Parse, "Syntax error.", (0, 0)));
+    }
+    errors
+}
+
+fn collect(node: Node, source: &str, language: &Language, errors: &mut Vec<Diagnostic>) {
+    if node.is_error() && node.parent().is_none() {
+        unfinished(node, source, language, errors);
+    } else if node.is_error() {
+        errors.push(unexpected(node, source, language));
+    } else if node.is_missing() {
+        errors.push(missing(node, source, language));
+    } else if node.has_error() {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            collect(child, source, language, errors);
+        }
+    }
+}
+
+// An ERROR node holds the pieces tree-sitter skipped: the parts that still fit, then the
+// token that did not. That token is the error, and the state before it says what fits.
+fn unexpected(error: Node, source: &str, language: &Language) -> Diagnostic {
+    let (culprit, state) = find_culprit(error, state_before(error), language);
+    let span = word_at(source, first_leaf(culprit));
+    let text = source[span.0..span.1]
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim();
+    let expected = state
+        .map(|s| expected_tokens(language, s))
+        .unwrap_or_default();
+
+    let mut message = match text {
+        "" => "Unexpected end of file.".to_string(),
+        _ => format!("Unexpected '{}'.", shorten(text)),
+    };
+    if !expected.is_empty() {
+        message.push_str(&format!(" Expected {}.", one_of(&expected)));
+    }
+    let diagnostic = Diagnostic::new(Kind::Parse, message, span).label("not expected here");
+    match hint(error, text, &expected, source) {
+        Some(help) => diagnostic.help(help),
+        None => diagnostic,
+    }
+}
+
+// The whole program is an ERROR when the file ends inside a statement. The statements
+// before it are reported on their own; the unfinished one is an error at the end of file.
+fn unfinished(root: Node, source: &str, language: &Language, errors: &mut Vec<Diagnostic>) {
+    let mut cursor = root.walk();
+    let children: Vec<Node> = root.children(&mut cursor).collect();
+    let rest = children
+        .iter()
+        .rposition(|c| c.is_error() || root.field_name_for_child(index_of(&children, c)).is_some())
+        .map_or(0, |i| i + 1);
+    for child in &children[..rest] {
+        collect(*child, source, language, errors);
+    }
+    let tokens: Vec<Node> = children[rest..]
+        .iter()
+        .filter(|c| !c.is_extra())
+        .copied()
+        .collect();
+    let Some(last) = tokens.last() else {
+        return;
+    };
+    // Step the parser from the start state through the unfinished statement
+    let mut state = Some(1);
+    for token in &tokens {
+        state = state
+            .map(|s| language.next_state(s, token.grammar_id()))
+            .filter(|s| *s != 0);
+    }
+    let mut message = "Unexpected end of file.".to_string();
+    let expected = state
+        .map(|s| expected_tokens(language, s))
+        .unwrap_or_default();
+    if !expected.is_empty() {
+        message.push_str(&format!(" Expected {}.", one_of(&expected)));
+    }
+    let at = last.end_byte();
+    errors.push(
+        Diagnostic::new(Kind::Parse, message, (at, at)).label("the statement is not finished"),
+    );
+}
+
+fn index_of(children: &[Node], child: &Node) -> u32 {
+    children.iter().position(|c| c == child).unwrap_or_default() as u32
+}
+
+fn missing(node: Node, source: &str, language: &Language) -> Diagnostic {
+    let token = describe(node.kind())
+        .or_else(|| {
+            describe(
+                language
+                    .node_kind_for_id(node.grammar_id())
+                    .unwrap_or_default(),
+            )
+        })
+        .unwrap_or_else(|| format!("`{}`", node.kind()));
+    // Right after the last token that was there, rather than at the next line
+    let at = node
+        .prev_sibling()
+        .map(|prev| prev.end_byte())
+        .unwrap_or(node.start_byte());
+    let diagnostic = Diagnostic::new(Kind::Parse, format!("Missing {}.", token), (at, at))
+        .label(format!("expected {} here", token));
+    match hint(node, "", std::slice::from_ref(&token), source) {
+        Some(help) => diagnostic.help(help),
+        None => diagnostic,
+    }
+}
+
+// The first child the parser could not accept, descending into nested ERROR nodes, and
+// the parse state it was met in
+fn find_culprit<'a>(
+    error: Node<'a>,
+    mut state: Option<u16>,
+    language: &Language,
+) -> (Node<'a>, Option<u16>) {
+    let mut cursor = error.walk();
+    let children: Vec<Node> = error.children(&mut cursor).collect();
+    for child in children {
+        if child.is_extra() {
+            continue;
+        }
+        if child.is_error() && child.child_count() > 0 {
+            return find_culprit(child, state, language);
+        }
+        let fits = state.is_none_or(|s| accepts(language, s, child));
+        if child.is_error() || child.is_missing() || !fits {
+            return (child, state);
+        }
+        // Pieces inside an ERROR may have lost their state; step the parser by hand
+        state = match child.next_parse_state() {
+            0 => state.map(|s| language.next_state(s, child.grammar_id())),
+            next => Some(next),
+        }
+        .filter(|s| *s != 0);
+    }
+    (error, state)
+}
+
+// Parse state after the last real token before `node`
+fn state_before(node: Node) -> Option<u16> {
+    let mut prev = node.prev_sibling();
+    while let Some(p) = prev {
+        if !p.is_extra() {
+            return Some(p.next_parse_state()).filter(|s| *s != 0);
+        }
+        prev = p.prev_sibling();
+    }
+    None
+}
+
+fn accepts(language: &Language, state: u16, node: Node) -> bool {
+    let Some(lookahead) = language.lookahead_iterator(state) else {
+        return true;
+    };
+    let first = first_leaf(node).grammar_id();
+    let symbols: Vec<u16> = lookahead.collect();
+    symbols.contains(&node.grammar_id()) || symbols.contains(&first)
+}
+
+// The token's span, widened to the whole word when the lexer split one: `int` where a
+// keyword was possible lexes as `in` + `t`
+fn word_at(source: &str, token: Node) -> (usize, usize) {
+    let (start, mut end) = (token.start_byte(), token.end_byte());
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    if source[start..end].chars().all(is_word) {
+        end += source[end..]
+            .find(|c: char| !is_word(c))
+            .unwrap_or(source.len() - end);
+    }
+    (start, end)
+}
+
+fn first_leaf(node: Node) -> Node {
+    let mut leaf = node;
+    while let Some(child) = leaf.child(0) {
+        leaf = child;
+    }
+    leaf
+}
+
+// Tokens that may come next in a parse state, as written in source
+fn expected_tokens(language: &Language, state: u16) -> Vec<String> {
+    let Some(mut lookahead) = language.lookahead_iterator(state) else {
+        return Vec::new();
+    };
+    let mut tokens: Vec<String> = Vec::new();
+    for name in lookahead.iter_names() {
+        if let Some(token) = describe(name)
+            && !tokens.contains(&token)
+        {
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+// `keyword` for fixed tokens, a description for names and literals; None for internal
+// symbols, whitespace and comments
+fn describe(symbol: &str) -> Option<String> {
+    // Tree-sitter splits some patterns into numbered tokens: VariableVal_value_token1
+    let name = match symbol.rsplit_once("_token") {
+        Some((base, n)) if n.chars().all(|c| c.is_ascii_digit()) => base,
+        _ => symbol,
+    };
+    if name == "end" {
+        return Some("end of file".to_string());
+    }
+    if let Some((_, text)) = STRING_TOKENS.iter().find(|(rule, _)| *rule == name) {
+        return Some(format!("`{}`", text));
+    }
+    if let Some((rule, pattern)) = PATTERN_TOKENS.iter().find(|(rule, _)| *rule == name) {
+        return match () {
+            _ if rule.starts_with("Whitespace") || rule.starts_with("DocComment") => None,
+            _ if pattern.starts_with(r"\d") => Some("a number".to_string()),
+            _ if pattern.contains('"') => Some("a string".to_string()),
+            _ if pattern.starts_with("[A-Z]") => Some("a capitalized name".to_string()),
+            _ => Some("a name".to_string()),
+        };
+    }
+    // Literal tokens used in one place are named by their text
+    let is_literal = !name.is_empty()
+        && (name.chars().all(|c| c.is_ascii_lowercase())
+            || name.chars().all(|c| c.is_ascii_punctuation()));
+    is_literal.then(|| format!("`{}`", name))
+}
+
+fn one_of(tokens: &[String]) -> String {
+    const SHOWN: usize = 8;
+    match tokens {
+        [one] => one.clone(),
+        [first, second] => format!("{} or {}", first, second),
+        _ if tokens.len() <= SHOWN => format!("one of {}", tokens.join(", ")),
+        _ => format!(
+            "one of {}, ... ({} more)",
+            tokens[..SHOWN].join(", "),
+            tokens.len() - SHOWN
+        ),
+    }
+}
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(24) {
+        Some((i, _)) => format!("{}...", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+fn inside(node: Node, kind: &str) -> bool {
+    let mut current = Some(node);
+    while let Some(n) = current {
+        if n.kind() == kind {
+            return true;
+        }
+        current = n.parent();
+    }
+    false
+}
+
+// Fixes for mistakes people bring from other languages
+fn hint(node: Node, text: &str, expected: &[String], source: &str) -> Option<&'static str> {
+    let line_start = source[..node.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+    let line = source[line_start..]
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_start();
+    let first_word = line.split(|c: char| !c.is_alphanumeric()).next();
+    let expects = |token: &str| expected.iter().any(|t| t == token);
+
+    if text == "," && (inside(node, "StructDef") || inside(node, "ErrorFields")) {
+        return Some("Fields are separated by new lines, not commas.");
+    }
+    if expects("`->`") && line.starts_with("rust fn") {
+        return Some(
+            "A `rust fn` declares its return type after `->`: rust fn name(x: int) -> str",
+        );
+    }
+    if expects("`->`") {
+        return Some("The return type comes after `->`: fn name(x: int) -> str { ... }");
+    }
+    if text == ";" {
+        return Some("Statements end at the end of the line; remove the `;`.");
+    }
+    match first_word {
+        Some("if") => Some("Conditions are written `on (condition) { ... }`."),
+        Some("else") => Some("The other branch of `on` is written `off { ... }`."),
+        Some("while") | Some("for") => {
+            Some("Loops are written `loop x in items { ... }` or `loop on (condition) { ... }`.")
+        }
+        Some("let") => Some("Variables are declared with `var`."),
+        Some("def") | Some("func") | Some("function") => Some("Functions are declared with `fn`."),
+        _ => None,
+    }
+}
@@ -0,0 +1,230 @@
+use super::Interpreter;
+use super::values::RuntimeVal;
+use crate::grammar::grammar::{Expression, KiroType};
+use std::collections::HashMap;
+
+// A built-in error value, e.g. EmptyList from `pop` on an empty list
+fn builtin_error(name: &str) -> RuntimeVal {
+    let (_, desc) = crate::grammar::BUILTIN_ERRORS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .expect("not a built-in error");
+    RuntimeVal::Error(name.to_string(), desc.to_string(), HashMap::new())
+}
+
+fn index_of(index: &RuntimeVal, len: usize) -> Option<usize> {
+    match index {
+        RuntimeVal::Int(i) => usize::try_from(*i).ok().filter(|i| *i < len),
+        _ => None,
+    }
+}
+
+impl Interpreter {
+    // Runs `f` on the value stored at `target` (a variable or field path), whose root
+    // variable must be mutable
+    pub fn with_place<T>(
+        &mut self,
+        target: Expression,
+        f: impl FnOnce(&mut RuntimeVal) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut path = Vec::new();
+        let mut current = target;
+        while let Expression::FieldAccess(inner, _, field) = current {
+            path.push(field.value);
+            current = *inner;
+        }
+        let root_name = match current {
+            Expression::Variable(v) => v.value,
+            Expression::ErrorRef(name) => name.value,
+            _ => return Err("ERROR: Only variables and their fields can be mutated.".to_string()),
+        };
+        if self.consts.contains_key(&root_name) {
+            return Err(format!("ERROR: '{}' is a constant.", root_name));
+        }
+
+        let entry = self
+            .env
+            .get_mut(&root_name)
+            .ok_or_else(|| format!("ERROR: Variable '{}' not found.", root_name))?;
+        if !entry.is_mutable {
+            return Err(format!("ERROR: '{}' is immutable.", root_name));
+        }
+        if let RuntimeVal::Moved(site) = entry.data {
+            return Err(self.moved_error(&root_name, site));
+        }
+
+        let mut place = &mut entry.data;
+        for field in path.iter().rev() {
+            place = match place {
+                RuntimeVal::Struct(_, fields) => fields
+                    .get_mut(field)
+                    .ok_or_else(|| format!("Field '{}' not found", field))?,
+                _ => return Err("Cannot access field on non-struct".to_string()),
+            };
+        }
+        f(place)
+    }
+
+    // Ints for list indexes; map keys keep the numeric type of the keys already stored
+    fn eval_key(&mut self, col: &RuntimeVal, key: Expression) -> Result<RuntimeVal, String> {
+        match col {
+            RuntimeVal::Map(map) => match map.values().next().and_then(|(k, _)| k.numeric_type()) {
+                Some(t) => self.eval_expecting(key, &t),
+                None => self.eval_expr(key),
+            },
+            _ => match self.eval_expecting(key, &KiroType::Int)? {
+                index @ RuntimeVal::Int(_) => Ok(index),
+                _ => Err("Type Error: List index must be an int. Convert with int(x).".to_string()),
+            },
+        }
+    }
+
+    // New elements keep the numeric type of the elements already stored
+    fn eval_element(&mut self, col: &RuntimeVal, value: Expression) -> Result<RuntimeVal, String> {
+        let expected = match col {
+            RuntimeVal::List(items) => items.first().and_then(|v| v.numeric_type()),
+            RuntimeVal::Map(map) => map.values().next().and_then(|(_, v)| v.numeric_type()),
+            _ => None,
+        };
+        match expected {
+            Some(t) => self.eval_expecting(value, &t),
+            None => self.eval_expr(value),
+        }
+    }
+
+    // Collection commands mutate the collection stored in the variable. A failed `insert`,
+    // `remove` or `pop` gives a Kiro error value that `on (...) error` can handle.
+    pub fn eval_collection_op(&mut self, expr: Expression) -> Result<RuntimeVal, String> {
+        match expr {
+            Expression::Push(col, _, value) => {
+                let current = self.eval_checked((*col).clone())?;
+                let value = self.eval_element(&current, *value)?;
+                self.with_place(*col, |place| match place {
+                    RuntimeVal::List(items) => {
+                        items.push(value);
+                        Ok(RuntimeVal::Void)
+                    }
+                    _ => Err("Cannot use 'push' on this type".to_string()),
+                })
+            }
+            Expression::Insert(col, _, args) => {
+                let Ok([key, value]) = <[Expression; 2]>::try_from(args.items) else {
+                    return Err(
+                        "ERROR: 'insert' expects (index, value) or (key, value).".to_string()
+                    );
+                };
+                let current = self.eval_checked((*col).clone())?;
+                let key = self.eval_key(&current, key)?;
+                let value = self.eval_element(&current, value)?;
+                self.with_place(*col, |place| match (place, key) {
+                    (RuntimeVal::List(items), RuntimeVal::Int(i)) => {
+                        match usize::try_from(i).ok().filter(|i| *i <= items.len()) {
+                            Some(i) => items.insert(i, value),
+                            None => return Ok(builtin_error("IndexOutOfBounds")),
+                        }
+                        Ok(RuntimeVal::Void)
+                    }
+                    (RuntimeVal::Map(map), key) => {
+                        map.insert(key.to_string(), (key, value));
+                        Ok(RuntimeVal::Void)
+                    }
+                    _ => Err("Cannot use 'insert' on this type".to_string()),
+                })
+            }
+            Expression::Remove(col, _, key) => {
+                let current = self.eval_checked((*col).clone())?;
+                let key = self.eval_key(&current, *key)?;
+                self.with_place(*col, |place| match place {
+                    RuntimeVal::List(items) => Ok(match index_of(&key, items.len()) {
+                        Some(i) => items.remove(i),
+                        None => builtin_error("IndexOutOfBounds"),
+                    }),
+                    RuntimeVal::Map(map) => Ok(map
+                        .remove(&key.to_string())
+                        .map(|(_, v)| v)
+                        .unwrap_or_else(|| builtin_error("KeyNotFound"))),
+                    _ => Err("Cannot use 'remove' on this type".to_string()),
+                })
+            }
+            // Lists are searched by value, maps by key
+            Expression::Contains(col, _, item) => {
+                let current = self.eval_checked(*col)?;
+                match &current {
+                    RuntimeVal::List(items) => {
+                        let item = self.eval_element(&current, *item)?;
+                        Ok(RuntimeVal::Bool(items.contains(&item)))
+                    }
+                    RuntimeVal::Map(map) => {
+                        let key = self.eval_expr(*item)?.to_string();
+                        Ok(RuntimeVal::Bool(map.contains_key(&key)))
+                    }
+                    _ => Err("Cannot use 'contains' on this type".to_string()),
+                }
+            }
+            Expression::Pop(_, col) => self.with_place(*col, |place| match place {
+                RuntimeVal::List(items) => {
+                    Ok(items.pop().unwrap_or_else(|| builtin_error("EmptyList")))
+                }
+                _ => Err("Cannot use 'pop' on this type".to_string()),
+            }),
+            Expression::Clear(_, col) => self.with_place(*col, |place| match place {
+                RuntimeVal::List(items) => {
+                    items.clear();
+                    Ok(RuntimeVal::Void)
+                }
+                RuntimeVal::Map(map) => {
+                    map.clear();
+                    Ok(RuntimeVal::Void)
+                }
+                _ => Err("Cannot use 'clear' on this type".to_string()),
+            }),
+            _ => unreachable!("not a collection command"),
+        }
+    }
+
+    // A built-in error from a collection access leaves a failable function with the error,
+    // like `try`, and is a runtime error anywhere else
+    pub fn raise(&mut self, name: &str) -> String {
+        let error = builtin_error(name);
+        let RuntimeVal::Error(name, desc, _) = &error else {
+            unreachable!()
+        };
+        let message = format!("Runtime Error: Unhandled error {}: {}", name, desc);
+        if self.in_failable_fn {
+            self.pending_error = Some(error);
+        }
+        message
+    }
+
+    // list at index = value / map at key = value. An index out of range is IndexOutOfBounds.
+    pub fn set_at(
+        &mut self,
+        col: Expression,
+        key: Expression,
+        value: RuntimeVal,
+    ) -> Result<(), String> {
+        let current = self.eval_checked(col.clone())?;
+        let key = self.eval_key(&current, key)?;
+        let in_range = match &current {
+            RuntimeVal::List(items) => index_of(&key, items.len()).is_some(),
+            _ => true,
+        };
+        if !in_range {
+            return Err(self.raise("IndexOutOfBounds"));
+        }
+        self.with_place(col, |place| match place {
+            RuntimeVal::List(items) => match index_of(&key, items.len()) {
+                Some(i) => {
+                    items[i] = value;
+                    Ok(())
+                }
+                None => Err("Index out of bounds".to_string()),
+            },
+            RuntimeVal::Map(map) => {
+                map.insert(key.to_string(), (key, value));
+                Ok(())
+            }
+            _ => Err("Cannot use 'at' on this type".to_string()),
+        })
+    }
+}
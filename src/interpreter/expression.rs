@@ -1,10 +1,33 @@
 use super::Interpreter;
-use super::values::RuntimeVal;
-use crate::grammar::grammar::{self, Expression, Statement};
+use super::StatementResult;
+use super::values::{FunctionVal, RuntimeVal, Value, check_numeric, checked_int};
+use crate::grammar::grammar::{self, Expression, KiroType, Statement};
+use crate::grammar::strings::StrPart;
 use std::collections::HashMap;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
+// An error value carries exactly the fields its definition declares
+fn check_error_fields(
+    name: &str,
+    declared: &[grammar::FieldDef],
+    data: &HashMap<String, RuntimeVal>,
+) -> Result<(), String> {
+    if let Some(field) = data
+        .keys()
+        .find(|k| !declared.iter().any(|d| d.name.value == **k))
+    {
+        return Err(format!("ERROR: Error '{}' has no field '{}'.", name, field));
+    }
+    match declared.iter().find(|d| !data.contains_key(&d.name.value)) {
+        Some(d) => Err(format!(
+            "ERROR: Error '{}' is missing field '{}'.",
+            name, d.name.value
+        )),
+        None => Ok(()),
+    }
+}
+
 impl Interpreter {
     pub fn eval_expr(&mut self, expr: Expression) -> Result<RuntimeVal, String> {
         match expr {
@@ -16,38 +39,93 @@ impl Interpreter {
                     );
                 }
 
-                // We need to modify the env, so we need mutable access.
-                if let Some(val) = self.env.get_mut(&name) {
-                    if !val.is_mutable {
-                        return Err(format!(
-                            "Interpreter Error: Cannot move immutable variable '{}'.",
-                            name
-                        ));
-                    }
-                    // Take the value, replace with Moved
-                    let moved_val = val.data.clone();
-                    // We can't actually "take" out of HashMap easily without replacing.
-                    // But we want to invalidate the source.
-                    val.data = RuntimeVal::Moved;
-                    Ok(moved_val)
-                } else {
-                    Err(format!("Interpreter Error: Variable '{}' not found.", name))
+                let frame = self.env.frame_of(&name);
+                let Some(val) = self.env.get_mut(&name) else {
+                    return Err(format!("Interpreter Error: Variable '{}' not found.", name));
+                };
+                if !val.is_mutable {
+                    return Err(format!(
+                        "Interpreter Error: Cannot move immutable variable '{}'.",
+                        name
+                    ));
+                }
+                if let RuntimeVal::Moved(site) = val.data {
+                    return Err(self.moved_error(&name, site));
+                }
+                // A variable declared outside the running loop would be moved again on the
+                // next iteration
+                if let Some(&depth) = self.loop_depths.last()
+                    && frame.is_some_and(|frame| frame < depth)
+                {
+                    return Err(format!(
+                        "Interpreter Error: Cannot move '{}' inside a loop, it is declared outside the loop.\n  moved in: {}",
+                        name, self.current_stmt
+                    ));
                 }
+
+                // Take the value; the variable stays unusable until it is assigned again
+                let site = RuntimeVal::Moved(self.current_span);
+                Ok(std::mem::replace(&mut val.data, site))
             }
 
             Expression::StructInit(name, _, fields, _) => {
-                // 1. Evaluate all fields
+                // 1. Evaluate all fields against their declared types
                 let mut data = HashMap::new();
                 for f in fields {
-                    let val = self.eval_expr(f.value)?;
+                    let declared = self.structs.get(&name.value).and_then(|defs| {
+                        defs.iter()
+                            .find(|d| d.name.value == f.name.value)
+                            .map(|d| d.field_type.clone())
+                    });
+                    let val = match declared {
+                        Some(t) => self.eval_expecting(f.value, &t)?,
+                        None => self.eval_expr(f.value)?,
+                    };
                     data.insert(f.name.value, val);
                 }
-                // 2. Return Struct Value
+                // 2. An error type gives an error value carrying the fields
+                if let Some(desc) = self.error_types.get(&name.value) {
+                    let declared = self.structs.get(&name.value).cloned().unwrap_or_default();
+                    check_error_fields(&name.value, &declared, &data)?;
+                    return Ok(RuntimeVal::Error(name.value, desc.clone(), data));
+                }
+                // 3. Return Struct Value
                 Ok(RuntimeVal::Struct(name.value, data))
             }
 
+            Expression::EnumInit(enum_name, _, variant, args) => {
+                let arity = self
+                    .enums
+                    .get(&enum_name.value)
+                    .ok_or_else(|| format!("ERROR: Unknown enum '{}'.", enum_name.value))?
+                    .iter()
+                    .find(|(name, _)| *name == variant.value)
+                    .map(|(_, arity)| *arity)
+                    .ok_or_else(|| {
+                        format!(
+                            "ERROR: '{}' has no variant '{}'.",
+                            enum_name.value, variant.value
+                        )
+                    })?;
+
+                let mut payload = Vec::new();
+                for arg in args.map(|a| a.values).unwrap_or_default() {
+                    payload.push(self.eval_expr(arg)?);
+                }
+                if payload.len() != arity {
+                    return Err(format!(
+                        "ERROR: Variant '{}.{}' expects {} value(s), got {}.",
+                        enum_name.value,
+                        variant.value,
+                        arity,
+                        payload.len()
+                    ));
+                }
+                Ok(RuntimeVal::Enum(enum_name.value, variant.value, payload))
+            }
+
             Expression::FieldAccess(target, _, field) => {
-                let val = self.eval_expr(*target)?;
+                let val = self.eval_checked(*target)?;
 
                 // AUTO-DEREF LOGIC
                 // Check if it's a struct directly OR a pointer to a struct
@@ -72,30 +150,55 @@ impl Interpreter {
                 }
             }
 
-            Expression::Variable(v) => {
-                // Check if this is an error type
-                if let Some(desc) = self.error_types.get(&v.value) {
-                    return Ok(RuntimeVal::Error(v.value.clone(), desc.clone()));
-                }
+            // Upper-case names are error types or constants
+            Expression::ErrorRef(name) => match self.error_types.get(&name.value) {
+                Some(_) if self.structs.contains_key(&name.value) => Err(format!(
+                    "ERROR: Error '{0}' carries fields; create it with {0} {{ ... }}.",
+                    name.value
+                )),
+                Some(desc) => Ok(RuntimeVal::Error(name.value, desc.clone(), HashMap::new())),
+                // A module function sees its module's constants in scope
+                None => self
+                    .consts
+                    .get(&name.value)
+                    .or_else(|| self.env.get(&name.value).map(|v| &v.data))
+                    .cloned()
+                    .ok_or_else(|| format!("ERROR: Unknown error type '{}'.", name.value)),
+            },
+
+            Expression::Lambda(def) => Ok(self.make_closure(def)),
+
+            Expression::Match(_, subject, _, arms, _) => {
+                self.eval_match_value(*subject, arms, None)
+            }
 
+            Expression::Variable(v) => {
                 // Strict Purity: Ban capturing external variables
                 if self.in_pure_mode && !self.pure_scope_params.contains(&v.value) {
                     // (Purity check logic from previous task)
                 }
 
-                // Otherwise look up as regular variable
-                let val = self
-                    .env
-                    .get(&v.value)
-                    .map(|val| val.data.clone())
-                    .ok_or_else(|| format!("ERROR: Variable '{}' not found.", v.value))?;
+                // Otherwise look up as regular variable, then as a named function value
+                let val = match self.env.get(&v.value) {
+                    Some(val) => val.data.clone(),
+                    None if self.consts.contains_key(&v.value) => self.consts[&v.value].clone(),
+                    None => self.function_value(&v.value).ok_or_else(|| {
+                        if self.env.is_out_of_scope(&v.value) {
+                            format!("ERROR: Use of out-of-scope variable '{}'.", v.value)
+                        } else if self.in_pure_mode {
+                            format!(
+                                "ERROR: Variable '{}' not found. Pure closures cannot capture mutable variables.",
+                                v.value
+                            )
+                        } else {
+                            format!("ERROR: Variable '{}' not found.", v.value)
+                        }
+                    })?,
+                };
 
                 // Check for Moved
-                if let RuntimeVal::Moved = val {
-                    return Err(format!(
-                        "Interpreter Error: Variable '{}' was moved and cannot be used.",
-                        v.value
-                    ));
+                if let RuntimeVal::Moved(site) = val {
+                    return Err(self.moved_error(&v.value, site));
                 }
 
                 Ok(val)
@@ -107,14 +210,27 @@ impl Interpreter {
             }
 
             // FIXED: Unwrap StringVal and strip quotes
+            // Plain text plus `{name:spec}` placeholders
             Expression::StringLit(s) => {
-                let content = &s.value[1..s.value.len() - 1];
-                Ok(RuntimeVal::String(content.to_string()))
+                let mut out = String::new();
+                for part in s.parts()? {
+                    match part {
+                        StrPart::Lit(text) => out.push_str(&text),
+                        StrPart::Expr(expr, spec) => {
+                            out.push_str(&self.eval_checked(expr)?.format_with(&spec))
+                        }
+                    }
+                }
+                Ok(RuntimeVal::String(out))
             }
             Expression::BoolLit(b) => match b {
                 grammar::BoolVal::True(_) => Ok(RuntimeVal::Bool(true)),
                 grammar::BoolVal::False(_) => Ok(RuntimeVal::Bool(false)),
             },
+            Expression::NoneLit(_) => Ok(RuntimeVal::Opt(None)),
+            Expression::SomeExpr(_, value) => {
+                Ok(RuntimeVal::Opt(Some(Box::new(self.eval_expr(*value)?))))
+            }
             // 3. Pipe Init
             Expression::PipeInit(_, _) => {
                 let (tx, rx) = mpsc::channel();
@@ -160,242 +276,307 @@ impl Interpreter {
             }
 
             // 2. List Init
-            Expression::ListInit(_, _, _, items, _) => {
+            Expression::ListInit(_, item_type, _, items, _) => {
                 let mut vec = Vec::new();
                 for i in items {
-                    vec.push(self.eval_expr(i)?);
+                    vec.push(self.eval_expecting(i, &item_type)?);
                 }
                 Ok(RuntimeVal::List(vec))
             }
 
+            Expression::TupleLit(list) => {
+                if list.items.len() < 2 {
+                    return Err("ERROR: A tuple needs at least two elements.".to_string());
+                }
+                let mut items = Vec::new();
+                for item in list.items {
+                    items.push(self.eval_expr(item)?);
+                }
+                Ok(RuntimeVal::Tuple(items))
+            }
+
             // 3. Map Init
-            Expression::MapInit(_, _, _, _, pairs, _) => {
+            Expression::MapInit(_, key_type, value_type, _, pairs, _) => {
                 let mut map = HashMap::new();
                 for p in pairs {
-                    let k = self.eval_expr(p.key)?.to_string();
-                    let v = self.eval_expr(p.value)?;
-                    map.insert(k, v);
+                    let k = self.eval_expecting(p.key, &key_type)?;
+                    let v = self.eval_expecting(p.value, &value_type)?;
+                    map.insert(k.to_string(), (k, v));
                 }
                 Ok(RuntimeVal::Map(map))
             }
 
             // 4. AT Command
             Expression::At(col, _, key_expr) => {
-                let collection = self.eval_expr(*col)?;
-                let key = self.eval_expr(*key_expr)?;
+                let collection = self.eval_checked(*col)?;
 
                 match collection {
                     RuntimeVal::List(vec) => {
-                        let idx = key.as_float()? as usize;
-                        vec.get(idx)
+                        let RuntimeVal::Int(idx) =
+                            self.eval_expecting(*key_expr, &KiroType::Int)?
+                        else {
+                            return Err(
+                                "Type Error: List index must be an int. Convert with int(x)."
+                                    .to_string(),
+                            );
+                        };
+                        usize::try_from(idx)
+                            .ok()
+                            .and_then(|i| vec.get(i))
                             .cloned()
-                            .ok_or_else(|| "Index out of bounds".to_string())
+                            .ok_or_else(|| self.raise("IndexOutOfBounds"))
                     }
                     RuntimeVal::Map(map) => {
-                        let k_str = key.to_string();
+                        let k_str = self.eval_expr(*key_expr)?.to_string();
                         map.get(&k_str)
-                            .cloned()
-                            .ok_or_else(|| "Key not found".to_string())
+                            .map(|(_, v)| v.clone())
+                            .ok_or_else(|| self.raise("KeyNotFound"))
                     }
                     _ => Err("Cannot use 'at' on this type".to_string()),
                 }
             }
 
-            // 5. PUSH Command (Interpreter Warning)
-            Expression::Push(col_expr, _, val_expr) => {
-                println!("⚠️ Interpreter: 'push' ignored (compile to Rust for mutation).");
-                let _ = self.eval_expr(*col_expr)?;
-                let _ = self.eval_expr(*val_expr)?;
-                Ok(RuntimeVal::Void)
-            }
+            expr @ (Expression::Push(..)
+            | Expression::Insert(..)
+            | Expression::Remove(..)
+            | Expression::Contains(..)
+            | Expression::Pop(..)
+            | Expression::Clear(..)) => self.eval_collection_op(expr),
             Expression::Range(start, _, end) => {
-                let s = self.eval_expr(*start)?.as_float()? as i64;
-                let e = self.eval_expr(*end)?.as_float()? as i64;
-                Ok(RuntimeVal::Range(s, e))
+                let s = self.eval_expr(*start)?.as_range_bound()?;
+                let e = self.eval_expr(*end)?.as_range_bound()?;
+                Ok(RuntimeVal::Range(s, e, false))
+            }
+            Expression::RangeInclusive(start, _, end) => {
+                let s = self.eval_expr(*start)?.as_range_bound()?;
+                let e = self.eval_expr(*end)?.as_range_bound()?;
+                Ok(RuntimeVal::Range(s, e, true))
             }
             Expression::Add(lhs, _, rhs) => {
-                let l = self.eval_expr(*lhs)?;
-                let r = self.eval_expr(*rhs)?;
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
                 match (l, r) {
                     (RuntimeVal::Float(a), RuntimeVal::Float(b)) => Ok(RuntimeVal::Float(a + b)),
+                    (RuntimeVal::Int(a), RuntimeVal::Int(b)) => checked_int(a.checked_add(b)),
                     (RuntimeVal::String(a), b) => Ok(RuntimeVal::String(format!("{}{}", a, b))),
                     (a, RuntimeVal::String(b)) => Ok(RuntimeVal::String(format!("{}{}", a, b))),
                     _ => Err("Runtime Error: Can only ADD numbers or strings".to_string()),
                 }
             }
-            Expression::Len(_, expr) => match self.eval_expr(*expr)? {
-                RuntimeVal::String(s) => Ok(RuntimeVal::Float(s.len() as f64)),
-                RuntimeVal::List(l) => Ok(RuntimeVal::Float(l.len() as f64)),
-                RuntimeVal::Map(m) => Ok(RuntimeVal::Float(m.len() as f64)),
+            Expression::Len(_, expr) => match self.eval_checked(*expr)? {
+                RuntimeVal::String(s) => Ok(RuntimeVal::Int(s.len() as i64)),
+                RuntimeVal::List(l) => Ok(RuntimeVal::Int(l.len() as i64)),
+                RuntimeVal::Map(m) => Ok(RuntimeVal::Int(m.len() as i64)),
                 _ => Err("Runtime Error: 'len' only supports string, list, map.".to_string()),
             },
             Expression::Sub(lhs, _, rhs) => {
-                let l = self.eval_expr(*lhs)?;
-                let r = self.eval_expr(*rhs)?;
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
                 match (l, r) {
                     (RuntimeVal::Float(a), RuntimeVal::Float(b)) => Ok(RuntimeVal::Float(a - b)),
+                    (RuntimeVal::Int(a), RuntimeVal::Int(b)) => checked_int(a.checked_sub(b)),
                     _ => Err("Runtime Error: Can only SUBTRACT numbers".to_string()),
                 }
             }
             Expression::Mul(lhs, _, rhs) => {
-                let l = self.eval_expr(*lhs)?;
-                let r = self.eval_expr(*rhs)?;
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
                 match (l, r) {
                     (RuntimeVal::Float(a), RuntimeVal::Float(b)) => Ok(RuntimeVal::Float(a * b)),
+                    (RuntimeVal::Int(a), RuntimeVal::Int(b)) => checked_int(a.checked_mul(b)),
                     _ => Err("Runtime Error: Can only MULTIPLY numbers".to_string()),
                 }
             }
+            // Int division truncates toward zero
             Expression::Div(lhs, _, rhs) => {
-                let l = self.eval_expr(*lhs)?;
-                let r = self.eval_expr(*rhs)?;
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
                 match (l, r) {
                     (RuntimeVal::Float(a), RuntimeVal::Float(b)) => Ok(RuntimeVal::Float(a / b)),
+                    (RuntimeVal::Int(_), RuntimeVal::Int(0)) => {
+                        Err("Runtime Error: Division by zero".to_string())
+                    }
+                    (RuntimeVal::Int(a), RuntimeVal::Int(b)) => checked_int(a.checked_div(b)),
                     _ => Err("Runtime Error: Can only DIVIDE numbers".to_string()),
                 }
             }
+            Expression::Mod(lhs, _, rhs) => {
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
+                match (l, r) {
+                    (RuntimeVal::Float(a), RuntimeVal::Float(b)) => Ok(RuntimeVal::Float(a % b)),
+                    (RuntimeVal::Int(_), RuntimeVal::Int(0)) => {
+                        Err("Runtime Error: Division by zero".to_string())
+                    }
+                    (RuntimeVal::Int(a), RuntimeVal::Int(b)) => checked_int(a.checked_rem(b)),
+                    _ => Err("Runtime Error: Can only MOD numbers".to_string()),
+                }
+            }
+            Expression::Neg(_, operand) => match self.eval_checked(*operand)? {
+                RuntimeVal::Float(a) => Ok(RuntimeVal::Float(-a)),
+                RuntimeVal::Int(a) => checked_int(a.checked_neg()),
+                _ => Err("Runtime Error: Can only NEGATE numbers".to_string()),
+            },
+            Expression::Not(_, operand) => {
+                let val = self.eval_checked(*operand)?;
+                Ok(RuntimeVal::Bool(!val.is_truthy()))
+            }
+            // try x: an error leaves the function. The error is parked in `pending_error`
+            // and the statement running this expression returns it.
+            Expression::Try(_, operand) => {
+                if !self.in_failable_fn {
+                    return Err(
+                        "ERROR: 'try' can only be used in a failable function (-> T!).".to_string(),
+                    );
+                }
+                match self.eval_expr(*operand)? {
+                    err @ RuntimeVal::Error(..) => {
+                        self.pending_error = Some(err);
+                        Err("ERROR: 'try' left the function with an error.".to_string())
+                    }
+                    val => Ok(val),
+                }
+            }
+            // Short-circuit: the right side only runs when it decides the result
+            Expression::And(lhs, _, rhs) => {
+                if !self.eval_checked(*lhs)?.is_truthy() {
+                    return Ok(RuntimeVal::Bool(false));
+                }
+                Ok(RuntimeVal::Bool(self.eval_checked(*rhs)?.is_truthy()))
+            }
+            Expression::Or(lhs, _, rhs) => {
+                if self.eval_checked(*lhs)?.is_truthy() {
+                    return Ok(RuntimeVal::Bool(true));
+                }
+                Ok(RuntimeVal::Bool(self.eval_checked(*rhs)?.is_truthy()))
+            }
             Expression::Gt(lhs, _, rhs) => {
-                let val = self.eval_expr(*lhs)? > self.eval_expr(*rhs)?;
-                Ok(RuntimeVal::Bool(val))
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
+                Ok(RuntimeVal::Bool(l > r))
             }
             Expression::Lt(lhs, _, rhs) => {
-                let val = self.eval_expr(*lhs)? < self.eval_expr(*rhs)?;
-                Ok(RuntimeVal::Bool(val))
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
+                Ok(RuntimeVal::Bool(l < r))
             }
             Expression::Eq(lhs, _, rhs) => {
-                let val = self.eval_expr(*lhs)? == self.eval_expr(*rhs)?;
-                Ok(RuntimeVal::Bool(val))
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
+                Ok(RuntimeVal::Bool(l == r))
             }
             Expression::Neq(lhs, _, rhs) => {
-                let val = self.eval_expr(*lhs)? != self.eval_expr(*rhs)?;
-                Ok(RuntimeVal::Bool(val))
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
+                Ok(RuntimeVal::Bool(l != r))
             }
             Expression::Geq(lhs, _, rhs) => {
-                let val = self.eval_expr(*lhs)? >= self.eval_expr(*rhs)?;
-                Ok(RuntimeVal::Bool(val))
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
+                Ok(RuntimeVal::Bool(l >= r))
             }
             Expression::Leq(lhs, _, rhs) => {
-                let val = self.eval_expr(*lhs)? <= self.eval_expr(*rhs)?;
-                Ok(RuntimeVal::Bool(val))
+                let (l, r) = self.eval_operands(*lhs, *rhs)?;
+                Ok(RuntimeVal::Bool(l <= r))
             }
 
             // 1. Handle Standard Calls
-            Expression::Call(func_var, _, args, _) => {
+            Expression::Call(func_var, grammar::ParenList { items: args, .. }) => {
                 // A. Resolve the function
                 // It could be a simple Variable (global function)
                 // OR a FieldAccess (module function)
 
                 // We'll extract the FunctionDef statement
-                let (func_stmt, func_debug_name) = match *func_var {
+                // Module functions run in the module's scope (its constants), not the caller's
+                let (func_stmt, func_debug_name, module_scope) = match *func_var {
+                    // Variables holding function values shadow named functions
+                    Expression::Variable(v) if self.env.contains(&v.value) => {
+                        let callee = self.eval_expr(Expression::Variable(v))?;
+                        return self.call_value(callee, args);
+                    }
+                    // Conversion builtins: int(x), num(x)
+                    Expression::Variable(v)
+                        if (v.value == "int" || v.value == "num")
+                            && !self.functions.contains_key(&v.value) =>
+                    {
+                        let [arg] = <[Expression; 1]>::try_from(args)
+                            .map_err(|_| format!("Function '{}' expects 1 args.", v.value))?;
+                        // Integral literals convert exactly, without a trip through num
+                        if let (true, Some(i)) = (v.value == "int", arg.int_literal()) {
+                            return Ok(RuntimeVal::Int(i));
+                        }
+                        let val = self.eval_expr(arg)?;
+                        return if v.value == "int" {
+                            val.to_int()
+                        } else {
+                            val.to_num()
+                        };
+                    }
                     Expression::Variable(v) => {
                         let f = self.functions.get(&v.value).cloned();
-                        (f, v.value)
+                        (f, v.value, None)
                     }
                     Expression::FieldAccess(target, _, field) => {
                         // Evaluate target to find the Module
-                        let val = self.eval_expr(*target)?;
-                        if let RuntimeVal::Module(_, funcs) = &val {
-                            let f = funcs.get(&field.value).cloned();
-                            (f, format!("{}.{}", val, field.value)) // Note: val display might be <Module>
-                        } else {
-                            return Err("Target of field access is not a module.".to_string());
+                        let val = self.eval_expr((*target).clone())?;
+                        match &val {
+                            RuntimeVal::Module(exports, funcs) => {
+                                let f = funcs.get(&field.value).cloned();
+                                let scope = exports
+                                    .iter()
+                                    .map(|(name, data)| {
+                                        let value = Value {
+                                            data: data.clone(),
+                                            is_mutable: false,
+                                        };
+                                        (name.clone(), value)
+                                    })
+                                    .collect();
+                                (f, format!("{}.{}", val, field.value), Some(scope)) // Note: val display might be <Module>
+                            }
+                            // Method declared in an impl block
+                            RuntimeVal::Struct(name, _)
+                                if self
+                                    .methods
+                                    .get(name)
+                                    .is_some_and(|m| m.contains_key(&field.value)) =>
+                            {
+                                let method = self.methods[name][&field.value].clone();
+                                return self.call_method(*target, val, method, args);
+                            }
+                            // Function stored in a struct field
+                            RuntimeVal::Struct(name, fields) => {
+                                let callee =
+                                    fields.get(&field.value).cloned().ok_or_else(|| {
+                                        format!("Struct '{}' has no field '{}'.", name, field.value)
+                                    })?;
+                                return self.call_value(callee, args);
+                            }
+                            _ => {
+                                return Err("Target of field access is not a module.".to_string());
+                            }
                         }
                     }
-                    _ => return Err("Expected function name or module access".to_string()),
+                    // Any other expression must evaluate to a function value
+                    other => {
+                        let callee = self.eval_expr(other)?;
+                        return self.call_value(callee, args);
+                    }
                 };
 
                 let func_stmt = func_stmt
                     .ok_or_else(|| format!("Undefined function: '{}'", func_debug_name))?;
 
                 if let Statement::FunctionDef(def) = func_stmt {
-                    let params = def.params.clone();
-                    let body = def.body.clone();
-                    let pure_kw = def.pure_kw;
                     // C. Purity Check (The "Sandbox")
-                    if pure_kw.is_some() {
-                        // Check Argument Safety (Must be Immutable)
-                        for arg_expr in &args {
-                            let mut current = arg_expr;
-                            // Unwrap FieldAccess to find root
-                            while let Expression::FieldAccess(target, _, _) = current {
-                                current = target;
-                            }
-
-                            if let Expression::Variable(v) = current {
-                                if let Some(entry) = self.env.get(&v.value) {
-                                    if entry.is_mutable {
-                                        return Err(format!(
-                                            "Pure Function Error: Argument '{}' is mutable. Pure functions only accept immutable values.",
-                                            v.value
-                                        ));
-                                    }
-                                }
-                            }
-                        }
+                    if def.pure_kw.is_some() {
+                        self.check_pure_args(&args)?;
                     }
 
                     // D. Evaluate Arguments *in the current scope*
-                    let mut arg_values = Vec::new();
-                    for arg in args {
-                        arg_values.push(self.eval_expr(arg)?);
-                    }
-
-                    // E. Create the "Stack Frame" (Local Scope)
-                    // We save the old environment to restore it later
-                    let old_env = self.env.clone();
-
-                    // We create a fresh environment.
-                    // Note: For true "Lexical Scoping", we should copy global variables in,
-                    // but for "Pure" functions, we might want an empty map!
-                    // For now, let's clone the global scope so we can read globals.
-                    let mut fn_env = self.env.clone();
-
-                    // F. Bind Arguments to Parameters
-                    if params.len() != arg_values.len() {
-                        return Err(format!(
-                            "Function '{}' expects {} args, got {}.",
-                            func_debug_name,
-                            params.len(),
-                            arg_values.len()
-                        ));
-                    }
-
-                    for (i, param) in params.into_iter().enumerate() {
-                        fn_env.insert(
-                            param.name,
-                            super::values::Value {
-                                data: arg_values[i].clone(),
-                                is_mutable: if pure_kw.is_some() { false } else { true },
-                            },
-                        );
-                    }
-
-                    // H. Run the Body
-                    let old_mode = self.in_pure_mode;
-                    if pure_kw.is_some() {
-                        self.in_pure_mode = true;
-                        // Args checked above before move
-                    }
-
-                    // G. Context Switch!
-                    self.env = fn_env;
-
-                    let result_sig = self.execute_block(body);
-
-                    // I. Restore the Old World
-                    self.env = old_env;
-                    self.in_pure_mode = old_mode;
-
-                    let result_sig = result_sig?; // Propagate error now
-
-                    // Return the result of the function
-                    match result_sig {
-                        super::StatementResult::Normal(v) => Ok(v),
-                        super::StatementResult::Return(v) => Ok(v),
-                        super::StatementResult::Break | super::StatementResult::Continue => {
-                            Err("Error: 'break' or 'continue' leaked from function body."
-                                .to_string())
-                        }
-                    }
+                    let arg_values = self.eval_call_args(&def.params, args)?;
+
+                    // E. Named functions run on a copy of the caller's scope so they can read globals
+                    let func = FunctionVal {
+                        name: func_debug_name,
+                        params: def.params,
+                        body: def.body,
+                        return_type: def.return_type,
+                        is_pure: def.pure_kw.is_some(),
+                        can_error: def.can_error.is_some(),
+                        captured: module_scope,
+                    };
+                    self.call_function(&func, arg_values)
                 } else if let Statement::RustFnDecl(def) = func_stmt {
                     let params = &def.params;
                     let return_type = &def.return_type;
@@ -422,6 +603,7 @@ impl Interpreter {
                     // 2. Return Mock Value based on return_type
                     match return_type {
                         crate::grammar::grammar::KiroType::Num => Ok(RuntimeVal::Float(0.0)),
+                        crate::grammar::grammar::KiroType::Int => Ok(RuntimeVal::Int(0)),
                         crate::grammar::grammar::KiroType::Str => {
                             Ok(RuntimeVal::String("MOCK_STRING".to_string()))
                         }
@@ -455,3 +637,110 @@ impl Interpreter {
         }
     }
 }
+
+impl Interpreter {
+    // Evaluates a value headed for a declared type (parameter, field, element, return value,
+    // reassignment). Integral literals become ints there; other int/num mismatches are errors.
+    pub fn eval_expecting(
+        &mut self,
+        expr: Expression,
+        expected: &KiroType,
+    ) -> Result<RuntimeVal, String> {
+        if let Expression::Match(_, subject, _, arms, _) = expr {
+            return self.eval_match_value(*subject, arms, Some(expected));
+        }
+        if let (KiroType::Int, Some(v)) = (expected, expr.int_literal()) {
+            return Ok(RuntimeVal::Int(v));
+        }
+        if let KiroType::Opt(_, inner) = expected {
+            return self.eval_optional(expr, inner);
+        }
+        // Tuple literals are checked element by element
+        if let (Some(types), Expression::TupleLit(list)) = (expected.tuple_items(), &expr)
+            && types.len() == list.items.len()
+        {
+            let Expression::TupleLit(list) = expr else {
+                unreachable!()
+            };
+            let mut items = Vec::new();
+            for (item, t) in list.items.into_iter().zip(&types) {
+                items.push(self.eval_expecting(item, t)?);
+            }
+            return Ok(RuntimeVal::Tuple(items));
+        }
+        // Generic parameters accept any value, optionals included
+        let val = match expected {
+            KiroType::Custom(_) => self.eval_expr(expr)?,
+            _ => self.eval_checked(expr)?,
+        };
+        check_numeric(expected, &val)?;
+        Ok(val)
+    }
+
+    // A match used as a value
+    fn eval_match_value(
+        &mut self,
+        subject: Expression,
+        arms: Vec<grammar::MatchArm>,
+        expected: Option<&KiroType>,
+    ) -> Result<RuntimeVal, String> {
+        match self.eval_match(subject, arms, expected)? {
+            StatementResult::Normal(val) => Ok(val),
+            _ => Err(
+                "Match Error: 'return', 'break' and 'continue' are only allowed in a match used as a statement."
+                    .to_string(),
+            ),
+        }
+    }
+
+    // A value used directly: operands, conditions, field access, printing
+    pub fn eval_checked(&mut self, expr: Expression) -> Result<RuntimeVal, String> {
+        self.eval_expr(expr)?.checked()
+    }
+
+    // A value headed for an `opt T` place: `none`, `some x` or another optional
+    fn eval_optional(&mut self, expr: Expression, inner: &KiroType) -> Result<RuntimeVal, String> {
+        match expr {
+            Expression::NoneLit(_) => Ok(RuntimeVal::Opt(None)),
+            Expression::SomeExpr(_, value) => Ok(RuntimeVal::Opt(Some(Box::new(
+                self.eval_expecting(*value, inner)?,
+            )))),
+            other => match self.eval_expr(other)? {
+                RuntimeVal::Opt(value) => {
+                    if let Some(v) = &value {
+                        check_numeric(inner, v)?;
+                    }
+                    Ok(RuntimeVal::Opt(value))
+                }
+                _ => Err(
+                    "Type Error: Expected an optional value. Wrap it with `some x`.".to_string(),
+                ),
+            },
+        }
+    }
+
+    // Operands of a binary operator. An integral literal next to an int is an int;
+    // any other mix of int and num is an error.
+    fn eval_operands(
+        &mut self,
+        lhs: Expression,
+        rhs: Expression,
+    ) -> Result<(RuntimeVal, RuntimeVal), String> {
+        let (lhs_lit, rhs_lit) = (lhs.int_literal(), rhs.int_literal());
+        let mut l = self.eval_checked(lhs)?;
+        let mut r = self.eval_checked(rhs)?;
+        if let (Some(v), RuntimeVal::Int(_)) = (lhs_lit, &r) {
+            l = RuntimeVal::Int(v);
+        }
+        if let (Some(v), RuntimeVal::Int(_)) = (rhs_lit, &l) {
+            r = RuntimeVal::Int(v);
+        }
+        match (&l, &r) {
+            (RuntimeVal::Int(_), RuntimeVal::Float(_))
+            | (RuntimeVal::Float(_), RuntimeVal::Int(_)) => Err(
+                "Type Error: Cannot mix int and num. Convert with int(x) or num(x).".to_string(),
+            ),
+            _ => Ok((l, r)),
+        }
+    }
+}
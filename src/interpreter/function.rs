@@ -0,0 +1,283 @@
+use super::Interpreter;
+use super::StatementResult;
+use super::values::{FunctionVal, RuntimeVal, Value};
+use crate::grammar::grammar::{self, Expression, KiroType, Statement};
+use crate::scope::Scopes;
+use std::sync::Arc;
+
+impl Interpreter {
+    // Closure literal: snapshots the environment. Closures created in pure mode stay pure
+    // and only see immutable bindings.
+    pub fn make_closure(&self, def: grammar::LambdaDef) -> RuntimeVal {
+        let captured = self
+            .env
+            .visible()
+            .into_iter()
+            .filter(|(_, v)| !self.in_pure_mode || !v.is_mutable)
+            .map(|(k, v)| (k, v.clone()))
+            .collect();
+        RuntimeVal::Function(Arc::new(FunctionVal {
+            name: "<closure>".to_string(),
+            params: def.params,
+            body: def.body,
+            return_type: def.return_type,
+            is_pure: self.in_pure_mode,
+            can_error: false,
+            captured: Some(captured),
+        }))
+    }
+
+    // Named function used as a value (e.g. passed as a callback)
+    pub fn function_value(&self, name: &str) -> Option<RuntimeVal> {
+        match self.functions.get(name)? {
+            Statement::FunctionDef(def) => Some(RuntimeVal::Function(Arc::new(FunctionVal {
+                name: name.to_string(),
+                params: def.params.clone(),
+                body: def.body.clone(),
+                return_type: def.return_type.clone(),
+                is_pure: def.pure_kw.is_some(),
+                can_error: def.can_error.is_some(),
+                captured: None,
+            }))),
+            _ => None,
+        }
+    }
+
+    // Evaluates call arguments in the current scope.
+    // A closure literal passed to a `pure fn(...)` parameter is created pure.
+    pub fn eval_call_args(
+        &mut self,
+        params: &[grammar::FuncParam],
+        args: Vec<Expression>,
+    ) -> Result<Vec<RuntimeVal>, String> {
+        let mut values = Vec::new();
+        for (i, arg) in args.into_iter().enumerate() {
+            let wants_pure = matches!(
+                params.get(i).map(|p| &p.command_type),
+                Some(KiroType::Func(Some(_), ..))
+            );
+            let val = if wants_pure && matches!(arg, Expression::Lambda(_)) {
+                let old_mode = self.in_pure_mode;
+                self.in_pure_mode = true;
+                let val = self.eval_expr(arg);
+                self.in_pure_mode = old_mode;
+                val?
+            } else if let Some(param) = params.get(i) {
+                self.eval_expecting(arg, &param.command_type)?
+            } else {
+                self.eval_expr(arg)?
+            };
+
+            if wants_pure
+                && let RuntimeVal::Function(f) = &val
+                && !f.is_pure
+            {
+                return Err(format!(
+                    "Pure Function Error: Parameter '{}' expects a pure function, got '{}'.",
+                    params[i].name, f.name
+                ));
+            }
+            if let Some(param) = params.get(i) {
+                self.check_interface_arg(param, &val)?;
+            }
+            values.push(val);
+        }
+        Ok(values)
+    }
+
+    // Pure functions only accept immutable values
+    pub fn check_pure_args(&self, args: &[Expression]) -> Result<(), String> {
+        for arg_expr in args {
+            let mut current = arg_expr;
+            // Unwrap FieldAccess to find root
+            while let Expression::FieldAccess(target, _, _) = current {
+                current = target;
+            }
+
+            if let Expression::Variable(v) = current
+                && let Some(entry) = self.env.get(&v.value)
+                && entry.is_mutable
+            {
+                return Err(format!(
+                    "Pure Function Error: Argument '{}' is mutable. Pure functions only accept immutable values.",
+                    v.value
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Runs a function body in a fresh frame built from the captured (or caller's) environment
+    pub fn call_function(
+        &mut self,
+        func: &FunctionVal,
+        arg_values: Vec<RuntimeVal>,
+    ) -> Result<RuntimeVal, String> {
+        Ok(self.invoke(func, None, arg_values)?.0)
+    }
+
+    // Method call `target.name(args)`. A `var self` receiver is written back to `target`.
+    pub fn call_method(
+        &mut self,
+        target: Expression,
+        receiver: RuntimeVal,
+        method: grammar::MethodDef,
+        args: Vec<Expression>,
+    ) -> Result<RuntimeVal, String> {
+        let RuntimeVal::Struct(struct_name, _) = &receiver else {
+            return Err("Methods can only be called on structs.".to_string());
+        };
+        let name = format!("{}.{}", struct_name, method.name);
+        let is_pure = method.pure_kw.is_some();
+        let mutating = method.var_kw.is_some();
+
+        if self.in_pure_mode && !is_pure {
+            return Err(format!(
+                "Pure Function Error: Cannot call impure method '{}' inside a pure function.",
+                name
+            ));
+        }
+        if is_pure {
+            self.check_pure_args(&args)?;
+        }
+        if mutating {
+            let mut root = &target;
+            while let Expression::FieldAccess(inner, _, _) = root {
+                root = inner;
+            }
+            let Expression::Variable(v) = root else {
+                return Err(format!(
+                    "ERROR: Mutating method '{}' needs a variable receiver.",
+                    name
+                ));
+            };
+            if !self.env.get(&v.value).is_some_and(|e| e.is_mutable) {
+                return Err(format!(
+                    "ERROR: Cannot call mutating method '{}' on immutable '{}'.",
+                    name, v.value
+                ));
+            }
+        }
+
+        let params: Vec<grammar::FuncParam> = method.params.into_iter().map(|p| p.param).collect();
+        let arg_values = self.eval_call_args(&params, args)?;
+        let func = FunctionVal {
+            name,
+            params,
+            body: method.body,
+            return_type: method.return_type,
+            is_pure,
+            can_error: method.can_error.is_some(),
+            captured: None,
+        };
+        let self_val = Value {
+            data: receiver,
+            is_mutable: mutating,
+        };
+        let (ret, new_self) = self.invoke(&func, Some(self_val), arg_values)?;
+
+        if mutating && let Some(new_self) = new_self {
+            match target {
+                Expression::Variable(v) => {
+                    if let Some(entry) = self.env.get_mut(&v.value) {
+                        entry.data = new_self;
+                    }
+                }
+                Expression::FieldAccess(inner, _, field) => {
+                    self.assign_field(*inner, field.value, new_self)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(ret)
+    }
+
+    // Shared call path. `receiver` is bound as `self`; its final value is returned alongside
+    // the result so mutating methods can write it back.
+    fn invoke(
+        &mut self,
+        func: &FunctionVal,
+        receiver: Option<Value>,
+        arg_values: Vec<RuntimeVal>,
+    ) -> Result<(RuntimeVal, Option<RuntimeVal>), String> {
+        if func.params.len() != arg_values.len() {
+            return Err(format!(
+                "Function '{}' expects {} args, got {}.",
+                func.name,
+                func.params.len(),
+                arg_values.len()
+            ));
+        }
+
+        // Create the "Stack Frame" (Local Scope)
+        let mut fn_env = match &func.captured {
+            Some(captured) => Scopes::from_frame(captured.clone()),
+            None => self.env.clone(),
+        };
+        fn_env.push();
+        for (param, data) in func.params.iter().zip(arg_values) {
+            fn_env.declare(
+                param.name.clone(),
+                Value {
+                    data,
+                    is_mutable: !func.is_pure,
+                },
+            );
+        }
+        let has_receiver = receiver.is_some();
+        if let Some(receiver) = receiver {
+            fn_env.declare("self".to_string(), receiver);
+        }
+
+        let old_env = std::mem::replace(&mut self.env, fn_env);
+        let old_return_type = std::mem::replace(&mut self.return_type, func.return_type.clone());
+        let old_loop_depths = std::mem::take(&mut self.loop_depths);
+        let old_loop_labels = std::mem::take(&mut self.loop_labels);
+        let old_failable = std::mem::replace(&mut self.in_failable_fn, func.can_error);
+        let old_mode = self.in_pure_mode;
+        if func.is_pure {
+            self.in_pure_mode = true;
+        }
+
+        let result_sig = self.execute_block(func.body.clone());
+
+        // Restore the Old World
+        let fn_env = std::mem::replace(&mut self.env, old_env);
+        self.return_type = old_return_type;
+        self.loop_depths = old_loop_depths;
+        self.loop_labels = old_loop_labels;
+        self.in_failable_fn = old_failable;
+        self.in_pure_mode = old_mode;
+        let new_self = if has_receiver {
+            fn_env.get("self").map(|v| v.data.clone())
+        } else {
+            None
+        };
+
+        match result_sig? {
+            StatementResult::Normal(v) | StatementResult::Return(v) => Ok((v, new_self)),
+            StatementResult::Break(_) | StatementResult::Continue(_) => {
+                Err("Error: 'break' or 'continue' leaked from function body.".to_string())
+            }
+        }
+    }
+
+    // Call through a function value (closure, callback variable, struct field)
+    pub fn call_value(
+        &mut self,
+        callee: RuntimeVal,
+        args: Vec<Expression>,
+    ) -> Result<RuntimeVal, String> {
+        let RuntimeVal::Function(func) = callee else {
+            return Err(format!("'{}' is not a function.", callee));
+        };
+        if self.in_pure_mode && !func.is_pure {
+            return Err(format!(
+                "Pure Function Error: Cannot call impure function '{}' inside a pure function.",
+                func.name
+            ));
+        }
+        let arg_values = self.eval_call_args(&func.params, args)?;
+        self.call_function(&func, arg_values)
+    }
+}
@@ -0,0 +1,94 @@
+use super::Interpreter;
+use super::values::RuntimeVal;
+use crate::grammar::grammar::{self, KiroType};
+
+impl Interpreter {
+    // Registers the methods of an impl block. "impl Shape for Circle" must provide exactly
+    // the methods declared by the interface.
+    pub fn register_impl(&mut self, block: grammar::ImplBlock) -> Result<(), String> {
+        let struct_name = block.struct_name().to_string();
+
+        if let Some(interface) = block.interface() {
+            let sigs = self
+                .interfaces
+                .get(interface)
+                .ok_or_else(|| format!("ERROR: Unknown interface '{}'.", interface))?;
+
+            for sig in sigs {
+                let def = block
+                    .methods
+                    .iter()
+                    .find(|m| m.name == sig.name)
+                    .ok_or_else(|| {
+                        format!(
+                            "ERROR: '{}' does not implement '{}': missing method '{}'.",
+                            struct_name, interface, sig.name
+                        )
+                    })?;
+                if !sig.matches(def) {
+                    return Err(format!(
+                        "ERROR: Method '{}.{}' does not match its declaration in interface '{}'.",
+                        struct_name, def.name, interface
+                    ));
+                }
+            }
+            if let Some(extra) = block
+                .methods
+                .iter()
+                .find(|m| !sigs.iter().any(|s| s.name == m.name))
+            {
+                return Err(format!(
+                    "ERROR: Method '{}' is not declared by interface '{}'.",
+                    extra.name, interface
+                ));
+            }
+            self.implementations
+                .insert((struct_name.clone(), interface.to_string()));
+        }
+
+        let methods = self.methods.entry(struct_name.clone()).or_default();
+        for method in block.methods {
+            if method.pure_kw.is_some() && method.var_kw.is_some() {
+                return Err(format!(
+                    "Pure Function Error: Pure method '{}.{}' cannot take 'var self'.",
+                    struct_name, method.name
+                ));
+            }
+            if methods.contains_key(&method.name) {
+                return Err(format!(
+                    "ERROR: Duplicate method '{}' on '{}'.",
+                    method.name, struct_name
+                ));
+            }
+            methods.insert(method.name.clone(), method);
+        }
+        Ok(())
+    }
+
+    // Interface-typed parameters only accept structs that implement the interface
+    pub fn check_interface_arg(
+        &self,
+        param: &grammar::FuncParam,
+        val: &RuntimeVal,
+    ) -> Result<(), String> {
+        let KiroType::Custom(type_name) = &param.command_type else {
+            return Ok(());
+        };
+        if !self.interfaces.contains_key(&type_name.value) {
+            return Ok(());
+        }
+        match val {
+            RuntimeVal::Struct(name, _)
+                if self
+                    .implementations
+                    .contains(&(name.clone(), type_name.value.clone())) =>
+            {
+                Ok(())
+            }
+            _ => Err(format!(
+                "Type Error: Parameter '{}' expects an implementation of '{}', got '{}'.",
+                param.name, type_name.value, val
+            )),
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use crate::grammar::{self, Statement};
 use std::collections::HashMap;
 
+pub mod collection;
 pub mod expression;
+pub mod function;
+pub mod interface;
+pub mod pattern;
 pub mod statement;
 pub mod values;
 
+use crate::scope::Scopes;
 use values::{RuntimeVal, Value};
 
 use std::collections::HashSet;
@@ -13,41 +18,80 @@ use std::collections::HashSet;
 pub enum StatementResult {
     Normal(RuntimeVal),
     Return(RuntimeVal),
-    Break,
-    Continue,
+    Break(Option<String>), // Label of the loop to leave, if not the innermost
+    Continue(Option<String>),
 }
 
 pub struct Interpreter {
-    pub env: HashMap<String, Value>,
+    pub env: Scopes<Value>,
     pub functions: HashMap<String, Statement>,
     pub in_pure_mode: bool,
     pub error_types: HashMap<String, String>, // name -> description
-    pub pure_scope_params: HashSet<String>,   // Allowed params in pure scope
+    pub enums: HashMap<String, Vec<(String, usize)>>, // name -> (variant, payload arity)
+    pub structs: HashMap<String, Vec<grammar::FieldDef>>, // name -> declared fields
+    pub methods: HashMap<String, HashMap<String, grammar::MethodDef>>, // struct -> method name -> def
+    pub interfaces: HashMap<String, Vec<grammar::MethodSig>>, // interface -> required methods
+    pub implementations: HashSet<(String, String)>,           // (struct, interface)
+    pub pure_scope_params: HashSet<String>,                   // Allowed params in pure scope
+    pub return_type: Option<grammar::KiroType>, // Declared return type of the running function
+    pub consts: HashMap<String, RuntimeVal>,    // Constants, exported when imported as a module
+    pub loop_depths: Vec<usize>, // Scope depth at the start of each running loop body
+    pub loop_labels: Vec<String>, // Labels of the running loops, innermost last
+    pub deferred: Vec<Vec<(grammar::Block, Scopes<Value>)>>, // Per running block: body, env copy
+    pub in_failable_fn: bool,    // Running a `-> T!` function, where `try` may return errors
+    pub pending_error: Option<RuntimeVal>, // Error a `try` is returning from the function
+    pub current_stmt: String,    // Statement being executed, for move diagnostics
+    pub current_span: (usize, usize), // Its span, where a `move` in it is reported
+    pub error_span: Option<(usize, usize)>, // Innermost statement that failed
+    pub error_related: Option<crate::diagnostics::Related>, // Second place the error points at
+    pub error_file: Option<(String, String)>, // Imported file it is in: name, source
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         Self {
-            env: HashMap::new(),
+            env: Scopes::new(),
             functions: HashMap::new(),
             in_pure_mode: false,
-            error_types: HashMap::new(),
+            error_types: grammar::BUILTIN_ERRORS
+                .iter()
+                .map(|(name, desc)| (name.to_string(), desc.to_string()))
+                .collect(),
+            enums: HashMap::new(),
+            structs: HashMap::new(),
+            methods: HashMap::new(),
+            interfaces: HashMap::new(),
+            implementations: HashSet::new(),
             pure_scope_params: HashSet::new(),
+            return_type: None,
+            consts: HashMap::new(),
+            loop_depths: Vec::new(),
+            loop_labels: Vec::new(),
+            deferred: Vec::new(),
+            in_failable_fn: false,
+            pending_error: None,
+            current_stmt: String::new(),
+            current_span: (0, 0),
+            error_span: None,
+            error_related: None,
+            error_file: None,
         }
     }
     pub fn run(&mut self, program: grammar::Program) -> Result<(), String> {
+        // Top-level defers run when the script ends
+        self.deferred.push(Vec::new());
         for statement in program.statements {
-            let res = self.execute_statement(statement)?;
+            let res = self.execute_spanned(statement)?;
             // If top-level statement returns Return/Break/Continue, that's an error
             // (or we could just exit logic, but standard is scripts only return via exit)
             match res {
                 StatementResult::Normal(_) => {}
-                StatementResult::Return(_) => return Ok(()), // Allow script to return
-                StatementResult::Break | StatementResult::Continue => {
+                StatementResult::Return(_) => break, // Allow script to return
+                StatementResult::Break(_) | StatementResult::Continue(_) => {
                     return Err("Cannot break/continue outside of loop".to_string());
                 }
             }
         }
-        Ok(())
+        self.run_deferred()
     }
 }
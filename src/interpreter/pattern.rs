@@ -0,0 +1,143 @@
+use super::Interpreter;
+use super::StatementResult;
+use super::values::{RuntimeVal, Value};
+use crate::exhaustive::check_match_exhaustive;
+use crate::grammar::grammar::{self, ArmBody, Expression, KiroType, Pattern};
+
+fn parse_number(num: &grammar::NumberVal) -> Result<f64, String> {
+    num.value
+        .parse()
+        .map_err(|_| format!("Invalid number '{}'", num.value))
+}
+
+// Returns the bindings introduced by the pattern, or None if it does not match
+pub fn match_pattern(
+    pattern: &Pattern,
+    val: &RuntimeVal,
+) -> Result<Option<Vec<(String, RuntimeVal)>>, String> {
+    match pattern {
+        Pattern::Wildcard(_) => Ok(Some(vec![])),
+        Pattern::Binding(name) => Ok(Some(vec![(name.value.clone(), val.clone())])),
+        Pattern::Variant(enum_name, _, variant, bindings) => match val {
+            RuntimeVal::Enum(e, v, payload) if *e == enum_name.value && *v == variant.value => {
+                let names = bindings
+                    .as_ref()
+                    .map(|b| b.names.clone())
+                    .unwrap_or_default();
+                Ok(Some(
+                    names
+                        .into_iter()
+                        .zip(payload.iter().cloned())
+                        .filter(|(name, _)| name.value != "_")
+                        .map(|(name, v)| (name.value, v))
+                        .collect(),
+                ))
+            }
+            _ => Ok(None),
+        },
+        Pattern::Struct(struct_name, _, fields, _) => match val {
+            RuntimeVal::Struct(name, data) if *name == struct_name.value => {
+                let mut bindings = Vec::new();
+                for field in fields {
+                    let v = data.get(&field.value).ok_or_else(|| {
+                        format!(
+                            "Match Error: Struct '{}' has no field '{}'.",
+                            name, field.value
+                        )
+                    })?;
+                    bindings.push((field.value.clone(), v.clone()));
+                }
+                Ok(Some(bindings))
+            }
+            _ => Ok(None),
+        },
+        Pattern::Range(start, _, end) => {
+            let (start, end) = (parse_number(start)?, parse_number(end)?);
+            match val {
+                RuntimeVal::Float(f) if *f >= start && *f < end => Ok(Some(vec![])),
+                RuntimeVal::Int(i) if (*i as f64) >= start && (*i as f64) < end => Ok(Some(vec![])),
+                _ => Ok(None),
+            }
+        }
+        Pattern::Number(num) => {
+            let n = parse_number(num)?;
+            match val {
+                RuntimeVal::Int(i) => Ok((*i as f64 == n).then(Vec::new)),
+                _ => Ok((*val == RuntimeVal::Float(n)).then(Vec::new)),
+            }
+        }
+        Pattern::StringLit(s) => Ok((*val == RuntimeVal::String(s.text()?)).then(Vec::new)),
+        Pattern::BoolLit(b) => {
+            let expected = matches!(b, grammar::BoolVal::True(_));
+            Ok((*val == RuntimeVal::Bool(expected)).then(Vec::new))
+        }
+    }
+}
+
+impl Interpreter {
+    // Runs the first arm whose pattern and guard match.
+    // Block arms may return/break; callers using match as a value reject those signals.
+    // Arm values headed for a declared type are evaluated against it.
+    pub fn eval_match(
+        &mut self,
+        subject: Expression,
+        arms: Vec<grammar::MatchArm>,
+        expected: Option<&KiroType>,
+    ) -> Result<StatementResult, String> {
+        let coverage: Vec<(&Pattern, bool)> = arms
+            .iter()
+            .map(|arm| (&arm.pattern, arm.guard.is_some()))
+            .collect();
+        let val = self.eval_expr(subject)?;
+        check_match_exhaustive(
+            &self.enums,
+            &self.structs,
+            val.match_type().as_ref(),
+            &coverage,
+        )
+        .map_err(|e| format!("Match Error: {}", e.message))?;
+
+        for arm in arms {
+            let Some(bindings) = match_pattern(&arm.pattern, &val)? else {
+                continue;
+            };
+            if let Some(guard) = arm.guard {
+                let passed = self.with_bindings(bindings.clone(), |i| {
+                    Ok(i.eval_expr(guard.condition)?.is_truthy())
+                })?;
+                if !passed {
+                    continue;
+                }
+            }
+            return self.with_bindings(bindings, |i| match arm.body {
+                ArmBody::Block(block) => i.execute_block(block),
+                ArmBody::Value(expr) => Ok(StatementResult::Normal(match expected {
+                    Some(t) => i.eval_expecting(expr, t)?,
+                    None => i.eval_expr(expr)?,
+                })),
+            });
+        }
+        Err(format!("Match Error: No arm matched value {}", val))
+    }
+
+    // Runs `f` in a new scope holding the (immutable) bindings; they are dropped after
+    pub fn with_bindings<T>(
+        &mut self,
+        bindings: Vec<(String, RuntimeVal)>,
+        f: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.env.push();
+        for (name, data) in bindings {
+            self.env.declare(
+                name,
+                Value {
+                    data,
+                    is_mutable: false,
+                },
+            );
+        }
+        let result = f(self);
+        self.env.pop();
+        result
+    }
+}
@@ -1,7 +1,54 @@
 use super::Interpreter;
 use super::StatementResult; // New Enum
-use super::values::{RuntimeVal, Value};
+use super::values::{RuntimeVal, Value, check_numeric, compare_keys, destructure};
 use crate::grammar::grammar::{self, Statement};
+use rust_sitter::Spanned;
+
+// How a loop goes on after one pass through its body. Returns, and signals labeled
+// for an outer loop, leave this loop and travel up.
+enum Pass {
+    Next,
+    Stop,
+    Leave(StatementResult),
+}
+
+fn after_pass(res: StatementResult, label: &Option<String>) -> Pass {
+    let targets_this = |l: &Option<String>| l.is_none() || l == label;
+    match res {
+        StatementResult::Normal(_) => Pass::Next,
+        StatementResult::Continue(l) if targets_this(&l) => Pass::Next,
+        StatementResult::Break(l) if targets_this(&l) => Pass::Stop,
+        other => Pass::Leave(other),
+    }
+}
+
+// `per n` must be a nonzero int; only ranges count down with a negative step
+fn loop_step(step: i64, is_range: bool) -> Result<i64, String> {
+    match step {
+        0 => Err("Loop Error: Step cannot be 0.".to_string()),
+        n if n < 0 && !is_range => {
+            Err("Loop Error: Only ranges can count down with a negative step.".to_string())
+        }
+        n => Ok(n),
+    }
+}
+
+// Values from start toward end: up for a positive step, down for a negative one
+fn range_values(start: i64, end: i64, inclusive: bool, step: i64) -> Vec<RuntimeVal> {
+    let in_range = |v: i64| match (step > 0, inclusive) {
+        (true, false) => v < end,
+        (true, true) => v <= end,
+        (false, false) => v > end,
+        (false, true) => v >= end,
+    };
+    let mut values = Vec::new();
+    let mut current = Some(start);
+    while let Some(v) = current.filter(|v| in_range(*v)) {
+        values.push(RuntimeVal::Int(v));
+        current = v.checked_add(step);
+    }
+    values
+}
 
 // Helper for Deep Updates
 // Path is reversed: [z, y] means x.y.z
@@ -36,22 +83,152 @@ fn update_nested_field(
 }
 
 impl Interpreter {
+    // Deep update of x.y.z = value; the root variable must be mutable
+    pub fn assign_field(
+        &mut self,
+        target: grammar::Expression,
+        field: String,
+        new_val: RuntimeVal,
+    ) -> Result<(), String> {
+        let mut path = vec![field];
+        let mut current = target;
+
+        // Unwind the dot chain: x.y.z -> path=[z, y], root=x
+        while let grammar::Expression::FieldAccess(inner_target, _, inner_field) = current {
+            path.push(inner_field.value);
+            current = *inner_target;
+        }
+
+        // Now 'current' should be the variable (x)
+        let root_name = match current {
+            grammar::Expression::Variable(v) => v.value,
+            _ => {
+                return Err("Assignment target must start with a variable.".to_string());
+            }
+        };
+
+        // Get Mutable Root
+        let entry = self
+            .env
+            .get_mut(&root_name)
+            .ok_or_else(|| format!("Variable '{}' not found", root_name))?;
+
+        if !entry.is_mutable {
+            return Err(format!("Variable '{}' is immutable.", root_name));
+        }
+        if let RuntimeVal::Moved(site) = entry.data {
+            return Err(self.moved_error(&root_name, site));
+        }
+
+        // Drill down and Update
+        update_nested_field(&mut entry.data, path, new_val)
+    }
+
+    // A failing statement records its span, unless a statement inside it already did
+    pub fn execute_spanned(
+        &mut self,
+        statement: Spanned<Statement>,
+    ) -> Result<StatementResult, String> {
+        let span = statement.span;
+        let outer_span = std::mem::replace(&mut self.current_span, span);
+        let result = self.execute_statement(statement.value).inspect_err(|_| {
+            self.error_span.get_or_insert(span);
+        });
+        self.current_span = outer_span;
+        result
+    }
+
+    // Using a variable whose value was moved out; the error also points at the move
+    pub fn moved_error(&mut self, name: &str, site: (usize, usize)) -> String {
+        self.error_related = Some(crate::diagnostics::Related {
+            span: site,
+            focus: format!("move {}", name),
+            label: "moved here".to_string(),
+        });
+        format!(
+            "Interpreter Error: Variable '{}' was moved and cannot be used.",
+            name
+        )
+    }
+
     pub fn execute_statement(&mut self, statement: Statement) -> Result<StatementResult, String> {
+        let outer_stmt = std::mem::replace(&mut self.current_stmt, statement.describe());
+        let result = match self.execute_statement_kind(statement) {
+            Err(_) if self.pending_error.is_some() => {
+                Ok(StatementResult::Return(self.pending_error.take().unwrap()))
+            }
+            result => result,
+        };
+        self.current_stmt = outer_stmt;
+        result
+    }
+
+    fn execute_statement_kind(&mut self, statement: Statement) -> Result<StatementResult, String> {
         match statement {
-            // Error definitions register the type and description
+            // Error definitions register the type and description. Payload fields are
+            // typed like struct fields and must be able to cross the host boundary.
             Statement::ErrorDef {
-                name, description, ..
+                name,
+                description,
+                fields,
+                ..
             } => {
-                let desc = description.map(|d| d.value.value).unwrap_or_default();
-                self.error_types.insert(name.clone(), desc);
+                let desc = match description {
+                    Some(d) => d.value.text()?,
+                    None => String::new(),
+                };
+                let fields = fields.map(|f| f.fields).unwrap_or_default();
+                if let Some(f) = fields.iter().find(|f| !f.field_type.is_payload()) {
+                    return Err(format!(
+                        "ERROR: Error field '{}' must be num, int, str or bool.",
+                        f.name.value
+                    ));
+                }
+                if !fields.is_empty() {
+                    self.structs.insert(name.clone(), fields);
+                }
+                self.error_types.insert(name, desc);
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
+            // Struct definitions only record field types (for int literals in initializers)
+            Statement::StructDef(def) => {
+                self.structs.insert(def.name.value, def.fields);
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
+            Statement::ImplBlock(block) => {
+                self.register_impl(block)?;
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
+            Statement::InterfaceDef(def) => {
+                if self.interfaces.contains_key(&def.name.value) {
+                    return Err(format!(
+                        "ERROR: Interface '{}' is already declared.",
+                        def.name.value
+                    ));
+                }
+                self.interfaces.insert(def.name.value, def.methods);
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
+            // Enum definitions register their variants for construction and match checks
+            Statement::EnumDef(def) => {
+                let mut variants: Vec<(String, usize)> = Vec::new();
+                for v in def.variants {
+                    if variants.iter().any(|(name, _)| *name == v.name.value) {
+                        return Err(format!(
+                            "ERROR: Duplicate variant '{}' in enum '{}'.",
+                            v.name.value, def.name.value
+                        ));
+                    }
+                    let arity = v.payload.map(|p| p.types.len()).unwrap_or(0);
+                    variants.push((v.name.value, arity));
+                }
+                self.enums.insert(def.name.value, variants);
                 Ok(StatementResult::Normal(RuntimeVal::Void))
             }
-            // Struct definitions are just Declarations, no runtime effect in interpreter
-            Statement::StructDef(_) => Ok(StatementResult::Normal(RuntimeVal::Void)),
             // 1. Variable Declaration
             Statement::VarDecl { ident, value, .. } => {
                 let val = self.eval_expr(value)?;
-                self.env.insert(
+                self.env.declare(
                     ident.clone(),
                     Value {
                         data: val,
@@ -60,10 +237,103 @@ impl Interpreter {
                 );
                 Ok(StatementResult::Normal(RuntimeVal::Void))
             }
+            // Constants are immutable and evaluated once; initializers cannot do IO or call
+            // impure functions
+            Statement::ConstDecl {
+                name,
+                const_type,
+                value,
+                ..
+            } => {
+                if self.consts.contains_key(&name) || self.env.contains(&name) {
+                    return Err(format!("ERROR: '{}' is already declared.", name));
+                }
+                let old_mode = std::mem::replace(&mut self.in_pure_mode, true);
+                let val = self.eval_expecting(value, &const_type);
+                self.in_pure_mode = old_mode;
+                self.consts.insert(name, val?);
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
+            // var (a, b) = pair: every name is a new mutable variable
+            Statement::VarTuple { names, value, .. } => {
+                let val = self.eval_expr(value)?;
+                for (name, data) in destructure(&names.values(), val)? {
+                    self.env.declare(
+                        name,
+                        Value {
+                            data,
+                            is_mutable: true,
+                        },
+                    );
+                }
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
+
+            // (a, b) = pair: each name is assigned or declared like `a = ...`
+            Statement::AssignStmt {
+                lhs: grammar::Expression::TupleLit(list),
+                rhs,
+                ..
+            } => {
+                let mut names = Vec::new();
+                for item in list.items {
+                    match item {
+                        grammar::Expression::Variable(grammar::VariableVal { value: name })
+                        | grammar::Expression::ErrorRef(grammar::StructNameVal { value: name })
+                            if self.consts.contains_key(&name) =>
+                        {
+                            return Err(format!("ERROR: '{}' is a constant.", name));
+                        }
+                        grammar::Expression::Variable(v) => names.push(v.value),
+                        _ => return Err("ERROR: Only names can be destructured.".to_string()),
+                    }
+                }
+                let val = self.eval_expr(rhs)?;
+                for (name, data) in destructure(&names, val)? {
+                    if let Some(entry) = self.env.get_mut(&name) {
+                        if !entry.is_mutable {
+                            return Err(format!("ERROR: '{}' is immutable.", name));
+                        }
+                        if let Some(expected) = entry.data.numeric_type() {
+                            check_numeric(&expected, &data)?;
+                        }
+                        entry.data = data;
+                    } else {
+                        self.env.declare(
+                            name,
+                            Value {
+                                data,
+                                is_mutable: false,
+                            },
+                        );
+                    }
+                }
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
 
             // 2. Assignment (Top-level OR Field)
             Statement::AssignStmt { lhs, rhs, .. } => {
-                let new_val = self.eval_expr(rhs)?;
+                if let grammar::Expression::Variable(grammar::VariableVal { value: name })
+                | grammar::Expression::ErrorRef(grammar::StructNameVal { value: name }) = &lhs
+                    && self.consts.contains_key(name)
+                {
+                    return Err(format!("ERROR: '{}' is a constant.", name));
+                }
+                // Reassignments keep the numeric type of the current value
+                let current = match &lhs {
+                    crate::grammar::grammar::Expression::Variable(v) => {
+                        self.env.get(&v.value).map(|e| e.data.clone())
+                    }
+                    crate::grammar::grammar::Expression::FieldAccess(..)
+                    | crate::grammar::grammar::Expression::At(..) => {
+                        self.eval_expr(lhs.clone()).ok()
+                    }
+                    _ => None,
+                };
+                let new_val = match current.and_then(|c| c.numeric_type()) {
+                    Some(expected) => self.eval_expecting(rhs, &expected)?,
+                    None => self.eval_expr(rhs)?,
+                };
 
                 match lhs {
                     // Simple: x = 10
@@ -79,7 +349,7 @@ impl Interpreter {
                             // NEW: Immutable Declaration (First Assignment)
                             // If it doesn't exist, we create it as IMMUTABLE.
                             // "const x = 10" is achieved by "x = 10"
-                            self.env.insert(
+                            self.env.declare(
                                 name,
                                 Value {
                                     data: new_val,
@@ -91,43 +361,12 @@ impl Interpreter {
                     }
                     // Complex: x.y.z = 10
                     crate::grammar::grammar::Expression::FieldAccess(target, _, field) => {
-                        let mut path = vec![field.value];
-                        let mut current = *target;
-
-                        // Unwind the dot chain: x.y.z -> path=[z, y], root=x
-                        while let crate::grammar::grammar::Expression::FieldAccess(
-                            inner_target,
-                            _,
-                            inner_field,
-                        ) = current
-                        {
-                            path.push(inner_field.value);
-                            current = *inner_target;
-                        }
-
-                        // Now 'current' should be the variable (x)
-                        let root_name = match current {
-                            crate::grammar::grammar::Expression::Variable(v) => v.value,
-                            _ => {
-                                return Err(
-                                    "Assignment target must start with a variable.".to_string()
-                                );
-                            }
-                        };
-
-                        // 2. Get Mutable Root
-                        let entry = self
-                            .env
-                            .get_mut(&root_name)
-                            .ok_or_else(|| format!("Variable '{}' not found", root_name))?;
-
-                        if !entry.is_mutable {
-                            return Err(format!("Variable '{}' is immutable.", root_name));
-                        }
-
-                        // 3. Drill down and Update
-                        update_nested_field(&mut entry.data, path, new_val)?;
-
+                        self.assign_field(*target, field.value, new_val)?;
+                        Ok(StatementResult::Normal(RuntimeVal::Void))
+                    }
+                    // Element: xs at 2 = 5, m at "k" = v
+                    crate::grammar::grammar::Expression::At(col, _, key) => {
+                        self.set_at(*col, *key, new_val)?;
                         Ok(StatementResult::Normal(RuntimeVal::Void))
                     }
                     _ => Err("Invalid left-hand side for assignment.".to_string()),
@@ -137,15 +376,56 @@ impl Interpreter {
             // 3. Control Flow
             Statement::Return(_, expr_opt) => {
                 if let Some(expr) = expr_opt {
-                    let val = self.eval_expr(expr)?;
+                    let val = match self.return_type.clone() {
+                        Some(ret) => self.eval_expecting(expr, &ret)?,
+                        None => self.eval_expr(expr)?,
+                    };
                     Ok(StatementResult::Return(val))
                 } else {
                     Ok(StatementResult::Return(RuntimeVal::Void))
                 }
             }
-            Statement::Break(_) => Ok(StatementResult::Break),
-            Statement::Continue(_) => Ok(StatementResult::Continue),
+            Statement::Defer(_, body) => {
+                let env = self.env.clone();
+                match self.deferred.last_mut() {
+                    Some(frame) => frame.push((body, env)),
+                    None => return Err("ERROR: 'defer' must be inside a block.".to_string()),
+                }
+                Ok(StatementResult::Normal(RuntimeVal::Void))
+            }
+            Statement::Break(_, label) => Ok(StatementResult::Break(self.target_loop(label)?)),
+            Statement::Continue(_, label) => {
+                Ok(StatementResult::Continue(self.target_loop(label)?))
+            }
 
+            // on (x is some v): the body sees the value as v
+            Statement::On {
+                condition,
+                some_check: Some(check),
+                body,
+                else_clause,
+                error_clauses,
+                ..
+            } => {
+                if error_clauses.is_some() {
+                    return Err(
+                        "ERROR: 'is some' cannot be combined with error handlers.".to_string()
+                    );
+                }
+                match self.eval_expr(condition)? {
+                    RuntimeVal::Opt(Some(value)) => {
+                        self.with_bindings(vec![(check.binding, *value)], |i| i.execute_block(body))
+                    }
+                    RuntimeVal::Opt(None) => match else_clause {
+                        Some(clause) => self.execute_block(clause.body),
+                        None => Ok(StatementResult::Normal(RuntimeVal::Void)),
+                    },
+                    other => Err(format!(
+                        "Type Error: 'is some' needs an optional value, got {}.",
+                        other
+                    )),
+                }
+            }
             Statement::On {
                 condition,
                 body,
@@ -153,7 +433,7 @@ impl Interpreter {
                 error_clauses,
                 ..
             } => {
-                let val = self.eval_expr(condition)?;
+                let val = self.eval_checked(condition)?;
 
                 // Helper to flatten ErrorClauseList into Vec<&grammar::ErrorClause>
                 fn flatten_clauses(list: &grammar::ErrorClauseList) -> Vec<&grammar::ErrorClause> {
@@ -164,8 +444,18 @@ impl Interpreter {
                     result
                 }
 
+                // Handlers must name known error types
+                if let Some(ref error_list) = error_clauses
+                    && let Some(name) = flatten_clauses(error_list)
+                        .iter()
+                        .filter_map(|c| c.error_type.as_ref())
+                        .find(|name| !self.error_types.contains_key(*name))
+                {
+                    return Err(format!("ERROR: Unknown error type '{}' in handler.", name));
+                }
+
                 // Check if value is an Error
-                if let RuntimeVal::Error(ref err_name, ref err_desc) = val {
+                if let RuntimeVal::Error(ref err_name, _, ref err_fields) = val {
                     // Try to match against error clauses in order
                     if let Some(ref error_list) = error_clauses {
                         let clauses = flatten_clauses(error_list);
@@ -174,14 +464,27 @@ impl Interpreter {
                             let matches = clause.error_type.is_none()
                                 || clause.error_type.as_ref() == Some(err_name);
                             if matches {
-                                let result = self.execute_block(clause.body.clone())?;
+                                // `as e` binds the payload, read like a struct's fields
+                                let bindings = match &clause.binding {
+                                    Some(binding) => {
+                                        self.check_error_binding(clause)?;
+                                        vec![(
+                                            binding.name.clone(),
+                                            RuntimeVal::Struct(
+                                                err_name.clone(),
+                                                err_fields.clone(),
+                                            ),
+                                        )]
+                                    }
+                                    None => Vec::new(),
+                                };
+                                let result = self.with_bindings(bindings, |s| {
+                                    s.execute_block(clause.body.clone())
+                                })?;
                                 // If block returned normally with Void, implicitly return the error
                                 match result {
                                     StatementResult::Normal(RuntimeVal::Void) => {
-                                        return Ok(StatementResult::Return(RuntimeVal::Error(
-                                            err_name.clone(),
-                                            err_desc.clone(),
-                                        )));
+                                        return Ok(StatementResult::Return(val.clone()));
                                     }
                                     other => return Ok(other),
                                 }
@@ -204,27 +507,33 @@ impl Interpreter {
                 }
             }
             Statement::LoopOn {
-                condition, body, ..
+                head,
+                condition,
+                body,
+                ..
             } => {
+                let label = head.label.map(|l| l.name);
                 // While condition evaluates to True (1)
-                loop {
-                    // Re-evaluate condition each iteration
-                    let val = self.eval_expr(condition.clone())?;
+                self.in_loop(label.clone(), |i| {
+                    loop {
+                        // Re-evaluate condition each iteration
+                        let val = i.eval_checked(condition.clone())?;
 
-                    if !val.is_truthy() {
-                        break;
-                    }
-                    let res = self.execute_block(body.clone())?;
-                    match res {
-                        StatementResult::Normal(_) => {}
-                        StatementResult::Continue => {} // Loop again
-                        StatementResult::Break => break,
-                        StatementResult::Return(v) => return Ok(StatementResult::Return(v)),
+                        if !val.is_truthy() {
+                            break;
+                        }
+                        let res = i.execute_block(body.clone())?;
+                        match after_pass(res, &label) {
+                            Pass::Next => {}
+                            Pass::Stop => break,
+                            Pass::Leave(res) => return Ok(res),
+                        }
                     }
-                }
-                Ok(StatementResult::Normal(RuntimeVal::Void))
+                    Ok(StatementResult::Normal(RuntimeVal::Void))
+                })
             }
             Statement::LoopIter {
+                head,
                 iterator,
                 iterable,
                 step,
@@ -233,96 +542,110 @@ impl Interpreter {
                 else_clause,
                 ..
             } => {
-                let iterable_val = self.eval_expr(iterable)?;
+                let label = head.label.map(|l| l.name);
+                let iterable_val = self.eval_checked(iterable)?;
+                let is_map = matches!(iterable_val, RuntimeVal::Map(_));
+                let is_range = matches!(iterable_val, RuntimeVal::Range(..));
+                let step = match step {
+                    Some(s) => match self.eval_expecting(s.value, &grammar::KiroType::Int)? {
+                        RuntimeVal::Int(n) => loop_step(n, is_range)?,
+                        _ => return Err("Loop Error: Step must be an int.".to_string()),
+                    },
+                    None => 1,
+                };
 
-                // Vector of items to iterate over
+                // Vector of items to iterate over; ranges are stepped as they are counted
                 let items: Vec<RuntimeVal> = match iterable_val {
-                    RuntimeVal::Range(start, end) => {
-                        let step_val = if let Some(s) = step {
-                            self.eval_expr(s.value)?.as_float()? as i64
-                        } else {
-                            1
-                        };
-                        let mut vec = Vec::new();
-                        let mut current = start;
-                        while current < end {
-                            vec.push(RuntimeVal::Float(current as f64));
-                            current += step_val;
-                        }
-                        vec
+                    RuntimeVal::Range(start, end, inclusive) => {
+                        range_values(start, end, inclusive, step)
                     }
                     RuntimeVal::List(list) => list,
                     RuntimeVal::String(s) => s
                         .chars()
                         .map(|c| RuntimeVal::String(c.to_string()))
                         .collect(),
+                    // (key, value) tuples, ordered by key so every run visits them alike
+                    RuntimeVal::Map(map) => {
+                        let mut entries: Vec<_> = map.into_values().collect();
+                        entries.sort_by(|a, b| compare_keys(&a.0, &b.0));
+                        entries
+                            .into_iter()
+                            .map(|(k, v)| RuntimeVal::Tuple(vec![k, v]))
+                            .collect()
+                    }
                     _ => {
                         return Err(
-                            "Loop Error: Can only loop over ranges, lists, or strings".to_string()
+                            "Loop Error: Can only loop over ranges, lists, maps, or strings"
+                                .to_string(),
                         );
                     }
                 };
 
-                for item in items {
-                    let parent_env = self.env.clone();
-
-                    self.env.insert(
-                        iterator.clone(),
-                        Value {
-                            data: item,
-                            is_mutable: false,
-                        },
-                    );
-
-                    let run_main = if let Some(f) = &filter {
-                        self.eval_expr(f.condition.clone())?.as_float()? != 0.0
-                    } else {
-                        true
-                    };
-
-                    let mut break_loop = false;
-
-                    if run_main {
-                        let res = self.execute_block(body.clone())?;
-                        match res {
-                            StatementResult::Normal(_) => {}
-                            StatementResult::Continue => {}
-                            StatementResult::Break => break_loop = true,
-                            StatementResult::Return(v) => {
-                                // Must restore env BEFORE returning!
-                                self.env = parent_env;
-                                return Ok(StatementResult::Return(v));
+                // Other items are stepped after pairing, so indexes stay positions in the list
+                let every = if is_range {
+                    1
+                } else {
+                    step.unsigned_abs() as usize
+                };
+                self.in_loop(label.clone(), |i| {
+                    for (index, item) in items.into_iter().enumerate().step_by(every) {
+                        let bindings = match &iterator {
+                            grammar::LoopBinding::Name(name) => vec![(name.clone(), item)],
+                            grammar::LoopBinding::Tuple(names) => {
+                                destructure(&names.values(), item)?
                             }
-                        }
-                    } else if let Some(off) = &else_clause {
-                        let res = self.execute_block(off.body.clone())?;
-                        match res {
-                            StatementResult::Normal(_) => {}
-                            StatementResult::Continue => {}
-                            StatementResult::Break => break_loop = true,
-                            StatementResult::Return(v) => {
-                                self.env = parent_env;
-                                return Ok(StatementResult::Return(v));
+                            // Map items already are (key, value)
+                            grammar::LoopBinding::Pair(first, _, second) => {
+                                let pair = match is_map {
+                                    true => item,
+                                    false => {
+                                        RuntimeVal::Tuple(vec![RuntimeVal::Int(index as i64), item])
+                                    }
+                                };
+                                destructure(&[first.clone(), second.value.clone()], pair)?
                             }
-                        }
-                    }
+                        };
 
-                    self.env = parent_env;
+                        // Each iteration gets its own scope for the loop variables; assignments
+                        // to outer variables inside the body persist across iterations
+                        let res = i.with_bindings(bindings, |i| {
+                            let run_main = match &filter {
+                                Some(f) => i.eval_expr(f.condition.clone())?.is_truthy(),
+                                None => true,
+                            };
+                            if run_main {
+                                i.execute_block(body.clone())
+                            } else if let Some(off) = &else_clause {
+                                i.execute_block(off.body.clone())
+                            } else {
+                                Ok(StatementResult::Normal(RuntimeVal::Void))
+                            }
+                        })?;
 
-                    if break_loop {
-                        break;
+                        match after_pass(res, &label) {
+                            Pass::Next => {}
+                            Pass::Stop => break,
+                            Pass::Leave(res) => return Ok(res),
+                        }
                     }
-                }
-                Ok(StatementResult::Normal(RuntimeVal::Void))
+                    Ok(StatementResult::Normal(RuntimeVal::Void))
+                })
+            }
+            Statement::CompoundAssign { lhs, op, rhs } => {
+                self.execute_statement(op.desugar(lhs, rhs))
             }
             Statement::Print(_, expr) => {
                 if self.in_pure_mode {
                     return Err("Pure Function Error: 'print' is forbidden.".to_string());
                 }
-                let val = self.eval_expr(expr)?;
+                let val = self.eval_checked(expr)?;
                 println!("{}", val);
                 Ok(StatementResult::Normal(RuntimeVal::Void))
             }
+            // Match in statement position may return/break out of the enclosing block
+            Statement::ExprStmt(grammar::Expression::Match(_, subject, _, arms, _)) => {
+                self.eval_match(*subject, arms, None)
+            }
             Statement::ExprStmt(expr) => {
                 let val = self.eval_expr(expr)?;
                 Ok(StatementResult::Normal(val))
@@ -373,52 +696,164 @@ impl Interpreter {
                 // Resolve module path:
                 // 1. If starts with "std_", look in src/kiro_std/{module_name}/std_{module_name}.kiro
                 // 2. Otherwise, look in current directory as {name}.kiro
-                let (source, filename) = if module_name.starts_with("std_") {
-                    let module_suffix = &module_name[4..]; // Remove "std_" prefix
-                    let asset_path = format!("{}/{}.kiro", module_suffix, module_name);
-                    let content = crate::StdAssets::get(&asset_path)
-                        .map(|f| std::str::from_utf8(f.data.as_ref()).unwrap().to_string())
-                        .ok_or_else(|| {
-                            format!(
-                                "Standard library module '{}' not found in embedded assets",
-                                module_name
-                            )
-                        })?;
-                    (content, asset_path)
-                } else {
-                    let filename = format!("{}.kiro", module_name);
-                    let content = std::fs::read_to_string(&filename)
-                        .map_err(|_| format!("Module '{}' not found", filename))?;
-                    (content, filename)
-                };
+                let (source, filename) =
+                    if let Some(module_suffix) = module_name.strip_prefix("std_") {
+                        // Remove "std_" prefix
+                        let asset_path = format!("{}/{}.kiro", module_suffix, module_name);
+                        let content = crate::StdAssets::get(&asset_path)
+                            .map(|f| std::str::from_utf8(f.data.as_ref()).unwrap().to_string())
+                            .ok_or_else(|| {
+                                format!(
+                                    "Standard library module '{}' not found in embedded assets",
+                                    module_name
+                                )
+                            })?;
+                        (content, asset_path)
+                    } else {
+                        let filename = format!("{}.kiro", module_name);
+                        let content = std::fs::read_to_string(&filename)
+                            .map_err(|_| format!("Module '{}' not found", filename))?;
+                        (content, filename)
+                    };
 
                 println!("📦 Importing {}...", filename);
 
-                // We need to access the parse function.
-                // Since main.rs uses grammar::parse, and grammar is crate::grammar::grammar
-                // We cannot access it directly here easily without circular dependencies or exposing it.
-                // For now, simpler: Just support pure Kiro parsing if we had access to parser.
-                // But interpreter doesn't interpret imported modules yet in this snippet!
-                println!(
-                    "⚠️ [Interpreter] 'import' is not fully supported in pure interpreter mode."
+                // The module runs in its own interpreter; its constants and functions
+                // become the fields of the module value
+                let program = match crate::grammar::syntax::parse_file(&source) {
+                    Ok(program) => program,
+                    Err(errors) => {
+                        eprintln!(
+                            "{}",
+                            crate::diagnostics::render(&errors, &filename, &source)
+                        );
+                        return Err(format!("Module '{}' has syntax errors.", filename));
+                    }
+                };
+                if let Err(errors) = crate::checker::check(&program) {
+                    eprintln!(
+                        "{}",
+                        crate::diagnostics::render(&errors, &filename, &source)
+                    );
+                    return Err(format!("Module '{}' has type errors.", filename));
+                }
+                let mut module = super::Interpreter::new();
+                if let Err(e) = module.run(program) {
+                    // The error points into the module, or into a module it imports
+                    self.error_span = module.error_span;
+                    self.error_related = module.error_related;
+                    self.error_file = module.error_file.or(Some((filename, source)));
+                    return Err(e);
+                }
+                // Its errors can be handled here; errors declared here take precedence
+                for (name, desc) in module.error_types {
+                    if self.error_types.contains_key(&name) {
+                        continue;
+                    }
+                    if let Some(fields) = module.structs.remove(&name) {
+                        self.structs.insert(name.clone(), fields);
+                    }
+                    self.error_types.insert(name, desc);
+                }
+                self.env.declare(
+                    module_name,
+                    Value {
+                        data: RuntimeVal::Module(module.consts, module.functions),
+                        is_mutable: false,
+                    },
                 );
                 Ok(StatementResult::Normal(RuntimeVal::Void))
             }
-            Statement::Documented { item, .. } => {
-                let stmt = match item {
-                    grammar::AnnotatableItem::StructDef(s) => Statement::StructDef(s),
-                    grammar::AnnotatableItem::FunctionDef(f) => Statement::FunctionDef(f),
-                    grammar::AnnotatableItem::RustFnDecl(r) => Statement::RustFnDecl(r),
-                };
-                self.execute_statement(stmt)
+            Statement::Documented { item, .. } => self.execute_statement(item.into_statement()),
+        }
+    }
+    // `error Name as e` needs an error type declared with fields
+    fn check_error_binding(&self, clause: &grammar::ErrorClause) -> Result<(), String> {
+        match &clause.error_type {
+            Some(name)
+                if self.error_types.contains_key(name) && self.structs.contains_key(name) =>
+            {
+                Ok(())
             }
+            Some(name) => Err(format!(
+                "ERROR: Error '{}' has no fields to bind with 'as'.",
+                name
+            )),
+            None => Err("ERROR: 'as' needs an error type: error Name as e { ... }".to_string()),
         }
     }
+
+    // Every block is a scope: names declared inside are dropped when it ends
     pub fn execute_block(&mut self, block: grammar::Block) -> Result<StatementResult, String> {
+        self.env.push();
+        self.deferred.push(Vec::new());
+        // Deferred blocks run on every way out: the end, return, break, continue
+        let result = self.execute_statements(block.statements);
+        let result = self.run_deferred().and(result);
+        self.env.pop();
+        result
+    }
+
+    // Runs the innermost block's deferred bodies, last first. Each runs on the copy of
+    // the variables taken by its `defer`, and cannot leave with return/break/continue.
+    pub fn run_deferred(&mut self) -> Result<(), String> {
+        let deferred = self.deferred.pop().unwrap_or_default();
+        for (body, env) in deferred.into_iter().rev() {
+            let outer_env = std::mem::replace(&mut self.env, env);
+            let outer_failable = std::mem::replace(&mut self.in_failable_fn, false);
+            let result = self.execute_block(body);
+            self.env = outer_env;
+            self.in_failable_fn = outer_failable;
+            match result? {
+                StatementResult::Normal(_) => {}
+                StatementResult::Return(_) => {
+                    return Err("ERROR: 'return' cannot leave a 'defer' block.".to_string());
+                }
+                StatementResult::Break(_) | StatementResult::Continue(_) => {
+                    return Err(
+                        "ERROR: 'break' and 'continue' cannot leave a 'defer' block.".to_string(),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Runs a loop. Moving a variable declared outside it from inside the body is an error.
+    fn in_loop(
+        &mut self,
+        label: Option<String>,
+        f: impl FnOnce(&mut Self) -> Result<StatementResult, String>,
+    ) -> Result<StatementResult, String> {
+        self.loop_depths.push(self.env.depth());
+        let labeled = label.is_some();
+        self.loop_labels.extend(label);
+        let result = f(self);
+        self.loop_depths.pop();
+        if labeled {
+            self.loop_labels.pop();
+        }
+        result
+    }
+
+    // `break@outer` / `continue@outer` must be inside a loop labeled outer
+    fn target_loop(&self, label: Option<grammar::LoopLabel>) -> Result<Option<String>, String> {
+        match label {
+            Some(l) if !self.loop_labels.contains(&l.name) => {
+                Err(format!("ERROR: No enclosing loop is labeled '{}'.", l.name))
+            }
+            label => Ok(label.map(|l| l.name)),
+        }
+    }
+
+    fn execute_statements(
+        &mut self,
+        statements: Vec<Spanned<Statement>>,
+    ) -> Result<StatementResult, String> {
         let mut last_val = RuntimeVal::Void;
 
-        for stmt in block.statements {
-            let res = self.execute_statement(stmt)?;
+        for stmt in statements {
+            let res = self.execute_spanned(stmt)?;
             match res {
                 StatementResult::Normal(v) => last_val = v,
                 // Bubble up control flow signals immediately!
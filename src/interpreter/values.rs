@@ -1,3 +1,5 @@
+use crate::grammar::grammar::{KiroType, StructNameVal};
+use crate::grammar::strings::FormatSpec;
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::mpsc::{Receiver, Sender};
@@ -6,22 +8,43 @@ use std::sync::{Arc, Mutex};
 #[derive(Clone, Debug)]
 pub enum RuntimeVal {
     Float(f64),
+    Int(i64),
     String(String),
     Bool(bool),
-    Range(i64, i64),
+    Range(i64, i64, bool), // start, end, inclusive
     Void,
     Pipe(Sender<f64>, Arc<Mutex<Receiver<f64>>>),
     Struct(String, HashMap<String, RuntimeVal>),
+    // Enum: (enum_name, variant_name, payload)
+    Enum(String, String, Vec<RuntimeVal>),
     List(Vec<RuntimeVal>),
-    Map(HashMap<String, RuntimeVal>),
-    // Data Exports, Function ASTs
+    // Key text -> (key, value); the key keeps its type for ordering and loops
+    Map(HashMap<String, (RuntimeVal, RuntimeVal)>),
+    Tuple(Vec<RuntimeVal>),
+    // opt T: none / some x
+    Opt(Option<Box<RuntimeVal>>),
+    // Exported constants, Function ASTs
     Module(
         HashMap<String, RuntimeVal>,
         HashMap<String, crate::grammar::grammar::Statement>,
     ),
-    // Error: (type_name, description)
-    Error(String, String),
-    Moved,
+    // Error: (type_name, description, payload fields)
+    Error(String, String, HashMap<String, RuntimeVal>),
+    Function(Arc<FunctionVal>),
+    Moved((usize, usize)), // Span of the statement that moved the value out
+}
+
+// A callable value: a named function or a closure with its captured environment
+#[derive(Debug)]
+pub struct FunctionVal {
+    pub name: String,
+    pub params: Vec<crate::grammar::grammar::FuncParam>,
+    pub body: crate::grammar::grammar::Block,
+    pub return_type: Option<KiroType>,
+    pub is_pure: bool,
+    pub can_error: bool, // `-> T!`: may return errors, and use `try`
+    // None for named functions, which see the caller's environment like direct calls
+    pub captured: Option<HashMap<String, Value>>,
 }
 
 // Manual implementation to handle Pipe which cannot be compared
@@ -29,20 +52,29 @@ impl PartialEq for RuntimeVal {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (RuntimeVal::Float(a), RuntimeVal::Float(b)) => a == b,
+            (RuntimeVal::Int(a), RuntimeVal::Int(b)) => a == b,
             (RuntimeVal::String(a), RuntimeVal::String(b)) => a == b,
             (RuntimeVal::Bool(a), RuntimeVal::Bool(b)) => a == b,
-            (RuntimeVal::Range(s1, e1), RuntimeVal::Range(s2, e2)) => s1 == s2 && e1 == e2,
+            (RuntimeVal::Range(s1, e1, i1), RuntimeVal::Range(s2, e2, i2)) => {
+                s1 == s2 && e1 == e2 && i1 == i2
+            }
             (RuntimeVal::Void, RuntimeVal::Void) => true,
             // Pipes are never equal (identity check is hard without ID)
             (RuntimeVal::Pipe(_, _), RuntimeVal::Pipe(_, _)) => false,
             // Structs equality
             (RuntimeVal::Struct(n1, d1), RuntimeVal::Struct(n2, d2)) => n1 == n2 && d1 == d2,
+            (RuntimeVal::Enum(e1, v1, p1), RuntimeVal::Enum(e2, v2, p2)) => {
+                e1 == e2 && v1 == v2 && p1 == p2
+            }
             // Collections equality
             (RuntimeVal::List(l1), RuntimeVal::List(l2)) => l1 == l2,
             (RuntimeVal::Map(m1), RuntimeVal::Map(m2)) => m1 == m2,
+            (RuntimeVal::Tuple(t1), RuntimeVal::Tuple(t2)) => t1 == t2,
+            (RuntimeVal::Opt(o1), RuntimeVal::Opt(o2)) => o1 == o2,
             (RuntimeVal::Module(_m1, _f1), RuntimeVal::Module(_m2, _f2)) => false, // Modules identity is tough, assume false for now
-            (RuntimeVal::Error(n1, _), RuntimeVal::Error(n2, _)) => n1 == n2,
-            (RuntimeVal::Moved, RuntimeVal::Moved) => true,
+            (RuntimeVal::Error(n1, _, _), RuntimeVal::Error(n2, _, _)) => n1 == n2,
+            (RuntimeVal::Function(f1), RuntimeVal::Function(f2)) => Arc::ptr_eq(f1, f2),
+            (RuntimeVal::Moved(_), RuntimeVal::Moved(_)) => true,
             _ => false,
         }
     }
@@ -52,6 +84,7 @@ impl PartialOrd for RuntimeVal {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self, other) {
             (RuntimeVal::Float(a), RuntimeVal::Float(b)) => a.partial_cmp(b),
+            (RuntimeVal::Int(a), RuntimeVal::Int(b)) => a.partial_cmp(b),
             (RuntimeVal::String(a), RuntimeVal::String(b)) => a.partial_cmp(b),
             // Other types: define an arbitrary order or return None
             _ => None,
@@ -67,33 +100,196 @@ impl RuntimeVal {
         }
     }
 
+    // Range bounds and steps accept either numeric type; num is truncated
+    pub fn as_range_bound(&self) -> Result<i64, String> {
+        match self {
+            RuntimeVal::Int(i) => Ok(*i),
+            RuntimeVal::Float(f) => Ok(*f as i64),
+            _ => Err("Type Error: Expected a number".to_string()),
+        }
+    }
+
+    // `int(x)`: truncates toward zero, rejecting NaN, infinities and out-of-range values
+    pub fn to_int(&self) -> Result<RuntimeVal, String> {
+        match self {
+            RuntimeVal::Int(i) => Ok(RuntimeVal::Int(*i)),
+            RuntimeVal::Float(f)
+                if f.is_finite() && *f >= i64::MIN as f64 && f.trunc() < i64::MAX as f64 =>
+            {
+                Ok(RuntimeVal::Int(*f as i64))
+            }
+            other => Err(format!("Runtime Error: Cannot convert {} to int.", other)),
+        }
+    }
+
+    // `num(x)`
+    pub fn to_num(&self) -> Result<RuntimeVal, String> {
+        match self {
+            RuntimeVal::Int(i) => Ok(RuntimeVal::Float(*i as f64)),
+            RuntimeVal::Float(f) => Ok(RuntimeVal::Float(*f)),
+            other => Err(format!("Runtime Error: Cannot convert {} to num.", other)),
+        }
+    }
+
+    // The numeric type of the value, if it has one
+    pub fn numeric_type(&self) -> Option<KiroType> {
+        match self {
+            RuntimeVal::Int(_) => Some(KiroType::Int),
+            RuntimeVal::Float(_) => Some(KiroType::Num),
+            _ => None,
+        }
+    }
+
+    // The type a match checks its patterns against; None where patterns can't tell
+    pub fn match_type(&self) -> Option<KiroType> {
+        match self {
+            RuntimeVal::Int(_) => Some(KiroType::Int),
+            RuntimeVal::Float(_) => Some(KiroType::Num),
+            RuntimeVal::String(_) => Some(KiroType::Str),
+            RuntimeVal::Bool(_) => Some(KiroType::Bool),
+            RuntimeVal::Struct(name, _) | RuntimeVal::Enum(name, _, _) => {
+                Some(KiroType::Custom(StructNameVal {
+                    value: name.clone(),
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    // Optionals only yield their value through `on (x is some v)`
+    pub fn checked(self) -> Result<RuntimeVal, String> {
+        match self {
+            RuntimeVal::Opt(_) => Err(
+                "Type Error: Optional value used without checking it. Use `on (x is some v)`."
+                    .to_string(),
+            ),
+            val => Ok(val),
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             RuntimeVal::Float(f) => *f != 0.0,
+            RuntimeVal::Int(i) => *i != 0,
             RuntimeVal::Bool(b) => *b,
             RuntimeVal::String(s) => !s.is_empty(),
             RuntimeVal::Void => false,
-            RuntimeVal::Moved => false,
+            RuntimeVal::Moved(_) => false,
             _ => true,
         }
     }
 }
 
+// Pads/truncates like Rust's format! so interpolated strings match compiled output
+fn pad<T: fmt::Display>(v: T, spec: &FormatSpec) -> String {
+    let w = spec.width.unwrap_or(0);
+    match (spec.align, spec.zero, spec.precision) {
+        (_, true, None) => format!("{:0w$}", v, w = w),
+        (_, true, Some(p)) => format!("{:0w$.p$}", v, w = w, p = p),
+        (Some('<'), _, None) => format!("{:<w$}", v, w = w),
+        (Some('<'), _, Some(p)) => format!("{:<w$.p$}", v, w = w, p = p),
+        (Some('>'), _, None) => format!("{:>w$}", v, w = w),
+        (Some('>'), _, Some(p)) => format!("{:>w$.p$}", v, w = w, p = p),
+        (Some(_), _, None) => format!("{:^w$}", v, w = w),
+        (Some(_), _, Some(p)) => format!("{:^w$.p$}", v, w = w, p = p),
+        (None, _, None) => format!("{:w$}", v, w = w),
+        (None, _, Some(p)) => format!("{:w$.p$}", v, w = w, p = p),
+    }
+}
+
+// int and num never mix implicitly; `expected` is the declared type of the destination
+pub fn check_numeric(expected: &KiroType, val: &RuntimeVal) -> Result<(), String> {
+    match (expected, val) {
+        (KiroType::Int, RuntimeVal::Float(_)) => {
+            Err("Type Error: Expected int, got num. Convert with int(x).".to_string())
+        }
+        (KiroType::Num, RuntimeVal::Int(_)) => {
+            Err("Type Error: Expected num, got int. Convert with num(x).".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+// Pairs each name with its tuple element; `_` discards the element
+pub fn destructure(names: &[String], val: RuntimeVal) -> Result<Vec<(String, RuntimeVal)>, String> {
+    let items = match val {
+        RuntimeVal::Tuple(items) => items,
+        other => {
+            return Err(format!(
+                "ERROR: Cannot destructure '{}', it is not a tuple.",
+                other
+            ));
+        }
+    };
+    if items.len() != names.len() {
+        return Err(format!(
+            "ERROR: Cannot destructure a {}-tuple into {} names.",
+            items.len(),
+            names.len()
+        ));
+    }
+    Ok(names
+        .iter()
+        .cloned()
+        .zip(items)
+        .filter(|(name, _)| name != "_")
+        .collect())
+}
+
+// Checked int arithmetic result
+// Order of map keys, which share one type: numbers by value, strings and bools as in Rust
+pub fn compare_keys(a: &RuntimeVal, b: &RuntimeVal) -> std::cmp::Ordering {
+    match (a, b) {
+        (RuntimeVal::Int(x), RuntimeVal::Int(y)) => x.cmp(y),
+        (RuntimeVal::Float(x), RuntimeVal::Float(y)) => x.total_cmp(y),
+        (RuntimeVal::Bool(x), RuntimeVal::Bool(y)) => x.cmp(y),
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+pub fn checked_int(result: Option<i64>) -> Result<RuntimeVal, String> {
+    result
+        .map(RuntimeVal::Int)
+        .ok_or_else(|| "Runtime Error: Integer overflow".to_string())
+}
+
+impl RuntimeVal {
+    // Formats a value for a `{x:spec}` placeholder
+    pub fn format_with(&self, spec: &FormatSpec) -> String {
+        match self {
+            RuntimeVal::Float(n) => pad(n, spec),
+            RuntimeVal::Int(n) => pad(n, spec),
+            RuntimeVal::Bool(b) => pad(b, spec),
+            other => pad(other.to_string(), spec),
+        }
+    }
+}
+
 impl fmt::Display for RuntimeVal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             RuntimeVal::Float(n) => write!(f, "{}", n),
+            RuntimeVal::Int(n) => write!(f, "{}", n),
             RuntimeVal::String(s) => write!(f, "{}", s),
             RuntimeVal::Bool(b) => write!(f, "{}", b),
-            RuntimeVal::Range(s, e) => write!(f, "{}..{}", s, e),
+            RuntimeVal::Range(s, e, false) => write!(f, "{}..{}", s, e),
+            RuntimeVal::Range(s, e, true) => write!(f, "{}..={}", s, e),
             RuntimeVal::Void => write!(f, "void"),
             RuntimeVal::Pipe(_, _) => write!(f, "<Pipe>"),
             RuntimeVal::Struct(name, _) => write!(f, "<Struct {}>", name),
+            RuntimeVal::Enum(name, variant, _) => write!(f, "<Enum {}.{}>", name, variant),
             RuntimeVal::List(l) => write!(f, "<List len={}>", l.len()),
             RuntimeVal::Map(m) => write!(f, "<Map len={}>", m.len()),
+            RuntimeVal::Opt(None) => write!(f, "none"),
+            RuntimeVal::Opt(Some(v)) => write!(f, "some {}", v),
+            RuntimeVal::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                write!(f, "({})", items.join(", "))
+            }
             RuntimeVal::Module(_, _) => write!(f, "<Module>"),
-            RuntimeVal::Error(name, desc) => write!(f, "Error({}): {}", name, desc),
-            RuntimeVal::Moved => write!(f, "<Moved>"),
+            RuntimeVal::Error(name, desc, _) => write!(f, "Error({}): {}", name, desc),
+            RuntimeVal::Function(func) => write!(f, "<Function {}>", func.name),
+            RuntimeVal::Moved(_) => write!(f, "<Moved>"),
         }
     }
 }
@@ -3,19 +3,21 @@
 
 use kiro_runtime::{KiroError, RuntimeVal};
 
+// The Kiro error for a failed operation on `path`, which it carries as a payload field
+fn io_error(e: std::io::Error, path: &str) -> KiroError {
+    let name = match e.kind() {
+        std::io::ErrorKind::NotFound => "NotFound",
+        std::io::ErrorKind::PermissionDenied => "PermissionDenied",
+        _ => "IoError",
+    };
+    KiroError::new(name).with_field("path", path)
+}
+
 pub async fn read(args: Vec<RuntimeVal>) -> Result<RuntimeVal, KiroError> {
     let path = args[0].as_str()?;
     match tokio::fs::read_to_string(path).await {
         Ok(content) => Ok(RuntimeVal::from(content)),
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Err(KiroError::new("NotFound"))
-            } else if e.kind() == std::io::ErrorKind::PermissionDenied {
-                Err(KiroError::new("PermissionDenied"))
-            } else {
-                Err(KiroError::new("IoError"))
-            }
-        }
+        Err(e) => Err(io_error(e, path)),
     }
 }
 
@@ -24,13 +26,7 @@ pub async fn write(args: Vec<RuntimeVal>) -> Result<RuntimeVal, KiroError> {
     let content = args[1].as_str()?;
     match tokio::fs::write(path, content).await {
         Ok(()) => Ok(RuntimeVal::Void),
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                Err(KiroError::new("PermissionDenied"))
-            } else {
-                Err(KiroError::new("IoError"))
-            }
-        }
+        Err(e) => Err(io_error(e, path)),
     }
 }
 
@@ -46,15 +42,7 @@ pub async fn remove(args: Vec<RuntimeVal>) -> Result<RuntimeVal, KiroError> {
     let path = args[0].as_str()?;
     match tokio::fs::remove_file(path).await {
         Ok(()) => Ok(RuntimeVal::Void),
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Err(KiroError::new("NotFound"))
-            } else if e.kind() == std::io::ErrorKind::PermissionDenied {
-                Err(KiroError::new("PermissionDenied"))
-            } else {
-                Err(KiroError::new("IoError"))
-            }
-        }
+        Err(e) => Err(io_error(e, path)),
     }
 }
 
@@ -70,14 +58,6 @@ pub async fn list(args: Vec<RuntimeVal>) -> Result<RuntimeVal, KiroError> {
             }
             Ok(RuntimeVal::List(names))
         }
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Err(KiroError::new("NotFound"))
-            } else if e.kind() == std::io::ErrorKind::PermissionDenied {
-                Err(KiroError::new("PermissionDenied"))
-            } else {
-                Err(KiroError::new("IoError"))
-            }
-        }
+        Err(e) => Err(io_error(e, path)),
     }
 }
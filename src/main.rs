@@ -1,7 +1,11 @@
 mod build_manager;
+mod checker;
 mod compiler;
+mod diagnostics;
+mod exhaustive;
 mod grammar;
 mod interpreter;
+mod scope;
 
 use crate::build_manager::BuildManager;
 
@@ -150,14 +154,12 @@ fn handle_add(dep: &str) {
         format!("{}/header.rs", dep)
     };
 
-    if dep.starts_with("std_") {
-        if StdAssets::get(&embedded_path).is_none() {
-            eprintln!(
-                "Error: Module '{}' starts with reserved prefix 'std_' but is not part of the Kiro Standard Library.",
-                dep
-            );
-            std::process::exit(1);
-        }
+    if dep.starts_with("std_") && StdAssets::get(&embedded_path).is_none() {
+        eprintln!(
+            "Error: Module '{}' starts with reserved prefix 'std_' but is not part of the Kiro Standard Library.",
+            dep
+        );
+        std::process::exit(1);
     }
 
     // 2. Read and Parse kiro.toml
@@ -194,10 +196,10 @@ fn handle_add(dep: &str) {
                 .current_dir(dot_kiro)
                 .status();
 
-            if let Ok(s) = status {
-                if !s.success() {
-                    eprintln!("Warning: 'cargo add' failed.");
-                }
+            if let Ok(s) = status
+                && !s.success()
+            {
+                eprintln!("Warning: 'cargo add' failed.");
             }
         }
     }
@@ -257,12 +259,12 @@ async fn main() {
             emit_rust,
             verbose,
         }) => {
-            if !execute_pipeline(&file, !*no_interpret, !*no_run, *emit_rust, *verbose) {
+            if !execute_pipeline(file, !*no_interpret, !*no_run, *emit_rust, *verbose) {
                 std::process::exit(1);
             }
         }
         Some(Commands::Check { file }) => {
-            if !run_interpreter(&file) {
+            if !run_interpreter(file) {
                 std::process::exit(1);
             }
         }
@@ -271,7 +273,7 @@ async fn main() {
             emit_rust,
             verbose,
         }) => {
-            if run_compiler(&file, *emit_rust, *verbose).is_err() {
+            if run_compiler(file, *emit_rust, *verbose).is_err() {
                 std::process::exit(1);
             }
         }
@@ -361,17 +363,33 @@ fn run_interpreter(filename: &str) -> bool {
         }
     };
 
-    let prog = match grammar::parse(&source) {
+    let prog = match grammar::syntax::parse_file(&source) {
         Ok(p) => p,
-        Err(e) => {
-            eprintln!("Parse Error in {}: {:?}", filename, e);
+        Err(errors) => {
+            eprintln!("{}", diagnostics::render(&errors, filename, &source));
             return false;
         }
     };
 
+    if let Err(errors) = checker::check(&prog) {
+        eprintln!("{}", diagnostics::render(&errors, filename, &source));
+        return false;
+    }
+
     let mut i = interpreter::Interpreter::new();
     if let Err(e) = i.run(prog) {
-        eprintln!("Interpreter Error: {}", e);
+        // Errors inside an imported module point into that module's source
+        let (file, src) = i
+            .error_file
+            .take()
+            .unwrap_or_else(|| (filename.to_string(), source));
+        let mut error = diagnostics::Diagnostic::new(
+            diagnostics::Kind::Runtime,
+            e,
+            i.error_span.unwrap_or((0, 0)),
+        );
+        error.related.extend(i.error_related.take());
+        eprintln!("{}", diagnostics::render(&[error], &file, &src));
         return false;
     }
     true
@@ -387,11 +405,11 @@ fn run_compiler(filename: &str, _emit_rust: bool, verbose: bool) -> Result<PathB
         return Err(format!("Init Error: {}", e));
     }
 
-    let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut built = std::collections::HashMap::new();
     let path = std::path::Path::new(filename);
     let name = path.file_stem().unwrap().to_str().unwrap();
     let dir = path.parent().map(|p| p.to_str().unwrap()).unwrap_or("");
-    build_recursive(name, dir, &mut seen, &pm, true);
+    build_recursive(name, dir, &mut built, &pm, true)?;
 
     match pm.build(verbose) {
         Ok(output_path) => Ok(output_path),
@@ -423,31 +441,36 @@ pub struct StdAssets;
 #[folder = "kiro_runtime/"]
 pub struct RuntimeAssets;
 
+// Errors a module declares, by name, with their payload fields
+type ModuleErrors = std::collections::HashMap<String, Vec<grammar::grammar::FieldDef>>;
+
+// Compiles `name` after its imports and returns the errors it declares, so importers
+// can handle them. Each module is built once. Fails when a file has type errors.
 fn build_recursive(
     name: &str,
     base_dir: &str,
-    seen: &mut std::collections::HashSet<String>,
+    built: &mut std::collections::HashMap<String, ModuleErrors>,
     pm: &BuildManager,
     is_root: bool,
-) {
-    if seen.contains(name) {
-        return;
+) -> Result<ModuleErrors, String> {
+    if let Some(errors) = built.get(name) {
+        return Ok(errors.clone());
     }
-    seen.insert(name.to_string());
+    built.insert(name.to_string(), ModuleErrors::new());
 
     // Try to resolve module path:
     // 1. If starts with "std_", look in embedded assets
     // 2. Otherwise, look in base_dir or current directory as {name}.kiro
-    let src = if name.starts_with("std_") {
-        let module_name = &name[4..]; // Remove "std_" prefix
+    let (src, filename) = if let Some(module_name) = name.strip_prefix("std_") {
+        // Remove "std_" prefix
         // Map std_fs -> fs/std_fs.kiro
         let asset_path = format!("{}/{}.kiro", module_name, name);
-        StdAssets::get(&asset_path)
+        let Some(src) = StdAssets::get(&asset_path)
             .map(|f| std::str::from_utf8(f.data.as_ref()).unwrap().to_string())
-            .expect(&format!(
-                "Standard library module '{}' not found in embedded assets",
-                name
-            ))
+        else {
+            return Err(format!("Standard library module '{}' not found", name));
+        };
+        (src, asset_path)
     } else {
         let filename = if !base_dir.is_empty() {
             format!("{}/{}.kiro", base_dir, name)
@@ -456,35 +479,68 @@ fn build_recursive(
         };
 
         match fs::read_to_string(&filename) {
-            Ok(s) => s,
+            Ok(s) => (s, filename),
             Err(_) => {
                 eprintln!(
                     "❌ Compiler Warning: File '{}' not found during build.",
                     filename
                 );
-                return;
+                return Ok(ModuleErrors::new());
             }
         }
     };
 
-    let prog = grammar::parse(&src).expect("Parse error during build");
+    let prog = match grammar::syntax::parse_file(&src) {
+        Ok(p) => p,
+        Err(errors) => {
+            eprintln!("{}", diagnostics::render(&errors, &filename, &src));
+            return Err(format!("Parse errors in {}", filename));
+        }
+    };
+    let types = match checker::check(&prog) {
+        Ok(types) => types,
+        Err(errors) => {
+            eprintln!("{}", diagnostics::render(&errors, &filename, &src));
+            return Err(format!("Type errors in {}", filename));
+        }
+    };
 
     // Find imports to recurse
+    let mut c = compiler::Compiler::new();
+    c.inferred = types;
     for s in &prog.statements {
-        if let grammar::grammar::Statement::Import { module_name, .. } = s {
+        if let grammar::grammar::Statement::Import { module_name, .. } = &s.value {
             // For imports, use base_dir for relative imports or "" for std imports
             let import_dir = if module_name.starts_with("std_") {
                 ""
             } else {
                 base_dir
             };
-            build_recursive(module_name, import_dir, seen, pm, false);
+            // A missing std module is reported at the import that names it
+            if let Some(module) = module_name.strip_prefix("std_")
+                && StdAssets::get(&format!("{}/{}.kiro", module, module_name)).is_none()
+            {
+                let error = diagnostics::Diagnostic::new(
+                    diagnostics::Kind::Compile,
+                    format!("Standard library module '{}' not found.", module_name),
+                    s.span,
+                );
+                eprintln!("{}", diagnostics::render(&[error], &filename, &src));
+                return Err(format!("Compile errors in {}", filename));
+            }
+            let errors = build_recursive(module_name, import_dir, built, pm, false)?;
+            c.import_errors(module_name, &errors);
         }
     }
 
     // Compile
-    let mut c = compiler::Compiler::new();
     let code = c.compile(prog, is_root);
+    if !c.diagnostics.is_empty() {
+        eprintln!("{}", diagnostics::render(&c.diagnostics, &filename, &src));
+        return Err(format!("Compile errors in {}", filename));
+    }
+    let errors = c.declared_errors();
+    built.insert(name.to_string(), errors.clone());
 
     let save_name = if is_root { "main" } else { name };
     if let Err(e) = pm.save_file(save_name, code) {
@@ -494,8 +550,7 @@ fn build_recursive(
     }
 
     // If this is a std module, also copy its header.rs content
-    if name.starts_with("std_") {
-        let module_suffix = &name[4..];
+    if let Some(module_suffix) = name.strip_prefix("std_") {
         let header_path = format!("{}/header.rs", module_suffix);
         if let Some(file) = StdAssets::get(&header_path) {
             let header_content = std::str::from_utf8(file.data.as_ref()).unwrap();
@@ -514,4 +569,5 @@ fn build_recursive(
             }
         }
     }
+    Ok(errors)
 }
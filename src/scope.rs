@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet};
+
+// Lexical scopes shared by the compiler and the interpreter: one frame per block.
+// A name declared in a block is dropped when the block ends. Declaring a name again with
+// `var` shadows the visible one until the end of the declaring block.
+#[derive(Debug, Clone)]
+pub struct Scopes<T> {
+    frames: Vec<HashMap<String, T>>,
+    // Names dropped with their block, to report "out of scope" instead of "not found"
+    dropped: HashSet<String>,
+}
+
+impl<T> Default for Scopes<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Scopes<T> {
+    pub fn new() -> Self {
+        Self::from_frame(HashMap::new())
+    }
+
+    pub fn from_frame(frame: HashMap<String, T>) -> Self {
+        Scopes {
+            frames: vec![frame],
+            dropped: HashSet::new(),
+        }
+    }
+
+    pub fn push(&mut self) {
+        self.frames.push(HashMap::new());
+    }
+
+    // Ends the innermost block and returns the names it declared
+    pub fn pop(&mut self) -> Vec<String> {
+        if self.frames.len() == 1 {
+            panic!("Scope Error: Cannot leave the outermost scope.");
+        }
+        let names: Vec<String> = self.frames.pop().unwrap_or_default().into_keys().collect();
+        self.dropped.extend(names.iter().cloned());
+        names
+    }
+
+    // Declares in the innermost block, shadowing any outer binding of the name
+    pub fn declare(&mut self, name: String, value: T) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name, value);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.frames.iter().rev().find_map(|frame| frame.get(name))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut T> {
+        self.frames
+            .iter_mut()
+            .rev()
+            .find_map(|frame| frame.get_mut(name))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    // Number of open blocks
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Index of the block declaring the visible binding of `name`, 0 being the outermost
+    pub fn frame_of(&self, name: &str) -> Option<usize> {
+        self.frames
+            .iter()
+            .rposition(|frame| frame.contains_key(name))
+    }
+
+    // Combines each binding with the same binding in `other`, which must have the same
+    // blocks open (e.g. the state at the end of another branch)
+    pub fn merge(&mut self, other: &Scopes<T>, f: impl Fn(&mut T, &T)) {
+        for (frame, other_frame) in self.frames.iter_mut().zip(&other.frames) {
+            for (name, value) in frame.iter_mut() {
+                if let Some(other_value) = other_frame.get(name) {
+                    f(value, other_value);
+                }
+            }
+        }
+    }
+
+    // Not visible, but was declared in a block that has ended
+    pub fn is_out_of_scope(&self, name: &str) -> bool {
+        !self.contains(name) && self.dropped.contains(name)
+    }
+
+    // Visible bindings, inner ones shadowing outer ones
+    pub fn visible(&self) -> HashMap<String, &T> {
+        let mut out = HashMap::new();
+        for frame in &self.frames {
+            for (name, value) in frame {
+                out.insert(name.clone(), value);
+            }
+        }
+        out
+    }
+}